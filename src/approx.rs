@@ -0,0 +1,443 @@
+//! Approximate equality for floats and everything built on them.
+//!
+//! `Vec3` and friends derive an exact `PartialEq`, which rarely holds after a rotation or a
+//! matrix product. `ApproxEq` compares with an absolute epsilon, a relative tolerance or a number
+//! of ULPs, and `assert_approx_eq!` reports every element that's off:
+//! ```
+//! use my_math::prelude::*;
+//! use my_math::assert_approx_eq;
+//!
+//! let mut v = Vec3::X;
+//! v.rot_quat(90., Vec3::Z);
+//! assert_approx_eq!(v, Vec3::Y, epsilon = 1e-6);
+//! assert_approx_eq!(0.1 + 0.2, 0.3f64, ulps = 1);
+//! ```
+use core::fmt::{self, Debug, Display, Formatter};
+use core::ops::Sub;
+
+use crate::matrix::{DMatrix, Matrix};
+use crate::quat::{DQuaternion, Quaternion};
+use crate::vec::{DVec2, DVec3, DVec4, Vec2, Vec3, Vec4};
+
+/// How close two values have to be, every mode also accepts values within `epsilon` of each other
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tolerance<E> {
+    /// `|a - b| <= epsilon`
+    Absolute { epsilon: E },
+    /// `|a - b| <= max_relative * max(|a|, |b|)`
+    Relative { epsilon: E, max_relative: E },
+    /// At most `max_ulps` representable floats between `a` and `b`, never across zero
+    Ulps { epsilon: E, max_ulps: u32 },
+}
+
+impl<E: Debug> Display for Tolerance<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Tolerance::Absolute { epsilon } => write!(f, "absolute, epsilon = {epsilon:?}"),
+            Tolerance::Relative { epsilon, max_relative } => {
+                write!(f, "relative, epsilon = {epsilon:?}, max_relative = {max_relative:?}")
+            }
+            Tolerance::Ulps { epsilon, max_ulps } => write!(f, "ulps, epsilon = {epsilon:?}, max_ulps = {max_ulps}"),
+        }
+    }
+}
+
+pub trait ApproxEq {
+    /// The scalar the tolerances are given in
+    type Epsilon: Copy + Debug;
+    const DEFAULT_EPSILON: Self::Epsilon;
+    const DEFAULT_MAX_RELATIVE: Self::Epsilon;
+    const DEFAULT_MAX_ULPS: u32 = 4;
+
+    fn eq_within(&self, other: &Self, tolerance: Tolerance<Self::Epsilon>) -> bool;
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        self.eq_within(other, Tolerance::Absolute { epsilon })
+    }
+    fn relative_eq(&self, other: &Self, epsilon: Self::Epsilon, max_relative: Self::Epsilon) -> bool {
+        self.eq_within(other, Tolerance::Relative { epsilon, max_relative })
+    }
+    fn ulps_eq(&self, other: &Self, epsilon: Self::Epsilon, max_ulps: u32) -> bool {
+        self.eq_within(other, Tolerance::Ulps { epsilon, max_ulps })
+    }
+    /// `relative_eq` with the default tolerances
+    fn approx_eq(&self, other: &Self) -> bool {
+        self.relative_eq(other, Self::DEFAULT_EPSILON, Self::DEFAULT_MAX_RELATIVE)
+    }
+}
+
+macro_rules! implFloat {
+    ($($float:ty => $bits:ty),+) => {
+        $(
+            impl ApproxEq for $float {
+                type Epsilon = $float;
+                const DEFAULT_EPSILON: $float = <$float>::EPSILON;
+                const DEFAULT_MAX_RELATIVE: $float = <$float>::EPSILON;
+
+                fn eq_within(&self, other: &Self, tolerance: Tolerance<$float>) -> bool {
+                    let (a, b) = (*self, *other);
+                    // catches equal infinities, which the differences below turn into NaN
+                    if a == b {
+                        return true;
+                    }
+                    let diff = (a - b).abs();
+                    let epsilon = match tolerance {
+                        Tolerance::Absolute { epsilon }
+                        | Tolerance::Relative { epsilon, .. }
+                        | Tolerance::Ulps { epsilon, .. } => epsilon,
+                    };
+                    if diff <= epsilon {
+                        return true;
+                    }
+                    match tolerance {
+                        Tolerance::Absolute { .. } => false,
+                        Tolerance::Relative { max_relative, .. } => {
+                            a.is_finite() && b.is_finite() && diff <= a.abs().max(b.abs()) * max_relative
+                        }
+                        Tolerance::Ulps { max_ulps, .. } => {
+                            if a.is_nan() || b.is_nan() || a.is_sign_negative() != b.is_sign_negative() {
+                                return false;
+                            }
+                            // same sign, so the bit patterns are ordered like the values
+                            (a.to_bits() as $bits).abs_diff(b.to_bits() as $bits) as u64 <= max_ulps as u64
+                        }
+                    }
+                }
+            }
+        )+
+    };
+}
+implFloat!(f32 => i32, f64 => i64);
+
+/// Where an element sits in its value, printed by `assert_approx_eq!`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ElementLabel {
+    Scalar,
+    Field(&'static str),
+    Entry { row: usize, col: usize },
+}
+
+impl Display for ElementLabel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ElementLabel::Scalar => f.write_str("value"),
+            ElementLabel::Field(name) => f.write_str(name),
+            ElementLabel::Entry { row, col } => write!(f, "[{row}][{col}]"),
+        }
+    }
+}
+
+/// The scalars a value is compared by, in order
+pub trait Elements: Copy {
+    type Scalar: ApproxEq<Epsilon = Self::Scalar> + Copy + Debug + Sub<Output = Self::Scalar>;
+
+    fn element_count(&self) -> usize;
+    fn element(&self, index: usize) -> Self::Scalar;
+    fn label(&self, index: usize) -> ElementLabel;
+    /// The representation of `other` closest to `self`, only differs for quaternions where q and -q are the same rotation
+    fn nearest(&self, other: &Self) -> Self {
+        *other
+    }
+}
+
+macro_rules! implScalarElements {
+    ($($float:ty),+) => {
+        $(
+            impl Elements for $float {
+                type Scalar = $float;
+
+                fn element_count(&self) -> usize {
+                    1
+                }
+                fn element(&self, _: usize) -> $float {
+                    *self
+                }
+                fn label(&self, _: usize) -> ElementLabel {
+                    ElementLabel::Scalar
+                }
+            }
+        )+
+    };
+}
+implScalarElements!(f32, f64);
+
+/// Compares element by element, `implElements!(Vec3 => f32 [x, y, z])`
+macro_rules! implElements {
+    ($($type:ty => $scalar:ty [$($field:ident),+]),+) => {
+        $(
+            impl Elements for $type {
+                type Scalar = $scalar;
+
+                fn element_count(&self) -> usize {
+                    [$(stringify!($field)),+].len()
+                }
+                fn element(&self, index: usize) -> $scalar {
+                    [$(self.$field),+][index]
+                }
+                fn label(&self, index: usize) -> ElementLabel {
+                    ElementLabel::Field([$(stringify!($field)),+][index])
+                }
+            }
+            impl ApproxEq for $type {
+                type Epsilon = $scalar;
+                const DEFAULT_EPSILON: $scalar = <$scalar>::EPSILON;
+                const DEFAULT_MAX_RELATIVE: $scalar = <$scalar>::EPSILON;
+
+                fn eq_within(&self, other: &Self, tolerance: Tolerance<$scalar>) -> bool {
+                    elementwise(self, other, tolerance)
+                }
+            }
+        )+
+    };
+}
+implElements!(
+    Vec2 => f32 [x, y],
+    Vec3 => f32 [x, y, z],
+    Vec4 => f32 [x, y, z, w],
+    DVec2 => f64 [x, y],
+    DVec3 => f64 [x, y, z],
+    DVec4 => f64 [x, y, z, w]
+);
+
+fn elementwise<T: Elements>(a: &T, b: &T, tolerance: Tolerance<T::Scalar>) -> bool {
+    (0..a.element_count()).all(|i| a.element(i).eq_within(&b.element(i), tolerance))
+}
+
+macro_rules! implMatrix {
+    ($($mat:ident => $scalar:ty),+) => {
+        $(
+            impl<const ROW: usize, const COL: usize> Elements for $mat<ROW, COL> {
+                type Scalar = $scalar;
+
+                fn element_count(&self) -> usize {
+                    ROW * COL
+                }
+                fn element(&self, index: usize) -> $scalar {
+                    self.data[index / COL][index % COL]
+                }
+                fn label(&self, index: usize) -> ElementLabel {
+                    ElementLabel::Entry { row: index / COL, col: index % COL }
+                }
+            }
+            impl<const ROW: usize, const COL: usize> ApproxEq for $mat<ROW, COL> {
+                type Epsilon = $scalar;
+                const DEFAULT_EPSILON: $scalar = <$scalar>::EPSILON;
+                const DEFAULT_MAX_RELATIVE: $scalar = <$scalar>::EPSILON;
+
+                fn eq_within(&self, other: &Self, tolerance: Tolerance<$scalar>) -> bool {
+                    elementwise(self, other, tolerance)
+                }
+            }
+        )+
+    };
+}
+implMatrix!(Matrix => f32, DMatrix => f64);
+
+/// q and -q rotate the same way, so either sign of `other` may match
+macro_rules! implQuaternion {
+    ($($quat:ident => $scalar:ty),+) => {
+        $(
+            impl Elements for $quat {
+                type Scalar = $scalar;
+
+                fn element_count(&self) -> usize {
+                    4
+                }
+                fn element(&self, index: usize) -> $scalar {
+                    [self.w, self.v.x, self.v.y, self.v.z][index]
+                }
+                fn label(&self, index: usize) -> ElementLabel {
+                    ElementLabel::Field(["w", "x", "y", "z"][index])
+                }
+                fn nearest(&self, other: &Self) -> Self {
+                    if self.w * other.w + self.v.dot(other.v) < 0. {
+                        $quat::new(-other.w, other.v * -1.)
+                    } else {
+                        *other
+                    }
+                }
+            }
+            impl ApproxEq for $quat {
+                type Epsilon = $scalar;
+                const DEFAULT_EPSILON: $scalar = <$scalar>::EPSILON;
+                const DEFAULT_MAX_RELATIVE: $scalar = <$scalar>::EPSILON;
+
+                fn eq_within(&self, other: &Self, tolerance: Tolerance<$scalar>) -> bool {
+                    let negated = $quat::new(-other.w, other.v * -1.);
+                    elementwise(self, other, tolerance) || elementwise(self, &negated, tolerance)
+                }
+            }
+        )+
+    };
+}
+implQuaternion!(Quaternion => f32, DQuaternion => f64);
+
+/// The options `assert_approx_eq!` accepts, whatever is left out falls back to the type's defaults
+#[doc(hidden)]
+pub struct ToleranceArgs<E> {
+    pub epsilon: Option<E>,
+    pub max_relative: Option<E>,
+    pub ulps: Option<u32>,
+}
+
+impl<E: Copy> ToleranceArgs<E> {
+    pub fn for_value<T: ApproxEq<Epsilon = E>>(_: &T) -> Self {
+        ToleranceArgs { epsilon: None, max_relative: None, ulps: None }
+    }
+    /// `ulps` picks the ULPs mode, `max_relative` the relative one, `epsilon` alone the absolute one
+    pub fn resolve<T: ApproxEq<Epsilon = E>>(self, _: &T) -> Tolerance<E> {
+        let epsilon = self.epsilon.unwrap_or(T::DEFAULT_EPSILON);
+        match (self.ulps, self.max_relative, self.epsilon) {
+            (Some(max_ulps), _, _) => Tolerance::Ulps { epsilon, max_ulps },
+            (None, Some(max_relative), _) => Tolerance::Relative { epsilon, max_relative },
+            (None, None, Some(epsilon)) => Tolerance::Absolute { epsilon },
+            (None, None, None) => Tolerance::Relative { epsilon, max_relative: T::DEFAULT_MAX_RELATIVE },
+        }
+    }
+}
+
+/// Lists the elements outside the tolerance, one per line
+struct Mismatches<'a, T: Elements> {
+    left: &'a T,
+    right: T,
+    tolerance: Tolerance<T::Scalar>,
+}
+
+impl<T: Elements> Display for Mismatches<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let count = self.left.element_count();
+        let mut mismatched = 0;
+        for i in 0..count {
+            let (a, b) = (self.left.element(i), self.right.element(i));
+            if !a.eq_within(&b, self.tolerance) {
+                mismatched += 1;
+                writeln!(f, "  {:>8}: {a:?} vs {b:?} (diff {:?})", self.left.label(i), a - b)?;
+            }
+        }
+        write!(f, "{mismatched} of {count} elements differ")
+    }
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn approx_eq_failed<T>(left_expr: &str, right_expr: &str, left: &T, right: &T, tolerance: Tolerance<T::Scalar>) -> !
+where
+    T: Elements + ApproxEq<Epsilon = <T as Elements>::Scalar>,
+{
+    let mismatches = Mismatches { left, right: left.nearest(right), tolerance };
+    panic!("assertion `{left_expr} ≈ {right_expr}` failed ({tolerance})\n{mismatches}")
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn approx_ne_failed<E: Debug>(left_expr: &str, right_expr: &str, tolerance: Tolerance<E>) -> ! {
+    panic!("assertion `{left_expr} ≉ {right_expr}` failed ({tolerance}), every element is within tolerance")
+}
+
+/// Asserts two values are `ApproxEq`, listing each element that's off when they aren't.
+///
+/// With no options it's `relative_eq` with the type's defaults, `epsilon = e` alone compares
+/// absolutely, `max_relative = r` relatively and `ulps = n` by ULPs, each mode also takes `epsilon`.
+/// ```should_panic
+/// # use my_math::prelude::*;
+/// # use my_math::{assert_approx_eq, vec3};
+/// // panics with
+/// // assertion `vec3!(1., 2., 3.) ≈ vec3!(1., 2.5, 3.)` failed (absolute, epsilon = 0.1)
+/// //          y: 2.0 vs 2.5 (diff -0.5)
+/// // 1 of 3 elements differ
+/// assert_approx_eq!(vec3!(1., 2., 3.), vec3!(1., 2.5, 3.), epsilon = 0.1);
+/// ```
+#[macro_export]
+macro_rules! assert_approx_eq {
+    ($left:expr, $right:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                #[allow(unused_mut)]
+                let mut args = $crate::approx::ToleranceArgs::for_value(left);
+                $( args.$key = Some($value); )*
+                let tolerance = args.resolve(left);
+                if !$crate::approx::ApproxEq::eq_within(left, right, tolerance) {
+                    $crate::approx::approx_eq_failed(stringify!($left), stringify!($right), left, right, tolerance);
+                }
+            }
+        }
+    };
+}
+
+/// The opposite of `assert_approx_eq!`, takes the same options
+#[macro_export]
+macro_rules! assert_approx_ne {
+    ($left:expr, $right:expr $(, $key:ident = $value:expr)* $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                #[allow(unused_mut)]
+                let mut args = $crate::approx::ToleranceArgs::for_value(left);
+                $( args.$key = Some($value); )*
+                let tolerance = args.resolve(left);
+                if $crate::approx::ApproxEq::eq_within(left, right, tolerance) {
+                    $crate::approx::approx_ne_failed(stringify!($left), stringify!($right), tolerance);
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dvec3, new_mat, vec3};
+
+    #[test]
+    fn floats() {
+        assert!(1f32.abs_diff_eq(&1.05, 0.1));
+        assert!(!1f32.abs_diff_eq(&1.2, 0.1));
+        assert!(1e6f32.relative_eq(&1.000_01e6, 0., 1e-4));
+        assert!(!1e-6f32.relative_eq(&2e-6, 0., 0.1));
+        assert!((0.1f64 + 0.2).ulps_eq(&0.3, 0., 1));
+        assert!(!1f32.ulps_eq(&1.001, 0., 4));
+        assert!(!1e-30f32.ulps_eq(&-1e-30, 0., u32::MAX));
+        assert!(1e-30f32.ulps_eq(&-1e-30, 1e-20, 0));
+        assert!(f32::INFINITY.approx_eq(&f32::INFINITY));
+        assert!(!f32::INFINITY.relative_eq(&f32::MAX, 0., 1.));
+        assert!(!f32::NAN.approx_eq(&f32::NAN));
+    }
+
+    #[test]
+    fn composites() {
+        let mut v = Vec3::X;
+        v.rot_quat(90., Vec3::Z);
+        assert_ne!(v, Vec3::Y);
+        assert_approx_eq!(v, Vec3::Y, epsilon = 1e-6);
+        assert_approx_ne!(v, Vec3::Y, epsilon = 0.);
+        assert_approx_eq!(dvec3!(1., 2., 3.) * 0.1, dvec3!(0.1, 0.2, 0.3), ulps = 1);
+
+        let rot = new_mat!(2, 2, [0., -1., 1., 0.]);
+        assert_approx_eq!(rot * rot * rot * rot, Matrix::<2, 2>::identity());
+
+        let q = Quaternion::new(0.5, vec3!(0.5, -0.5, 0.5));
+        let neg = Quaternion::new(-0.5, vec3!(-0.5, 0.5, -0.500_000_1));
+        assert_approx_eq!(q, neg, epsilon = 1e-6);
+        assert_approx_ne!(q, Quaternion::new(0.5, vec3!(-0.5, 0.5, -0.5)), epsilon = 1e-6);
+        assert_approx_eq!(DQuaternion::from(q), DQuaternion::from(neg), max_relative = 1e-6);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn report() {
+        let result = std::panic::catch_unwind(|| {
+            assert_approx_eq!(new_mat!(2, 2, [1., 2., 3., 4.]), new_mat!(2, 2, [1., 2.5, 3., 4.]), epsilon = 0.1);
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.starts_with("assertion `new_mat!(2, 2, [1., 2., 3., 4.]) ≈"));
+        assert!(message.contains("(absolute, epsilon = 0.1)"));
+        assert!(message.contains("[0][1]: 2.0 vs 2.5 (diff -0.5)"));
+        assert!(message.ends_with("1 of 4 elements differ"));
+
+        let q = Quaternion::new(1., Vec3::ZERO);
+        let result = std::panic::catch_unwind(|| {
+            assert_approx_eq!(q, Quaternion::new(-0.9, Vec3::ZERO), epsilon = 0.01);
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("w: 1.0 vs 0.9"));
+    }
+}
@@ -0,0 +1,461 @@
+use core::cmp::Ordering;
+use core::fmt::{Display, Formatter, Result};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::bigint::BigInt;
+use crate::math;
+use crate::fraction::{Fraction, Ratio, SignedInt};
+
+#[macro_export]
+macro_rules! bfr {
+    ($num:expr) => {
+        BigFraction::new($num, 1)
+    };
+    ($num:expr,$den:expr) => {
+        BigFraction::new($num, $den)
+    };
+}
+
+/// Same as [`Fraction`] but backed by a [`BigInt`], so it never overflows
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigFraction {
+    numerator: BigInt,
+    denominator: BigInt,
+}
+impl BigFraction {
+    pub fn new(num: impl Into<BigInt>, den: impl Into<BigInt>) -> Self {
+        let den = den.into();
+        assert!(!den.is_zero());
+        Self {
+            numerator: num.into(),
+            denominator: den,
+        }
+        .simplify()
+    }
+    fn simplify(mut self) -> Self {
+        let max_factor = self.numerator.gcd(&self.denominator);
+
+        if self.denominator.is_negative() {
+            self.numerator = -self.numerator;
+            self.denominator = -self.denominator;
+        }
+        if !max_factor.is_one() {
+            self.numerator = &self.numerator / &max_factor;
+            self.denominator = &self.denominator / &max_factor;
+        }
+        self
+    }
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+    pub fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+    pub fn as_f64(&self) -> f64 {
+        // Scale so the quotient keeps 64 significant bits, then undo the scale in the exponent.
+        // Dividing the two f64 approximations directly gives inf/inf for huge values.
+        let shift = self.denominator.bits() as i64 - self.numerator.bits() as i64 + 64;
+        let quotient = if shift >= 0 {
+            &self.numerator.shl(shift as u64) / &self.denominator
+        } else {
+            &self.numerator / &self.denominator.shl((-shift) as u64)
+        };
+        let mut out = quotient.to_f64();
+        let mut shift = shift;
+        // powi only covers the normal exponent range, so scale in steps
+        while shift > 1000 {
+            out *= math::powi(2., -1000);
+            shift -= 1000;
+        }
+        while shift < -1000 {
+            out *= math::powi(2., 1000);
+            shift += 1000;
+        }
+        out * math::powi(2., -shift as i32)
+    }
+    pub fn as_mixed(&self) -> String {
+        let rem = &self.numerator % &self.denominator;
+        if self.denominator.is_one() {
+            return format!("{}", self.numerator);
+        }
+        if self.numerator < self.denominator {
+            return format!("({}/{})", self.numerator, self.denominator);
+        }
+        let whole_part = &(&self.numerator - &rem) / &self.denominator;
+        format!("{whole_part}({rem}/{})", self.denominator)
+    }
+    pub fn abs(&self) -> BigFraction {
+        Self {
+            numerator: self.numerator.abs(),
+            denominator: self.denominator.clone(),
+        }
+    }
+    /// Converts back to a [`Fraction`], `None` if either part doesn't fit in an `i128`
+    pub fn to_fraction(&self) -> Option<Fraction> {
+        Some(Fraction::new(self.numerator.to_i128()?, self.denominator.to_i128()?))
+    }
+}
+
+//==========================================================//
+
+impl Sub<&BigFraction> for &BigFraction {
+    type Output = BigFraction;
+
+    fn sub(self, rhs: &BigFraction) -> Self::Output {
+        if self.denominator == rhs.denominator {
+            BigFraction {
+                numerator: &self.numerator - &rhs.numerator,
+                denominator: self.denominator.clone(),
+            }
+            .simplify()
+        } else {
+            BigFraction {
+                numerator: &self.numerator * &rhs.denominator - &rhs.numerator * &self.denominator,
+                denominator: &self.denominator * &rhs.denominator,
+            }
+            .simplify()
+        }
+    }
+}
+impl Add<&BigFraction> for &BigFraction {
+    type Output = BigFraction;
+
+    fn add(self, rhs: &BigFraction) -> Self::Output {
+        if self.denominator == rhs.denominator {
+            BigFraction {
+                numerator: &self.numerator + &rhs.numerator,
+                denominator: self.denominator.clone(),
+            }
+            .simplify()
+        } else {
+            BigFraction {
+                numerator: &self.numerator * &rhs.denominator + &rhs.numerator * &self.denominator,
+                denominator: &self.denominator * &rhs.denominator,
+            }
+            .simplify()
+        }
+    }
+}
+impl Mul<&BigFraction> for &BigFraction {
+    type Output = BigFraction;
+
+    fn mul(self, rhs: &BigFraction) -> Self::Output {
+        BigFraction {
+            numerator: &self.numerator * &rhs.numerator,
+            denominator: &self.denominator * &rhs.denominator,
+        }
+        .simplify()
+    }
+}
+impl Div<&BigFraction> for &BigFraction {
+    type Output = BigFraction;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: &BigFraction) -> Self::Output {
+        assert!(!rhs.numerator.is_zero());
+        BigFraction {
+            numerator: &self.numerator * &rhs.denominator,
+            denominator: &self.denominator * &rhs.numerator,
+        }
+        .simplify()
+    }
+}
+
+macro_rules! forwardBinop {
+    ($($trait:ident, $fn:ident, $assign_trait:ident, $assign_fn:ident);*) => { $(
+        impl $trait<BigFraction> for BigFraction {
+            type Output = BigFraction;
+
+            fn $fn(self, rhs: BigFraction) -> Self::Output {
+                (&self).$fn(&rhs)
+            }
+        }
+        impl $trait<&BigFraction> for BigFraction {
+            type Output = BigFraction;
+
+            fn $fn(self, rhs: &BigFraction) -> Self::Output {
+                (&self).$fn(rhs)
+            }
+        }
+        impl $trait<BigFraction> for &BigFraction {
+            type Output = BigFraction;
+
+            fn $fn(self, rhs: BigFraction) -> Self::Output {
+                self.$fn(&rhs)
+            }
+        }
+        impl $assign_trait<BigFraction> for BigFraction {
+            fn $assign_fn(&mut self, rhs: BigFraction) {
+                *self = (&*self).$fn(&rhs);
+            }
+        }
+        impl $assign_trait<&BigFraction> for BigFraction {
+            fn $assign_fn(&mut self, rhs: &BigFraction) {
+                *self = (&*self).$fn(rhs);
+            }
+        }
+        )*
+    };
+}
+forwardBinop!(
+    Sub, sub, SubAssign, sub_assign;
+    Add, add, AddAssign, add_assign;
+    Mul, mul, MulAssign, mul_assign;
+    Div, div, DivAssign, div_assign
+);
+
+//==========================================================//
+
+// Mixed operands are promoted losslessly and go through the BigFraction impls above
+macro_rules! implSub {
+    ($($type:ty),*) => { $(
+        impl Sub<$type> for BigFraction {
+            type Output = BigFraction;
+
+            fn sub(self, rhs: $type) -> Self::Output {
+                self - BigFraction::from(rhs)
+            }
+        }
+        impl Sub<BigFraction> for $type {
+            type Output = BigFraction;
+
+            fn sub(self, rhs: BigFraction) -> Self::Output {
+                BigFraction::from(self) - rhs
+            }
+        }
+        )*
+    };
+}
+implSub!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, Fraction);
+
+macro_rules! implAdd {
+    ($($type:ty),*) => { $(
+        impl Add<$type> for BigFraction {
+            type Output = BigFraction;
+
+            fn add(self, rhs: $type) -> Self::Output {
+                self + BigFraction::from(rhs)
+            }
+        }
+        impl Add<BigFraction> for $type {
+            type Output = BigFraction;
+
+            fn add(self, rhs: BigFraction) -> Self::Output {
+                BigFraction::from(self) + rhs
+            }
+        }
+        )*
+    };
+}
+implAdd!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, Fraction);
+
+macro_rules! implMul {
+    ($($type:ty),*) => { $(
+        impl Mul<$type> for BigFraction {
+            type Output = BigFraction;
+
+            fn mul(self, rhs: $type) -> Self::Output {
+                self * BigFraction::from(rhs)
+            }
+        }
+        impl Mul<BigFraction> for $type {
+            type Output = BigFraction;
+
+            fn mul(self, rhs: BigFraction) -> Self::Output {
+                BigFraction::from(self) * rhs
+            }
+        }
+        )*
+    };
+}
+implMul!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, Fraction);
+
+macro_rules! implDiv {
+    ($($type:ty),*) => { $(
+        impl Div<BigFraction> for $type {
+            type Output = BigFraction;
+
+            fn div(self, rhs: BigFraction) -> Self::Output {
+                BigFraction::from(self) / rhs
+            }
+        }
+        impl Div<$type> for BigFraction {
+            type Output = BigFraction;
+
+            fn div(self, rhs: $type) -> Self::Output {
+                self / BigFraction::from(rhs)
+            }
+        }
+        )*
+    };
+}
+implDiv!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, Fraction);
+
+macro_rules! implAssign {
+    ($($type:ty),*) => { $(
+        impl SubAssign<$type> for BigFraction {
+            fn sub_assign(&mut self, rhs: $type) {
+                *self -= BigFraction::from(rhs);
+            }
+        }
+        impl AddAssign<$type> for BigFraction {
+            fn add_assign(&mut self, rhs: $type) {
+                *self += BigFraction::from(rhs);
+            }
+        }
+        impl MulAssign<$type> for BigFraction {
+            fn mul_assign(&mut self, rhs: $type) {
+                *self *= BigFraction::from(rhs);
+            }
+        }
+        impl DivAssign<$type> for BigFraction {
+            fn div_assign(&mut self, rhs: $type) {
+                *self /= BigFraction::from(rhs);
+            }
+        }
+        )*
+    };
+}
+implAssign!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, Fraction);
+
+//==========================================================//
+
+impl PartialOrd for BigFraction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for BigFraction {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.denominator == other.denominator {
+            self.numerator.cmp(&other.numerator)
+        } else {
+            (&self.numerator * &other.denominator).cmp(&(&other.numerator * &self.denominator))
+        }
+    }
+}
+macro_rules! implCmp {
+    ($($type:ty),*) => { $(
+        impl PartialEq<$type> for BigFraction {
+            fn eq(&self, other: &$type) -> bool {
+                self.partial_cmp(other) == Some(Ordering::Equal)
+            }
+        }
+        impl PartialOrd<$type> for BigFraction {
+            fn partial_cmp(&self, other: &$type) -> Option<Ordering> {
+                Some(self.cmp(&BigFraction::from(*other)))
+            }
+        }
+
+        impl PartialEq<BigFraction> for $type {
+            fn eq(&self, other: &BigFraction) -> bool {
+                other == self
+            }
+        }
+        impl PartialOrd<BigFraction> for $type {
+            fn partial_cmp(&self, other: &BigFraction) -> Option<Ordering> {
+                Some(BigFraction::from(*self).cmp(other))
+            }
+        }
+        )*
+    };
+}
+implCmp!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, Fraction);
+
+//==========================================================//
+
+macro_rules! implFrom {
+    ($($type:ty),*) => { $(
+        impl From<$type> for BigFraction {
+            fn from(value: $type) -> Self {
+                BigFraction {
+                    numerator: BigInt::from(value),
+                    denominator: BigInt::one(),
+                }
+            }
+        }
+        )*
+    };
+}
+implFrom!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
+
+impl From<BigInt> for BigFraction {
+    fn from(value: BigInt) -> Self {
+        BigFraction {
+            numerator: value,
+            denominator: BigInt::one(),
+        }
+    }
+}
+impl<T: SignedInt> From<Ratio<T>> for BigFraction {
+    fn from(value: Ratio<T>) -> Self {
+        // Already in lowest terms with a positive denominator
+        BigFraction {
+            numerator: BigInt::from(value.numerator.to_i128()),
+            denominator: BigInt::from(value.denominator.to_i128()),
+        }
+    }
+}
+
+//==========================================================//
+
+impl Display for BigFraction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        if self.denominator.is_one() {
+            write!(f, "{}", self.numerator)?;
+        } else {
+            write!(f, "({}/{})", self.numerator, self.denominator)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fr;
+
+    #[test]
+    fn test() {
+        assert_eq!(bfr!(10, 2), bfr!(5));
+        assert_eq!(bfr!(10, -4), bfr!(-5, 2));
+        assert!(bfr!(10, 2) > bfr!(10, 3));
+        assert!(bfr!(10, 2) == 5);
+        assert!(6 > bfr!(10, 3));
+        assert!(bfr!(1, 3) < fr!(1, 2));
+
+        let mut fr = bfr!(7, 3);
+        assert_eq!(format!("{fr}"), "(7/3)");
+        assert_eq!(fr.as_mixed(), "2(1/3)");
+        fr += 7;
+        assert_eq!(fr, bfr!(28, 3));
+        fr -= fr!(1, 3);
+        assert_eq!(fr, 9);
+        fr = fr / 4u8 * bfr!(2, 9);
+        assert_eq!(format!("{fr}"), "(1/2)");
+        fr = 1 - fr;
+        assert_eq!(fr.to_fraction(), Some(fr!(1, 2)));
+        assert_eq!(bfr!(1, 3).as_f64(), 1. / 3.);
+    }
+
+    #[test]
+    fn past_i128() {
+        // 1/2 + 1/3 + ... + 1/120 has a denominator far beyond i128
+        let mut sum = bfr!(0);
+        for i in 2..=120 {
+            sum += bfr!(1, i);
+        }
+        assert_eq!(sum.to_fraction(), None);
+        assert!((sum.as_f64() - 4.368_868_287_353_395).abs() < 1e-12);
+
+        let promoted = BigFraction::from(fr!(i128::MAX, 3));
+        let squared = &promoted * &promoted;
+        assert_eq!(squared / promoted, fr!(i128::MAX, 3));
+    }
+}
@@ -0,0 +1,557 @@
+use core::cmp::Ordering;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use crate::math;
+
+/// Arbitrary-precision signed integer.
+///
+/// Stored as a sign and a little-endian magnitude of `u32` limbs without trailing zero limbs,
+/// so zero is an empty magnitude and is never negative.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    mag: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt { negative: false, mag: vec![] }
+    }
+    pub fn one() -> Self {
+        BigInt { negative: false, mag: vec![1] }
+    }
+    fn from_mag(negative: bool, mut mag: Vec<u32>) -> Self {
+        trim(&mut mag);
+        let negative = negative && !mag.is_empty();
+        BigInt { negative, mag }
+    }
+    pub fn is_zero(&self) -> bool {
+        self.mag.is_empty()
+    }
+    pub fn is_one(&self) -> bool {
+        !self.negative && self.mag == [1]
+    }
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+    pub fn is_positive(&self) -> bool {
+        !self.negative && !self.mag.is_empty()
+    }
+    pub fn abs(&self) -> BigInt {
+        BigInt { negative: false, mag: self.mag.clone() }
+    }
+    pub fn signum(&self) -> i32 {
+        if self.negative {
+            -1
+        } else if self.mag.is_empty() {
+            0
+        } else {
+            1
+        }
+    }
+    /// Number of significant bits in the magnitude
+    pub fn bits(&self) -> u64 {
+        match self.mag.last() {
+            None => 0,
+            Some(top) => self.mag.len() as u64 * 32 - top.leading_zeros() as u64,
+        }
+    }
+    /// Truncating division returning `(quotient, remainder)`, the remainder has the sign of `self`
+    pub fn div_rem(&self, rhs: &BigInt) -> (BigInt, BigInt) {
+        assert!(!rhs.is_zero(), "division by zero");
+        let (q, r) = divrem_mag(&self.mag, &rhs.mag);
+        (
+            BigInt::from_mag(self.negative != rhs.negative, q),
+            BigInt::from_mag(self.negative, r),
+        )
+    }
+    /// Floor division, rounds the quotient towards negative infinity
+    pub fn div_floor(&self, rhs: &BigInt) -> BigInt {
+        let (q, r) = self.div_rem(rhs);
+        if !r.is_zero() && (r.negative != rhs.negative) {
+            q - BigInt::one()
+        } else {
+            q
+        }
+    }
+    pub fn gcd(&self, rhs: &BigInt) -> BigInt {
+        let mut a = self.abs();
+        let mut b = rhs.abs();
+        while !b.is_zero() {
+            let r = BigInt::from_mag(false, divrem_mag(&a.mag, &b.mag).1);
+            a = b;
+            b = r;
+        }
+        a
+    }
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut out = BigInt::one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                out = &out * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        out
+    }
+    pub fn shl(&self, bits: u64) -> BigInt {
+        BigInt::from_mag(self.negative, shl_mag(&self.mag, bits))
+    }
+    /// Shifts the magnitude right, truncating towards zero
+    pub fn shr(&self, bits: u64) -> BigInt {
+        BigInt::from_mag(self.negative, shr_mag(&self.mag, bits))
+    }
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.mag.len() > 4 {
+            return None;
+        }
+        let mut out: u128 = 0;
+        for (i, limb) in self.mag.iter().enumerate() {
+            out |= (*limb as u128) << (32 * i);
+        }
+        if self.negative {
+            if out <= i128::MAX as u128 + 1 {
+                Some((out as i128).wrapping_neg())
+            } else {
+                None
+            }
+        } else {
+            i128::try_from(out).ok()
+        }
+    }
+    pub fn to_f64(&self) -> f64 {
+        // Only the top 64 bits matter for an f64, everything below is scaled in by the exponent.
+        let bits = self.bits();
+        let (top, exp) = if bits > 64 {
+            (shr_mag(&self.mag, bits - 64), (bits - 64) as i32)
+        } else {
+            (self.mag.clone(), 0)
+        };
+        let mut out = 0.;
+        for limb in top.iter().rev() {
+            out = out * 4294967296. + *limb as f64;
+        }
+        out *= math::powi(2., exp);
+        if self.negative {
+            -out
+        } else {
+            out
+        }
+    }
+}
+
+//==========================================================//
+
+fn trim(mag: &mut Vec<u32>) {
+    while mag.last() == Some(&0) {
+        mag.pop();
+    }
+}
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+    for i in (0..a.len()).rev() {
+        if a[i] != b[i] {
+            return a[i].cmp(&b[i]);
+        }
+    }
+    Ordering::Equal
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut out = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, limb) in long.iter().enumerate() {
+        let sum = *limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        out.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry != 0 {
+        out.push(carry as u32);
+    }
+    out
+}
+
+/// Expects `a >= b`
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut out = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, limb) in a.iter().enumerate() {
+        let mut diff = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        } else {
+            borrow = 0;
+        }
+        out.push(diff as u32);
+    }
+    debug_assert_eq!(borrow, 0);
+    trim(&mut out);
+    out
+}
+
+fn mul_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut out = vec![0u32; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, y) in b.iter().enumerate() {
+            let prod = *x as u64 * *y as u64 + out[i + j] as u64 + carry;
+            out[i + j] = prod as u32;
+            carry = prod >> 32;
+        }
+        out[i + b.len()] = carry as u32;
+    }
+    trim(&mut out);
+    out
+}
+
+fn shl_mag(a: &[u32], bits: u64) -> Vec<u32> {
+    if a.is_empty() {
+        return vec![];
+    }
+    let limbs = (bits / 32) as usize;
+    let bits = (bits % 32) as u32;
+    let mut out = vec![0u32; limbs];
+    if bits == 0 {
+        out.extend_from_slice(a);
+    } else {
+        let mut carry = 0u32;
+        for limb in a {
+            out.push((limb << bits) | carry);
+            carry = limb >> (32 - bits);
+        }
+        out.push(carry);
+    }
+    trim(&mut out);
+    out
+}
+
+fn shr_mag(a: &[u32], bits: u64) -> Vec<u32> {
+    let limbs = (bits / 32) as usize;
+    if limbs >= a.len() {
+        return vec![];
+    }
+    let bits = (bits % 32) as u32;
+    let a = &a[limbs..];
+    let mut out = Vec::with_capacity(a.len());
+    if bits == 0 {
+        out.extend_from_slice(a);
+    } else {
+        for i in 0..a.len() {
+            let high = a.get(i + 1).map_or(0, |next| next << (32 - bits));
+            out.push((a[i] >> bits) | high);
+        }
+    }
+    trim(&mut out);
+    out
+}
+
+fn divrem_small(a: &[u32], b: u32) -> (Vec<u32>, u32) {
+    let mut out = vec![0u32; a.len()];
+    let mut rem = 0u64;
+    for i in (0..a.len()).rev() {
+        let cur = (rem << 32) | a[i] as u64;
+        out[i] = (cur / b as u64) as u32;
+        rem = cur % b as u64;
+    }
+    trim(&mut out);
+    (out, rem as u32)
+}
+
+/// Long division of magnitudes (Knuth, TAOCP vol. 2, algorithm D)
+fn divrem_mag(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if cmp_mag(a, b) == Ordering::Less {
+        return (vec![], a.to_vec());
+    }
+    if b.len() == 1 {
+        let (q, r) = divrem_small(a, b[0]);
+        return (q, if r == 0 { vec![] } else { vec![r] });
+    }
+    // Normalize so the top limb of the divisor has its high bit set, that keeps the
+    // quotient digit estimate off by at most two.
+    let shift = b[b.len() - 1].leading_zeros() as u64;
+    let v = shl_mag(b, shift);
+    let mut u = shl_mag(a, shift);
+    u.resize(a.len() + 1, 0);
+
+    let n = v.len();
+    let m = u.len() - n - 1;
+    let mut q = vec![0u32; m + 1];
+    const BASE: u64 = 1 << 32;
+
+    for j in (0..=m).rev() {
+        let num = ((u[j + n] as u64) << 32) | u[j + n - 1] as u64;
+        let mut qhat = num / v[n - 1] as u64;
+        let mut rhat = num % v[n - 1] as u64;
+        while qhat >= BASE || qhat * v[n - 2] as u64 > ((rhat << 32) | u[j + n - 2] as u64) {
+            qhat -= 1;
+            rhat += v[n - 1] as u64;
+            if rhat >= BASE {
+                break;
+            }
+        }
+
+        let mut borrow = 0i64;
+        let mut carry = 0u64;
+        for i in 0..n {
+            let prod = qhat * v[i] as u64 + carry;
+            carry = prod >> 32;
+            let diff = u[i + j] as i64 - borrow - (prod & 0xffff_ffff) as i64;
+            u[i + j] = diff as u32;
+            borrow = (diff < 0) as i64;
+        }
+        let diff = u[j + n] as i64 - borrow - carry as i64;
+        u[j + n] = diff as u32;
+
+        if diff < 0 {
+            // Estimate was one too large, add the divisor back
+            qhat -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = u[i + j] as u64 + v[i] as u64 + carry;
+                u[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            u[j + n] = u[j + n].wrapping_add(carry as u32);
+        }
+        q[j] = qhat as u32;
+    }
+    trim(&mut q);
+    u.truncate(n);
+    (q, shr_mag(&u, shift))
+}
+
+//==========================================================//
+
+impl Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, rhs: &BigInt) -> Self::Output {
+        if self.negative == rhs.negative {
+            return BigInt::from_mag(self.negative, add_mag(&self.mag, &rhs.mag));
+        }
+        match cmp_mag(&self.mag, &rhs.mag) {
+            Ordering::Equal => BigInt::zero(),
+            Ordering::Greater => BigInt::from_mag(self.negative, sub_mag(&self.mag, &rhs.mag)),
+            Ordering::Less => BigInt::from_mag(rhs.negative, sub_mag(&rhs.mag, &self.mag)),
+        }
+    }
+}
+impl Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, rhs: &BigInt) -> Self::Output {
+        self + &(-rhs)
+    }
+}
+impl Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, rhs: &BigInt) -> Self::Output {
+        BigInt::from_mag(self.negative != rhs.negative, mul_mag(&self.mag, &rhs.mag))
+    }
+}
+impl Div<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn div(self, rhs: &BigInt) -> Self::Output {
+        self.div_rem(rhs).0
+    }
+}
+impl Rem<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn rem(self, rhs: &BigInt) -> Self::Output {
+        self.div_rem(rhs).1
+    }
+}
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> Self::Output {
+        BigInt::from_mag(!self.negative, self.mag.clone())
+    }
+}
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> Self::Output {
+        BigInt::from_mag(!self.negative, self.mag)
+    }
+}
+
+macro_rules! forwardBinop {
+    ($($trait:ident, $fn:ident);*) => { $(
+        impl $trait<BigInt> for BigInt {
+            type Output = BigInt;
+
+            fn $fn(self, rhs: BigInt) -> Self::Output {
+                (&self).$fn(&rhs)
+            }
+        }
+        impl $trait<&BigInt> for BigInt {
+            type Output = BigInt;
+
+            fn $fn(self, rhs: &BigInt) -> Self::Output {
+                (&self).$fn(rhs)
+            }
+        }
+        impl $trait<BigInt> for &BigInt {
+            type Output = BigInt;
+
+            fn $fn(self, rhs: BigInt) -> Self::Output {
+                self.$fn(&rhs)
+            }
+        }
+        )*
+    };
+}
+forwardBinop!(Add, add; Sub, sub; Mul, mul; Div, div; Rem, rem);
+
+//==========================================================//
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_mag(&self.mag, &other.mag),
+            (true, true) => cmp_mag(&other.mag, &self.mag),
+        }
+    }
+}
+
+macro_rules! implFromSigned {
+    ($($type:ty),*) => { $(
+        impl From<$type> for BigInt {
+            fn from(value: $type) -> Self {
+                let negative = value < 0;
+                let mut abs = (value as i128).unsigned_abs();
+                let mut mag = vec![];
+                while abs != 0 {
+                    mag.push(abs as u32);
+                    abs >>= 32;
+                }
+                BigInt::from_mag(negative, mag)
+            }
+        }
+        )*
+    };
+}
+implFromSigned!(isize, i8, i16, i32, i64, i128);
+
+macro_rules! implFromUnsigned {
+    ($($type:ty),*) => { $(
+        impl From<$type> for BigInt {
+            fn from(value: $type) -> Self {
+                let mut abs = value as u128;
+                let mut mag = vec![];
+                while abs != 0 {
+                    mag.push(abs as u32);
+                    abs >>= 32;
+                }
+                BigInt::from_mag(false, mag)
+            }
+        }
+        )*
+    };
+}
+implFromUnsigned!(usize, u8, u16, u32, u64, u128);
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.pad_integral(true, "", "0");
+        }
+        // Peel off 9 decimal digits at a time
+        let mut chunks = vec![];
+        let mut mag = self.mag.clone();
+        while !mag.is_empty() {
+            let (q, r) = divrem_small(&mag, 1_000_000_000);
+            chunks.push(r);
+            mag = q;
+        }
+        let mut digits = format!("{}", chunks.pop().unwrap());
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:09}", chunk));
+        }
+        f.pad_integral(!self.negative, "", &digits)
+    }
+}
+impl Debug for BigInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arithmetic_matches_i128() {
+        let values: [i128; 9] = [
+            0,
+            1,
+            -1,
+            7,
+            -13,
+            4294967296,
+            -98765432109876543210,
+            12345678901234567890123456789,
+            i64::MAX as i128 * 3,
+        ];
+        for a in values {
+            for b in values {
+                let (x, y) = (BigInt::from(a), BigInt::from(b));
+                assert_eq!((&x + &y).to_i128(), Some(a + b), "{a} + {b}");
+                assert_eq!((&x - &y).to_i128(), Some(a - b), "{a} - {b}");
+                assert_eq!(x.cmp(&y), a.cmp(&b), "{a} cmp {b}");
+                if b != 0 {
+                    assert_eq!((&x / &y).to_i128(), Some(a / b), "{a} / {b}");
+                    assert_eq!((&x % &y).to_i128(), Some(a % b), "{a} % {b}");
+                }
+                if let Some(prod) = a.checked_mul(b) {
+                    assert_eq!((&x * &y).to_i128(), Some(prod), "{a} * {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_division() {
+        let a = BigInt::from(3).pow(200) + BigInt::from(17);
+        let b = BigInt::from(7).pow(50) - BigInt::from(1);
+        let (q, r) = a.div_rem(&b);
+        assert!(r < b);
+        assert_eq!(&q * &b + r, a);
+
+        let big = BigInt::from(10).pow(40);
+        assert_eq!(format!("{}", big), format!("1{}", "0".repeat(40)));
+        assert_eq!(format!("{}", -&big), format!("-1{}", "0".repeat(40)));
+        assert_eq!(BigInt::from(2).pow(100).gcd(&BigInt::from(6).pow(30)), BigInt::from(2).pow(30));
+        assert_eq!(BigInt::from(2).pow(80).to_f64(), 2f64.powi(80));
+        assert_eq!(BigInt::from(i128::MIN).to_i128(), Some(i128::MIN));
+        assert_eq!((BigInt::from(i128::MAX) + BigInt::one()).to_i128(), None);
+    }
+}
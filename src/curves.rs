@@ -0,0 +1,924 @@
+//! Parametric curves over `Vec2` and `Vec3`.
+//!
+//! Every curve implements `Curve`: a parameter range, the position and first derivative in it,
+//! and from those the nearest point to a query. Béziers and Hermite segments run over `0..=1`,
+//! a Catmull-Rom spline through `n` points over `0..=n - 1`, and a B-spline over the span of its
+//! knot vector. `ArcLength` reparameterizes any of them by distance travelled, so things can
+//! move along them at constant speed.
+use core::ops::{Add, Mul, Sub};
+
+use crate::math;
+use crate::vec::{Vec2, Vec3};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Samples per polynomial piece for nearest point searches and arc length tables
+const SAMPLES_PER_PIECE: usize = 16;
+/// Golden section steps refining a nearest point, enough to exhaust `f32`
+const GOLDEN_STEPS: usize = 32;
+const INV_PHI: f32 = 0.618_034;
+
+/// A point a curve can pass through
+pub trait CurvePoint: Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self> {
+    /// How many coordinates it has
+    const DIM: usize;
+
+    fn dot(self, rhs: Self) -> f32;
+    fn axis(self, index: usize) -> f32;
+    /// Component wise minimum
+    fn min(self, rhs: Self) -> Self;
+    /// Component wise maximum
+    fn max(self, rhs: Self) -> Self;
+    fn length(self) -> f32 {
+        math::sqrtf(self.dot(self))
+    }
+}
+macro_rules! implCurvePoint {
+    ($($type:ident [$($field:ident),+]),+) => {
+        $(
+            impl CurvePoint for $type {
+                const DIM: usize = [$(stringify!($field)),+].len();
+
+                fn dot(self, rhs: Self) -> f32 {
+                    $type::dot(&self, rhs)
+                }
+                fn axis(self, index: usize) -> f32 {
+                    [$(self.$field),+][index]
+                }
+                fn min(self, rhs: Self) -> Self {
+                    $type::min(&self, rhs)
+                }
+                fn max(self, rhs: Self) -> Self {
+                    $type::max(&self, rhs)
+                }
+            }
+        )+
+    };
+}
+implCurvePoint!(Vec2 [x, y], Vec3 [x, y, z]);
+
+/// The closest point of a curve to a query
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Nearest<P> {
+    /// Parameter of the closest point
+    pub t: f32,
+    pub point: P,
+    pub distance: f32,
+}
+
+/// A curve parameterized over an interval
+pub trait Curve {
+    type Point: CurvePoint;
+
+    /// The first and last parameter
+    fn domain(&self) -> (f32, f32);
+    /// Position at `t`, which is clamped to the domain
+    fn eval(&self, t: f32) -> Self::Point;
+    /// First derivative by `t`
+    fn derivative(&self, t: f32) -> Self::Point;
+    /// How many polynomial pieces it is made of, which sets how densely it is sampled
+    fn pieces(&self) -> usize {
+        1
+    }
+
+    /// Closest point to `point`, found by sampling and refining the best sample
+    fn nearest(&self, point: Self::Point) -> Nearest<Self::Point> {
+        let (start, end) = self.domain();
+        let samples = SAMPLES_PER_PIECE * self.pieces().max(1);
+        let at = |i: usize| start + (end - start) * i as f32 / samples as f32;
+        let dist = |t: f32| {
+            let d = self.eval(t) - point;
+            d.dot(d)
+        };
+        let (best, best_dist) = (0..=samples)
+            .map(|i| (i, dist(at(i))))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+
+        // golden section search between the neighbouring samples
+        let (mut lo, mut hi) = (at(best.saturating_sub(1)), at((best + 1).min(samples)));
+        let (mut a, mut b) = (hi - INV_PHI * (hi - lo), lo + INV_PHI * (hi - lo));
+        let (mut fa, mut fb) = (dist(a), dist(b));
+        for _ in 0..GOLDEN_STEPS {
+            if fa < fb {
+                hi = b;
+                (b, fb) = (a, fa);
+                a = hi - INV_PHI * (hi - lo);
+                fa = dist(a);
+            } else {
+                lo = a;
+                (a, fa) = (b, fb);
+                b = lo + INV_PHI * (hi - lo);
+                fb = dist(b);
+            }
+        }
+        let mut t = (lo + hi) / 2.;
+        if best_dist < dist(t) {
+            t = at(best);
+        }
+        let closest = self.eval(t);
+        Nearest { t, point: closest, distance: (closest - point).length() }
+    }
+}
+impl<C: Curve + ?Sized> Curve for &C {
+    type Point = C::Point;
+
+    fn domain(&self) -> (f32, f32) {
+        (**self).domain()
+    }
+    fn eval(&self, t: f32) -> C::Point {
+        (**self).eval(t)
+    }
+    fn derivative(&self, t: f32) -> C::Point {
+        (**self).derivative(t)
+    }
+    fn pieces(&self) -> usize {
+        (**self).pieces()
+    }
+}
+
+fn lerp<P: CurvePoint>(a: P, b: P, t: f32) -> P {
+    a + (b - a) * t
+}
+/// Roots of `a t² + b t + c` strictly inside `0..1`
+fn unit_roots(a: f32, b: f32, c: f32) -> impl Iterator<Item = f32> {
+    let roots = if a == 0. {
+        [(b != 0.).then(|| -c / b), None]
+    } else {
+        let disc = b * b - 4. * a * c;
+        if disc < 0. {
+            [None, None]
+        } else {
+            // the stable form, no cancellation when `a` is tiny
+            let q = -(b + math::sqrtf(disc).copysign(b)) / 2.;
+            [Some(q / a), (q != 0.).then(|| c / q)]
+        }
+    };
+    roots.into_iter().flatten().filter(|t| *t > 0. && *t < 1.)
+}
+
+// ------------------- Bézier -----------------------------
+
+/// A quadratic Bézier curve, from `p0` towards `p1` to `p2`
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuadraticBezier<P> {
+    pub p0: P,
+    pub p1: P,
+    pub p2: P,
+}
+impl<P: CurvePoint> QuadraticBezier<P> {
+    pub fn new(p0: P, p1: P, p2: P) -> Self {
+        QuadraticBezier { p0, p1, p2 }
+    }
+    /// The two halves either side of `t`, by de Casteljau
+    pub fn split(&self, t: f32) -> (Self, Self) {
+        let (a, b) = (lerp(self.p0, self.p1, t), lerp(self.p1, self.p2, t));
+        let mid = lerp(a, b, t);
+        (QuadraticBezier::new(self.p0, a, mid), QuadraticBezier::new(mid, b, self.p2))
+    }
+    /// Tight bounds, minimum and maximum corner
+    pub fn bounding_box(&self) -> (P, P) {
+        let (mut min, mut max) = (self.p0.min(self.p2), self.p0.max(self.p2));
+        let (a, b) = (self.p1 - self.p0, self.p2 - self.p1);
+        for i in 0..P::DIM {
+            for t in unit_roots(0., b.axis(i) - a.axis(i), a.axis(i)) {
+                let p = self.eval(t);
+                (min, max) = (min.min(p), max.max(p));
+            }
+        }
+        (min, max)
+    }
+}
+impl<P: CurvePoint> Curve for QuadraticBezier<P> {
+    type Point = P;
+
+    fn domain(&self) -> (f32, f32) {
+        (0., 1.)
+    }
+    fn eval(&self, t: f32) -> P {
+        let t = t.clamp(0., 1.);
+        let s = 1. - t;
+        self.p0 * (s * s) + self.p1 * (2. * s * t) + self.p2 * (t * t)
+    }
+    fn derivative(&self, t: f32) -> P {
+        let t = t.clamp(0., 1.);
+        (self.p1 - self.p0) * (2. * (1. - t)) + (self.p2 - self.p1) * (2. * t)
+    }
+}
+
+/// A cubic Bézier curve, from `p0` towards `p1` and `p2` to `p3`
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CubicBezier<P> {
+    pub p0: P,
+    pub p1: P,
+    pub p2: P,
+    pub p3: P,
+}
+impl<P: CurvePoint> CubicBezier<P> {
+    pub fn new(p0: P, p1: P, p2: P, p3: P) -> Self {
+        CubicBezier { p0, p1, p2, p3 }
+    }
+    /// The two halves either side of `t`, by de Casteljau
+    pub fn split(&self, t: f32) -> (Self, Self) {
+        let (a, b, c) = (lerp(self.p0, self.p1, t), lerp(self.p1, self.p2, t), lerp(self.p2, self.p3, t));
+        let (d, e) = (lerp(a, b, t), lerp(b, c, t));
+        let mid = lerp(d, e, t);
+        (CubicBezier::new(self.p0, a, d, mid), CubicBezier::new(mid, e, c, self.p3))
+    }
+    /// Tight bounds, minimum and maximum corner
+    pub fn bounding_box(&self) -> (P, P) {
+        let (mut min, mut max) = (self.p0.min(self.p3), self.p0.max(self.p3));
+        // the derivative over three, as a quadratic in t
+        let (a, b, c) = (self.p1 - self.p0, self.p2 - self.p1, self.p3 - self.p2);
+        let (qa, qb) = (a - b * 2. + c, (b - a) * 2.);
+        for i in 0..P::DIM {
+            for t in unit_roots(qa.axis(i), qb.axis(i), a.axis(i)) {
+                let p = self.eval(t);
+                (min, max) = (min.min(p), max.max(p));
+            }
+        }
+        (min, max)
+    }
+}
+impl<P: CurvePoint> Curve for CubicBezier<P> {
+    type Point = P;
+
+    fn domain(&self) -> (f32, f32) {
+        (0., 1.)
+    }
+    fn eval(&self, t: f32) -> P {
+        let t = t.clamp(0., 1.);
+        let s = 1. - t;
+        self.p0 * (s * s * s) + self.p1 * (3. * s * s * t) + self.p2 * (3. * s * t * t) + self.p3 * (t * t * t)
+    }
+    fn derivative(&self, t: f32) -> P {
+        let t = t.clamp(0., 1.);
+        let s = 1. - t;
+        (self.p1 - self.p0) * (3. * s * s) + (self.p2 - self.p1) * (6. * s * t) + (self.p3 - self.p2) * (3. * t * t)
+    }
+}
+
+// ------------------- Hermite -----------------------------
+
+/// A cubic from `p0` to `p1`, leaving with velocity `v0` and arriving with `v1`
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hermite<P> {
+    pub p0: P,
+    pub v0: P,
+    pub p1: P,
+    pub v1: P,
+}
+impl<P: CurvePoint> Hermite<P> {
+    pub fn new(p0: P, v0: P, p1: P, v1: P) -> Self {
+        Hermite { p0, v0, p1, v1 }
+    }
+    /// The same curve with Bézier control points
+    pub fn to_bezier(&self) -> CubicBezier<P> {
+        CubicBezier::new(self.p0, self.p0 + self.v0 * (1. / 3.), self.p1 - self.v1 * (1. / 3.), self.p1)
+    }
+}
+impl<P: CurvePoint> Curve for Hermite<P> {
+    type Point = P;
+
+    fn domain(&self) -> (f32, f32) {
+        (0., 1.)
+    }
+    fn eval(&self, t: f32) -> P {
+        let t = t.clamp(0., 1.);
+        let (t2, t3) = (t * t, t * t * t);
+        self.p0 * (2. * t3 - 3. * t2 + 1.)
+            + self.v0 * (t3 - 2. * t2 + t)
+            + self.p1 * (3. * t2 - 2. * t3)
+            + self.v1 * (t3 - t2)
+    }
+    fn derivative(&self, t: f32) -> P {
+        let t = t.clamp(0., 1.);
+        let t2 = t * t;
+        (self.p0 - self.p1) * (6. * t2 - 6. * t) + self.v0 * (3. * t2 - 4. * t + 1.) + self.v1 * (3. * t2 - 2. * t)
+    }
+}
+
+// ------------------- Catmull-Rom -----------------------------
+
+/// How a Catmull-Rom spline spaces its knots
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Parameterization {
+    /// Evenly, the classic spline, which can cusp and loop on uneven points
+    Uniform,
+    /// By the square root of the distance, never cusps or self intersects within a segment
+    Centripetal,
+    /// By the distance
+    Chordal,
+}
+impl Parameterization {
+    /// The exponent applied to the distance between points
+    pub fn alpha(self) -> f32 {
+        match self {
+            Parameterization::Uniform => 0.,
+            Parameterization::Centripetal => 0.5,
+            Parameterization::Chordal => 1.,
+        }
+    }
+    #[cfg(feature = "alloc")]
+    fn interval<P: CurvePoint>(self, from: P, to: P) -> f32 {
+        let distance = (to - from).length();
+        let interval = match self {
+            Parameterization::Uniform => 1.,
+            Parameterization::Centripetal => math::sqrtf(distance),
+            Parameterization::Chordal => distance,
+        };
+        // repeated points would divide by zero
+        interval.max(1e-6)
+    }
+}
+
+/// A spline through every one of its points
+///
+/// Segment `i` runs from `points[i]` to `points[i + 1]` over parameters `i..=i + 1`. The end
+/// segments are shaped by mirroring their neighbour across the end point.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "CatmullRomRaw<P>", bound(deserialize = "P: CurvePoint + serde::Deserialize<'de>"))
+)]
+pub struct CatmullRom<P> {
+    points: Vec<P>,
+    parameterization: Parameterization,
+}
+/// What a [`CatmullRom`] deserializes from before `new`'s checks
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CatmullRomRaw<P> {
+    points: Vec<P>,
+    parameterization: Parameterization,
+}
+#[cfg(feature = "serde")]
+impl<P: CurvePoint> TryFrom<CatmullRomRaw<P>> for CatmullRom<P> {
+    type Error = &'static str;
+
+    fn try_from(raw: CatmullRomRaw<P>) -> Result<Self, Self::Error> {
+        if raw.points.len() < 2 {
+            return Err("a Catmull-Rom spline needs at least two points");
+        }
+        Ok(CatmullRom::new(raw.points, raw.parameterization))
+    }
+}
+#[cfg(feature = "alloc")]
+impl<P: CurvePoint> CatmullRom<P> {
+    /// Panics with fewer than two points
+    pub fn new(points: Vec<P>, parameterization: Parameterization) -> Self {
+        assert!(points.len() >= 2, "a Catmull-Rom spline needs at least two points");
+        CatmullRom { points, parameterization }
+    }
+    pub fn points(&self) -> &[P] {
+        &self.points
+    }
+    pub fn parameterization(&self) -> Parameterization {
+        self.parameterization
+    }
+    /// Segment `i` as a Hermite curve
+    pub fn segment(&self, i: usize) -> Hermite<P> {
+        let n = self.points.len();
+        let (p1, p2) = (self.points[i], self.points[i + 1]);
+        let p0 = if i > 0 { self.points[i - 1] } else { p1 + (p1 - p2) };
+        let p3 = if i + 2 < n { self.points[i + 2] } else { p2 + (p2 - p1) };
+
+        let kind = self.parameterization;
+        let (d0, d1, d2) = (kind.interval(p0, p1), kind.interval(p1, p2), kind.interval(p2, p3));
+        // the tangents of the non uniform spline, rescaled to the segment's own 0..=1
+        let v1 = ((p1 - p0) * (1. / d0) - (p2 - p0) * (1. / (d0 + d1)) + (p2 - p1) * (1. / d1)) * d1;
+        let v2 = ((p2 - p1) * (1. / d1) - (p3 - p1) * (1. / (d1 + d2)) + (p3 - p2) * (1. / d2)) * d1;
+        Hermite::new(p1, v1, p2, v2)
+    }
+    /// The segment `t` falls in and how far along it
+    fn locate(&self, t: f32) -> (usize, f32) {
+        let last = self.points.len() - 1;
+        let t = t.clamp(0., last as f32);
+        let i = (t as usize).min(last - 1);
+        (i, t - i as f32)
+    }
+}
+#[cfg(feature = "alloc")]
+impl<P: CurvePoint> Curve for CatmullRom<P> {
+    type Point = P;
+
+    fn domain(&self) -> (f32, f32) {
+        (0., (self.points.len() - 1) as f32)
+    }
+    fn eval(&self, t: f32) -> P {
+        let (i, u) = self.locate(t);
+        self.segment(i).eval(u)
+    }
+    fn derivative(&self, t: f32) -> P {
+        let (i, u) = self.locate(t);
+        self.segment(i).derivative(u)
+    }
+    fn pieces(&self) -> usize {
+        self.points.len() - 1
+    }
+}
+
+// ------------------- B-spline -----------------------------
+
+/// A B-spline of any degree, which follows its control points without passing through them
+///
+/// It is defined between knots `degree` and `control.len()`, and is `degree - 1` times
+/// continuously differentiable wherever its knots are distinct.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "BSplineRaw<P>", bound(deserialize = "P: CurvePoint + serde::Deserialize<'de>"))
+)]
+pub struct BSpline<P> {
+    control: Vec<P>,
+    knots: Vec<f32>,
+    degree: usize,
+}
+/// What a [`BSpline`] deserializes from before `new`'s checks
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BSplineRaw<P> {
+    control: Vec<P>,
+    knots: Vec<f32>,
+    degree: usize,
+}
+#[cfg(feature = "serde")]
+impl<P: CurvePoint> TryFrom<BSplineRaw<P>> for BSpline<P> {
+    type Error = &'static str;
+
+    fn try_from(raw: BSplineRaw<P>) -> Result<Self, Self::Error> {
+        BSpline::new(raw.control, raw.knots, raw.degree)
+            .ok_or("a B-spline needs non decreasing knots, control.len() + degree + 1 of them")
+    }
+}
+#[cfg(feature = "alloc")]
+impl<P: CurvePoint> BSpline<P> {
+    /// Knots `0, 1, 2, ...`, so it runs over `degree..=control.len()`
+    ///
+    /// Panics unless there are more control points than the degree.
+    pub fn uniform(control: Vec<P>, degree: usize) -> Self {
+        assert!(control.len() > degree, "a B-spline needs more control points than its degree");
+        let knots = (0..=control.len() + degree).map(|k| k as f32).collect();
+        BSpline { control, knots, degree }
+    }
+    /// Uniform knots repeated at both ends, so it starts and ends on its first and last control
+    /// point, running over `0..=control.len() - degree`
+    ///
+    /// Panics unless there are more control points than the degree.
+    pub fn clamped(control: Vec<P>, degree: usize) -> Self {
+        assert!(control.len() > degree, "a B-spline needs more control points than its degree");
+        let end = control.len() - degree;
+        let knots = (0..=control.len() + degree)
+            .map(|k| k.saturating_sub(degree).min(end) as f32)
+            .collect();
+        BSpline { control, knots, degree }
+    }
+    /// Any non decreasing knot vector, `control.len() + degree + 1` long with a non empty domain
+    pub fn new(control: Vec<P>, knots: Vec<f32>, degree: usize) -> Option<Self> {
+        let n = control.len();
+        let valid = n > degree
+            && knots.len() == n + degree + 1
+            && knots.windows(2).all(|k| k[0] <= k[1])
+            && knots[degree] < knots[n];
+        valid.then_some(BSpline { control, knots, degree })
+    }
+    pub fn control(&self) -> &[P] {
+        &self.control
+    }
+    pub fn knots(&self) -> &[f32] {
+        &self.knots
+    }
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+    /// The non empty knot span holding `t`, which is already clamped to the domain
+    fn span(&self, t: f32) -> usize {
+        let (p, n) = (self.degree, self.control.len());
+        let mut span = p + self.knots[p + 1..n].partition_point(|&k| k <= t);
+        while self.knots[span] == self.knots[span + 1] && span > p {
+            span -= 1;
+        }
+        span
+    }
+    fn clamp(&self, t: f32) -> f32 {
+        let (start, end) = self.domain();
+        t.clamp(start, end)
+    }
+}
+/// de Boor's algorithm on the `degree + 1` points of `span`, in place
+#[cfg(feature = "alloc")]
+fn de_boor<P: CurvePoint>(d: &mut [P], knots: &[f32], degree: usize, span: usize, t: f32) -> P {
+    for r in 1..=degree {
+        for j in (r..=degree).rev() {
+            let (lo, hi) = (knots[j + span - degree], knots[j + 1 + span - r]);
+            let alpha = if hi > lo { (t - lo) / (hi - lo) } else { 0. };
+            d[j] = lerp(d[j - 1], d[j], alpha);
+        }
+    }
+    d[degree]
+}
+#[cfg(feature = "alloc")]
+impl<P: CurvePoint> Curve for BSpline<P> {
+    type Point = P;
+
+    fn domain(&self) -> (f32, f32) {
+        (self.knots[self.degree], self.knots[self.control.len()])
+    }
+    fn eval(&self, t: f32) -> P {
+        let t = self.clamp(t);
+        let (p, span) = (self.degree, self.span(t));
+        let mut d = self.control[span - p..=span].to_vec();
+        de_boor(&mut d, &self.knots, p, span, t)
+    }
+    fn derivative(&self, t: f32) -> P {
+        let t = self.clamp(t);
+        let (p, span) = (self.degree, self.span(t));
+        if p == 0 {
+            return self.control[0] * 0.;
+        }
+        // the derivative is a B-spline of one degree less on the inner knots
+        let (c, k) = (&self.control, &self.knots);
+        let mut d: Vec<P> = (span - p..span)
+            .map(|i| {
+                let width = k[i + p + 1] - k[i + 1];
+                if width > 0. { (c[i + 1] - c[i]) * (p as f32 / width) } else { c[i] * 0. }
+            })
+            .collect();
+        de_boor(&mut d, &k[1..], p - 1, span - 1, t)
+    }
+    fn pieces(&self) -> usize {
+        let knots = &self.knots[self.degree..=self.control.len()];
+        knots.windows(2).filter(|k| k[0] < k[1]).count()
+    }
+}
+
+// ------------------- Arc length -----------------------------
+
+/// Gauss-Legendre nodes on `-1..=1` and their weights
+#[cfg(feature = "alloc")]
+const GAUSS: [(f32, f32); 5] = [
+    (-0.906_179_85, 0.236_926_89),
+    (-0.538_469_3, 0.478_628_67),
+    (0., 0.568_888_9),
+    (0.538_469_3, 0.478_628_67),
+    (0.906_179_85, 0.236_926_89),
+];
+
+/// A curve with a table of its length, to travel it by distance rather than parameter
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+pub struct ArcLength<C> {
+    curve: C,
+    /// Evenly spaced parameters
+    params: Vec<f32>,
+    /// Distance travelled up to each parameter
+    lengths: Vec<f32>,
+}
+#[cfg(feature = "alloc")]
+impl<C: Curve> ArcLength<C> {
+    pub fn new(curve: C) -> Self {
+        let (start, end) = curve.domain();
+        let intervals = SAMPLES_PER_PIECE * curve.pieces().max(1);
+        let params: Vec<f32> = (0..=intervals)
+            .map(|i| start + (end - start) * i as f32 / intervals as f32)
+            .collect();
+        let mut lengths = Vec::with_capacity(params.len());
+        let mut total = 0.;
+        lengths.push(total);
+        for pair in params.windows(2) {
+            total += integrate(&curve, pair[0], pair[1]);
+            lengths.push(total);
+        }
+        ArcLength { curve, params, lengths }
+    }
+    pub fn curve(&self) -> &C {
+        &self.curve
+    }
+    /// Total length of the curve
+    pub fn length(&self) -> f32 {
+        *self.lengths.last().unwrap()
+    }
+    /// Distance along the curve from its start to parameter `t`
+    pub fn distance_at(&self, t: f32) -> f32 {
+        let (start, end) = self.curve.domain();
+        let t = t.clamp(start, end);
+        let i = self.interval(&self.params, t);
+        self.lengths[i] + integrate(&self.curve, self.params[i], t)
+    }
+    /// The parameter `distance` along the curve, which is clamped to its length
+    pub fn param_at(&self, distance: f32) -> f32 {
+        let distance = distance.clamp(0., self.length());
+        let i = self.interval(&self.lengths, distance);
+        let (lo, hi) = (self.params[i], self.params[i + 1]);
+        let span = self.lengths[i + 1] - self.lengths[i];
+        let fraction = if span > 0. { (distance - self.lengths[i]) / span } else { 0. };
+        // newton's method from the linear guess, the speed being the derivative of the distance
+        let mut t = lo + (hi - lo) * fraction;
+        for _ in 0..4 {
+            let speed = self.curve.derivative(t).length();
+            if speed <= f32::EPSILON {
+                break;
+            }
+            let error = self.lengths[i] + integrate(&self.curve, lo, t) - distance;
+            t = (t - error / speed).clamp(lo, hi);
+        }
+        t
+    }
+    /// Position `distance` along the curve
+    pub fn point_at(&self, distance: f32) -> C::Point {
+        self.curve.eval(self.param_at(distance))
+    }
+    /// Index of the table interval holding `value`
+    fn interval(&self, table: &[f32], value: f32) -> usize {
+        table.partition_point(|&v| v <= value).saturating_sub(1).min(table.len() - 2)
+    }
+}
+/// Length of the curve between two parameters
+#[cfg(feature = "alloc")]
+fn integrate<C: Curve>(curve: &C, from: f32, to: f32) -> f32 {
+    let (mid, half) = ((from + to) / 2., (to - from) / 2.);
+    GAUSS.iter().map(|&(x, w)| w * curve.derivative(mid + half * x).length()).sum::<f32>() * half
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+    use crate::{assert_approx_eq, vec2};
+    #[cfg(feature = "alloc")]
+    use crate::vec3;
+    #[cfg(feature = "alloc")]
+    use alloc::vec;
+    #[cfg(feature = "serde")]
+    use alloc::{format, string::ToString};
+
+    /// The derivative against a central difference, away from the ends and any breaks at
+    /// whole parameters
+    fn check_derivative<C: Curve>(curve: &C, rng: &mut Rng) {
+        let (start, end) = curve.domain();
+        for _ in 0..50 {
+            let t = rng.range(start, end);
+            if t - t.floor() < 0.01 || t.ceil() - t < 0.01 {
+                continue;
+            }
+            let h = 1e-3;
+            let difference = (curve.eval(t + h) - curve.eval(t - h)) * (1. / (2. * h));
+            let error = (difference - curve.derivative(t)).length();
+            assert!(error < 0.05 * (1. + curve.derivative(t).length()), "derivative off by {error} at {t}");
+        }
+    }
+    /// The bounding box holds every sample and touches the extreme ones
+    fn check_bounds<C: Curve>(curve: &C, (min, max): (C::Point, C::Point)) {
+        let samples = (0..=10_000).map(|i| curve.eval(i as f32 / 10_000.));
+        let (lo, hi) = samples.fold((max, min), |(lo, hi), p| (lo.min(p), hi.max(p)));
+        for i in 0..C::Point::DIM {
+            assert!(lo.axis(i) >= min.axis(i) - 1e-4 && hi.axis(i) <= max.axis(i) + 1e-4);
+            assert!(lo.axis(i) - min.axis(i) < 1e-3 && max.axis(i) - hi.axis(i) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn beziers() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..200 {
+            let cubic = CubicBezier::new(rng.vec2(-10., 10.), rng.vec2(-10., 10.), rng.vec2(-10., 10.), rng.vec2(-10., 10.));
+            assert_eq!(cubic.eval(0.), cubic.p0);
+            assert_eq!(cubic.eval(1.), cubic.p3);
+            assert_approx_eq!(cubic.derivative(0.), (cubic.p1 - cubic.p0) * 3., epsilon = 1e-4);
+            check_derivative(&cubic, &mut rng);
+            check_bounds(&cubic, cubic.bounding_box());
+
+            let t = rng.range(0.1, 0.9);
+            let (left, right) = cubic.split(t);
+            assert_approx_eq!(left.eval(1.), cubic.eval(t), epsilon = 1e-4);
+            assert_approx_eq!(right.eval(0.), cubic.eval(t), epsilon = 1e-4);
+            assert_approx_eq!(left.eval(0.5), cubic.eval(t / 2.), epsilon = 1e-3);
+            assert_approx_eq!(right.eval(0.5), cubic.eval((1. + t) / 2.), epsilon = 1e-3);
+
+            let quadratic = QuadraticBezier::new(rng.vec3(-10., 10.), rng.vec3(-10., 10.), rng.vec3(-10., 10.));
+            assert_approx_eq!(quadratic.eval(0.5), (quadratic.p0 + quadratic.p1 * 2. + quadratic.p2) * 0.25, epsilon = 1e-5);
+            check_derivative(&quadratic, &mut rng);
+            check_bounds(&quadratic, quadratic.bounding_box());
+            let (left, right) = quadratic.split(t);
+            assert_approx_eq!(left.eval(0.5), quadratic.eval(t / 2.), epsilon = 1e-3);
+            assert_approx_eq!(right.eval(0.5), quadratic.eval((1. + t) / 2.), epsilon = 1e-3);
+        }
+
+        // a control point pulled far past the ends
+        let arch = CubicBezier::new(vec2!(0., 0.), vec2!(-1., 4.), vec2!(3., 4.), vec2!(2., 0.));
+        let (min, max) = arch.bounding_box();
+        assert_approx_eq!(max.y, 3., epsilon = 1e-5);
+        assert!(min.x < 0. && max.x > 2.);
+        // straight and degenerate ones have no turning points to find
+        let line = CubicBezier::new(vec2!(0., 0.), vec2!(1., 1.), vec2!(2., 2.), vec2!(3., 3.));
+        assert_eq!(line.bounding_box(), (vec2!(0., 0.), vec2!(3., 3.)));
+        let point = QuadraticBezier::new(vec2!(1., 1.), vec2!(1., 1.), vec2!(1., 1.));
+        assert_eq!(point.bounding_box(), (vec2!(1., 1.), vec2!(1., 1.)));
+    }
+
+    #[test]
+    fn hermite() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..100 {
+            let curve = Hermite::new(rng.vec3(-10., 10.), rng.vec3(-10., 10.), rng.vec3(-10., 10.), rng.vec3(-10., 10.));
+            assert_approx_eq!(curve.eval(0.), curve.p0, epsilon = 1e-5);
+            assert_approx_eq!(curve.eval(1.), curve.p1, epsilon = 1e-5);
+            assert_approx_eq!(curve.derivative(0.), curve.v0, epsilon = 1e-5);
+            assert_approx_eq!(curve.derivative(1.), curve.v1, epsilon = 1e-5);
+            check_derivative(&curve, &mut rng);
+            let bezier = curve.to_bezier();
+            for t in [0.2, 0.5, 0.7] {
+                assert_approx_eq!(curve.eval(t), bezier.eval(t), epsilon = 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn catmull_rom() {
+        let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+        let kinds = [Parameterization::Uniform, Parameterization::Centripetal, Parameterization::Chordal];
+        for round in 0..60 {
+            let points: Vec<Vec2> = (0..2 + round % 7).map(|_| rng.vec2(-10., 10.)).collect();
+            let spline = CatmullRom::new(points.clone(), kinds[round % 3]);
+            assert_eq!(spline.domain(), (0., (points.len() - 1) as f32));
+            for (i, p) in points.iter().enumerate() {
+                assert_approx_eq!(spline.eval(i as f32), *p, epsilon = 1e-4);
+            }
+            check_derivative(&spline, &mut rng);
+        }
+
+        // the uniform tangent is half the neighbours' difference
+        let points = vec![vec2!(0., 0.), vec2!(1., 2.), vec2!(3., 3.), vec2!(4., 0.)];
+        let uniform = CatmullRom::new(points.clone(), Parameterization::Uniform);
+        assert_approx_eq!(uniform.derivative(1.), (points[2] - points[0]) * 0.5, epsilon = 1e-5);
+        // a doubled point doesn't blow up
+        let doubled = vec![vec2!(0., 0.), vec2!(1., 1.), vec2!(1., 1.), vec2!(2., 0.)];
+        for kind in kinds {
+            let spline = CatmullRom::new(doubled.clone(), kind);
+            assert!((0..=30).all(|i| spline.eval(i as f32 / 10.).mag().is_finite()));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn b_splines() {
+        let mut rng = Rng(0x6a09_e667_f3bc_c908);
+        let control: Vec<Vec2> = (0..8).map(|_| rng.vec2(-10., 10.)).collect();
+
+        let uniform = BSpline::uniform(control.clone(), 3);
+        assert_eq!(uniform.domain(), (3., 8.));
+        assert_eq!(uniform.pieces(), 5);
+        for k in 3..=8 {
+            let c = &control[k - 3..k];
+            assert_approx_eq!(uniform.eval(k as f32), (c[0] + c[1] * 4. + c[2]) * (1. / 6.), epsilon = 1e-4);
+        }
+        check_derivative(&uniform, &mut rng);
+
+        let clamped = BSpline::clamped(control.clone(), 3);
+        assert_eq!(clamped.domain(), (0., 5.));
+        assert_approx_eq!(clamped.eval(0.), control[0], epsilon = 1e-5);
+        assert_approx_eq!(clamped.eval(5.), control[7], epsilon = 1e-5);
+        assert_approx_eq!(clamped.derivative(0.), (control[1] - control[0]) * 3., epsilon = 1e-4);
+        check_derivative(&clamped, &mut rng);
+
+        // clamped with one piece is a Bézier curve
+        let bezier = CubicBezier::new(control[0], control[1], control[2], control[3]);
+        let single = BSpline::clamped(control[..4].to_vec(), 3);
+        for t in [0., 0.3, 0.6, 1.] {
+            assert_approx_eq!(single.eval(t), bezier.eval(t), epsilon = 1e-4);
+            assert_approx_eq!(single.derivative(t), bezier.derivative(t), epsilon = 1e-3);
+        }
+
+        // degree one follows the control polygon
+        let polyline = BSpline::clamped(control.clone(), 1);
+        assert_approx_eq!(polyline.eval(2.5), (control[2] + control[3]) * 0.5, epsilon = 1e-5);
+
+        // uneven knots with an interior double knot
+        let knots = vec![0., 0., 0., 1., 2.5, 2.5, 4., 4., 4.];
+        let uneven = BSpline::new(control[..6].to_vec(), knots, 2).unwrap();
+        assert_eq!(uneven.pieces(), 3);
+        assert_approx_eq!(uneven.eval(4.), control[5], epsilon = 1e-5);
+        // the double knot drops continuity to C0, leaving it on a control point
+        assert_approx_eq!(uneven.eval(2.5), control[3], epsilon = 1e-4);
+        check_derivative(&uneven, &mut rng);
+
+        assert!(BSpline::new(control.clone(), vec![0.; 12], 3).is_none());
+        assert!(BSpline::new(control.clone(), (0..11).map(|k| k as f32).collect(), 3).is_none());
+        assert!(BSpline::new(control[..3].to_vec(), vec![0., 1., 2., 3., 4., 5., 6.], 3).is_none());
+        let mut descending: Vec<f32> = (0..12).map(|k| k as f32).collect();
+        descending.swap(5, 6);
+        assert!(BSpline::new(control, descending, 3).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn arc_length() {
+        // straight, but bunched up at the start
+        let line = CubicBezier::new(vec2!(0., 0.), vec2!(0.1, 0.), vec2!(0.2, 0.), vec2!(3., 0.));
+        let table = ArcLength::new(line);
+        assert_approx_eq!(table.length(), 3., epsilon = 1e-4);
+        for i in 0..=30 {
+            let d = i as f32 / 10.;
+            assert_approx_eq!(table.point_at(d), vec2!(d, 0.), epsilon = 1e-4);
+        }
+        assert_eq!(table.point_at(-1.), vec2!(0., 0.));
+        assert_eq!(table.point_at(10.), vec2!(3., 0.));
+
+        // a circle through 32 points
+        let circle: Vec<Vec3> = (0..=32)
+            .map(|i| {
+                let angle = core::f32::consts::TAU * i as f32 / 32.;
+                vec3!(math::cosf(angle), math::sinf(angle), 0.)
+            })
+            .collect();
+        let spline = CatmullRom::new(circle, Parameterization::Centripetal);
+        let table = ArcLength::new(&spline);
+        assert_approx_eq!(table.length(), core::f32::consts::TAU, epsilon = 2e-3);
+        let mut rng = Rng(0xbb67_ae85_84ca_a73b);
+        for _ in 0..100 {
+            let d = rng.range(0., table.length());
+            assert_approx_eq!(table.distance_at(table.param_at(d)), d, epsilon = 1e-4);
+        }
+        // equal steps in distance are equal steps along the circle
+        let step = table.length() / 100.;
+        for i in 0..100 {
+            let chord = (table.point_at(step * (i + 1) as f32) - table.point_at(step * i as f32)).mag();
+            assert_approx_eq!(chord, step, epsilon = 1e-4);
+        }
+
+        // a zero length curve
+        let still = QuadraticBezier::new(vec2!(1., 1.), vec2!(1., 1.), vec2!(1., 1.));
+        let table = ArcLength::new(still);
+        assert_eq!(table.length(), 0.);
+        assert_eq!(table.point_at(1.), vec2!(1., 1.));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn nearest() {
+        let mut rng = Rng(0x3c6e_f372_fe94_f82b);
+        for round in 0..100 {
+            let query = rng.vec2(-10., 10.);
+            let cubic = CubicBezier::new(rng.vec2(-10., 10.), rng.vec2(-10., 10.), rng.vec2(-10., 10.), rng.vec2(-10., 10.));
+            let spline = BSpline::uniform((0..6).map(|_| rng.vec2(-10., 10.)).collect(), 3);
+            let curves: [&dyn Curve<Point = Vec2>; 2] = [&cubic, &spline];
+            let curve = curves[round % 2];
+
+            let found = curve.nearest(query);
+            assert_approx_eq!(found.point, curve.eval(found.t), epsilon = 1e-5);
+            assert_approx_eq!(found.distance, (found.point - query).mag(), epsilon = 1e-5);
+            let (start, end) = curve.domain();
+            let brute = (0..=20_000)
+                .map(|i| (curve.eval(start + (end - start) * i as f32 / 20_000.) - query).mag())
+                .fold(f32::INFINITY, f32::min);
+            assert!(found.distance <= brute + 1e-4, "{} against {brute}", found.distance);
+        }
+
+        // on the curve, and past its end
+        let line = CubicBezier::new(vec3!(0., 0., 0.), vec3!(1., 0., 0.), vec3!(2., 0., 0.), vec3!(3., 0., 0.));
+        let on = line.nearest(vec3!(1.5, 0., 0.));
+        assert_approx_eq!(on.t, 0.5, epsilon = 1e-4);
+        assert!(on.distance < 1e-4);
+        let past = line.nearest(vec3!(5., 1., 0.));
+        assert_eq!(past.t, 1.);
+        assert_approx_eq!(past.distance, math::sqrtf(5.), epsilon = 1e-5);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn deserialize_validates() {
+        let spline = BSpline::clamped(vec![vec2!(0., 0.), vec2!(1., 2.), vec2!(3., 1.), vec2!(4., 0.)], 2);
+        let json = serde_json::to_string(&spline).unwrap();
+        assert_eq!(serde_json::from_str::<BSpline<Vec2>>(&json).unwrap(), spline);
+        let spline = CatmullRom::new(vec![vec3!(0., 0., 0.), vec3!(1., 2., 3.)], Parameterization::Centripetal);
+        let json = serde_json::to_string(&spline).unwrap();
+        assert_eq!(serde_json::from_str::<CatmullRom<Vec3>>(&json).unwrap(), spline);
+
+        // these would index out of bounds or underflow in eval and domain instead
+        let point = r#"{"x":0.0,"y":0.0}"#;
+        let bad = [
+            format!(r#"{{"control":[{point},{point}],"knots":[0.0,1.0],"degree":1}}"#),
+            format!(r#"{{"control":[{point},{point}],"knots":[0.0,1.0,3.0,2.0],"degree":1}}"#),
+            format!(r#"{{"control":[{point},{point}],"knots":[0.0,1.0,1.0,1.0],"degree":1}}"#),
+            format!(r#"{{"control":[{point}],"knots":[0.0,1.0,2.0,3.0],"degree":2}}"#),
+        ];
+        for json in bad {
+            let err = serde_json::from_str::<BSpline<Vec2>>(&json).unwrap_err();
+            assert!(err.to_string().contains("non decreasing knots"), "{err}");
+        }
+        for points in ["[]", &format!("[{point}]")] {
+            let json = format!(r#"{{"points":{points},"parameterization":"Uniform"}}"#);
+            let err = serde_json::from_str::<CatmullRom<Vec2>>(&json).unwrap_err();
+            assert!(err.to_string().contains("at least two points"), "{err}");
+        }
+    }
+}
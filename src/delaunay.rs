@@ -0,0 +1,973 @@
+//! Delaunay triangulation of `Vec2` points, its Voronoi dual and constrained edges.
+//!
+//! The triangulation is built with a sweep hull: points are added in order of distance from a
+//! seed triangle, each one joined to the hull edges it can see, and edges are flipped until
+//! every triangle's circumcircle is empty. `orientation` and `in_circle` decide every step.
+//! They fall back to exact expansion arithmetic when plain `f64` can't tell, so nearly
+//! collinear or cocircular input, like a grid, can't break the mesh.
+use core::cmp::Ordering;
+use core::error::Error;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::vec::Vec2;
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+// ------------------- Predicates -----------------------------
+
+/// Half an ulp of one, the unit roundoff of `f64`
+const ROUNDOFF: f64 = f64::EPSILON / 2.;
+const ORIENTATION_BOUND: f64 = (3. + 16. * ROUNDOFF) * ROUNDOFF;
+const IN_CIRCLE_BOUND: f64 = (10. + 96. * ROUNDOFF) * ROUNDOFF;
+
+// Exact arithmetic on expansions, sums of non-overlapping doubles of increasing magnitude
+// (Shewchuk, "Adaptive Precision Floating-Point Arithmetic and Fast Robust Geometric Predicates")
+
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    (x, (a - a_virtual) + (b - b_virtual))
+}
+fn split(a: f64) -> (f64, f64) {
+    let c = 134_217_729. * a;
+    let high = c - (c - a);
+    (high, a - high)
+}
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    let ((a_hi, a_lo), (b_hi, b_lo)) = (split(a), split(b));
+    let err = x - a_hi * b_hi - a_lo * b_hi - a_hi * b_lo;
+    (x, a_lo * b_lo - err)
+}
+fn difference(a: f32, b: f32) -> Vec<f64> {
+    let (x, err) = two_sum(a as f64, -(b as f64));
+    [err, x].into_iter().filter(|&c| c != 0.).collect()
+}
+fn grow(e: &[f64], b: f64) -> Vec<f64> {
+    let mut out = Vec::with_capacity(e.len() + 1);
+    let mut q = b;
+    for &c in e {
+        let (sum, err) = two_sum(q, c);
+        if err != 0. {
+            out.push(err);
+        }
+        q = sum;
+    }
+    if q != 0. {
+        out.push(q);
+    }
+    out
+}
+fn sum(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(e.to_vec(), |acc, &c| grow(&acc, c))
+}
+fn negate(e: &[f64]) -> Vec<f64> {
+    e.iter().map(|c| -c).collect()
+}
+fn scale(e: &[f64], b: f64) -> Vec<f64> {
+    let mut out = Vec::with_capacity(2 * e.len());
+    let Some((&first, rest)) = e.split_first() else {
+        return out;
+    };
+    let (mut q, err) = two_product(first, b);
+    out.push(err);
+    for &c in rest {
+        let (product, product_err) = two_product(c, b);
+        let (s, err) = two_sum(q, product_err);
+        out.push(err);
+        let (s, err) = two_sum(product, s);
+        out.push(err);
+        q = s;
+    }
+    out.push(q);
+    out.retain(|&c| c != 0.);
+    out
+}
+fn product(e: &[f64], f: &[f64]) -> Vec<f64> {
+    f.iter().fold(Vec::new(), |acc, &c| sum(&acc, &scale(e, c)))
+}
+/// The sign of an expansion is the sign of its largest component, which is last
+fn sign(e: &[f64]) -> Ordering {
+    e.last().map_or(Ordering::Equal, |c| c.partial_cmp(&0.).unwrap_or(Ordering::Equal))
+}
+
+/// Which way `a`, `b`, `c` turn: `Greater` counter clockwise, `Less` clockwise, `Equal` when
+/// they are exactly collinear. Exact for any finite input.
+pub fn orientation(a: Vec2, b: Vec2, c: Vec2) -> Ordering {
+    let (acx, acy) = (a.x as f64 - c.x as f64, a.y as f64 - c.y as f64);
+    let (bcx, bcy) = (b.x as f64 - c.x as f64, b.y as f64 - c.y as f64);
+    let (left, right) = (acx * bcy, acy * bcx);
+    let det = left - right;
+    let bound = ORIENTATION_BOUND * (left.abs() + right.abs());
+    if det > bound || -det > bound {
+        return det.partial_cmp(&0.).unwrap_or(Ordering::Equal);
+    }
+    let left = product(&difference(a.x, c.x), &difference(b.y, c.y));
+    let right = product(&difference(a.y, c.y), &difference(b.x, c.x));
+    sign(&sum(&left, &negate(&right)))
+}
+/// Whether `d` is inside (`Greater`), on (`Equal`) or outside (`Less`) the circle through the
+/// counter clockwise triangle `a b c`. Exact for any finite input.
+pub fn in_circle(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> Ordering {
+    let (adx, ady) = (a.x as f64 - d.x as f64, a.y as f64 - d.y as f64);
+    let (bdx, bdy) = (b.x as f64 - d.x as f64, b.y as f64 - d.y as f64);
+    let (cdx, cdy) = (c.x as f64 - d.x as f64, c.y as f64 - d.y as f64);
+    let (bdxcdy, cdxbdy) = (bdx * cdy, cdx * bdy);
+    let (cdxady, adxcdy) = (cdx * ady, adx * cdy);
+    let (adxbdy, bdxady) = (adx * bdy, bdx * ady);
+    let alift = adx * adx + ady * ady;
+    let blift = bdx * bdx + bdy * bdy;
+    let clift = cdx * cdx + cdy * cdy;
+    let det = alift * (bdxcdy - cdxbdy) + blift * (cdxady - adxcdy) + clift * (adxbdy - bdxady);
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * alift
+        + (cdxady.abs() + adxcdy.abs()) * blift
+        + (adxbdy.abs() + bdxady.abs()) * clift;
+    let bound = IN_CIRCLE_BOUND * permanent;
+    if det > bound || -det > bound {
+        return det.partial_cmp(&0.).unwrap_or(Ordering::Equal);
+    }
+    let (adx, ady) = (difference(a.x, d.x), difference(a.y, d.y));
+    let (bdx, bdy) = (difference(b.x, d.x), difference(b.y, d.y));
+    let (cdx, cdy) = (difference(c.x, d.x), difference(c.y, d.y));
+    let lift = |x: &[f64], y: &[f64]| sum(&product(x, x), &product(y, y));
+    let cross = |x0: &[f64], y0: &[f64], x1: &[f64], y1: &[f64]| sum(&product(x0, y1), &negate(&product(y0, x1)));
+    let det = sum(
+        &sum(
+            &product(&lift(&adx, &ady), &cross(&bdx, &bdy, &cdx, &cdy)),
+            &product(&lift(&bdx, &bdy), &cross(&cdx, &cdy, &adx, &ady)),
+        ),
+        &product(&lift(&cdx, &cdy), &cross(&adx, &ady, &bdx, &bdy)),
+    );
+    sign(&det)
+}
+
+/// Offset of the circumcenter of `abc` from `a`
+fn circumcenter_offset(a: Vec2, b: Vec2, c: Vec2) -> (f64, f64) {
+    let (dx, dy) = (b.x as f64 - a.x as f64, b.y as f64 - a.y as f64);
+    let (ex, ey) = (c.x as f64 - a.x as f64, c.y as f64 - a.y as f64);
+    let (bl, cl) = (dx * dx + dy * dy, ex * ex + ey * ey);
+    let d = 0.5 / (dx * ey - dy * ex);
+    ((ey * bl - dy * cl) * d, (dx * cl - ex * bl) * d)
+}
+fn distance_squared(a: Vec2, x: f64, y: f64) -> f64 {
+    let (dx, dy) = (a.x as f64 - x, a.y as f64 - y);
+    dx * dx + dy * dy
+}
+
+// ------------------- Triangulation -----------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintError {
+    /// The index is out of range, or the point couldn't be placed in the triangulation
+    MissingPoint(usize),
+    /// The edge would cross the constrained edge between these two points
+    CrossesConstraint(usize, usize),
+}
+impl Display for ConstraintError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ConstraintError::MissingPoint(i) => write!(f, "point {i} is not in the triangulation"),
+            ConstraintError::CrossesConstraint(a, b) => {
+                write!(f, "edge crosses the constrained edge between points {a} and {b}")
+            }
+        }
+    }
+}
+impl Error for ConstraintError {}
+
+const NONE: usize = usize::MAX;
+
+fn next(h: usize) -> usize {
+    if h % 3 == 2 { h - 2 } else { h + 1 }
+}
+fn prev(h: usize) -> usize {
+    if h.is_multiple_of(3) { h + 2 } else { h - 1 }
+}
+
+/// Maps a direction to `[0, 1)`, monotonic in its angle
+fn pseudo_angle(dx: f64, dy: f64) -> f64 {
+    let p = dx / (dx.abs() + dy.abs());
+    (if dy > 0. { 3. - p } else { 1. + p }) / 4.
+}
+
+/// A Delaunay triangulation, optionally with constrained edges.
+///
+/// Triangles are counter clockwise triples of point indices. Duplicate points are
+/// triangulated once, under the index of their first copy.
+#[derive(Debug, Clone)]
+pub struct Delaunay {
+    points: Vec<Vec2>,
+    /// Three corners per triangle. Half-edge `h` runs from `triangles[h]` to the next corner
+    /// of triangle `h / 3`.
+    triangles: Vec<usize>,
+    /// The half-edge running the other way, `NONE` on the hull
+    halfedges: Vec<usize>,
+    constrained: Vec<bool>,
+    /// Some half-edge leaving each point, `NONE` for points not in the triangulation
+    leaving: Vec<usize>,
+    /// The first copy of each point
+    canonical: Vec<usize>,
+    hull: Vec<usize>,
+}
+
+/// The hull of the triangulation while it's being swept
+struct Sweep {
+    next: Vec<usize>,
+    prev: Vec<usize>,
+    /// The half-edge along the hull edge leaving each hull point
+    tri: Vec<usize>,
+    hash: Vec<usize>,
+    center: (f64, f64),
+}
+impl Sweep {
+    fn key(&self, p: Vec2) -> usize {
+        let angle = pseudo_angle(p.x as f64 - self.center.0, p.y as f64 - self.center.1);
+        (angle * self.hash.len() as f64) as usize % self.hash.len()
+    }
+}
+
+impl Delaunay {
+    pub fn new(points: &[Vec2]) -> Self {
+        let n = points.len();
+        let mut mesh = Delaunay {
+            points: points.to_vec(),
+            triangles: Vec::with_capacity(6 * n),
+            halfedges: Vec::with_capacity(6 * n),
+            constrained: Vec::with_capacity(6 * n),
+            leaving: vec![NONE; n],
+            canonical: (0..n).collect(),
+            hull: Vec::new(),
+        };
+        let lexical = |a: Vec2, b: Vec2| {
+            a.x.partial_cmp(&b.x)
+                .unwrap_or(Ordering::Equal)
+                .then(a.y.partial_cmp(&b.y).unwrap_or(Ordering::Equal))
+        };
+        let mut unique: Vec<usize> = (0..n).collect();
+        unique.sort_by(|&i, &j| lexical(points[i], points[j]).then(i.cmp(&j)));
+        for w in 1..n {
+            let (first, copy) = (unique[w - 1], unique[w]);
+            if points[first] == points[copy] {
+                mesh.canonical[copy] = mesh.canonical[first];
+            }
+        }
+        unique.retain(|&i| mesh.canonical[i] == i);
+
+        let Some((i0, i1, i2)) = mesh.seed(&unique) else {
+            // all on a line, which stays its own hull
+            mesh.hull = unique;
+            return mesh;
+        };
+        let p = |i: usize| points[i];
+        let (ox, oy) = circumcenter_offset(p(i0), p(i1), p(i2));
+        let center = (p(i0).x as f64 + ox, p(i0).y as f64 + oy);
+        unique.sort_by(|&i, &j| {
+            let (di, dj) = (distance_squared(p(i), center.0, center.1), distance_squared(p(j), center.0, center.1));
+            di.partial_cmp(&dj).unwrap_or(Ordering::Equal)
+        });
+
+        let mut hull = Sweep {
+            next: vec![NONE; n],
+            prev: vec![NONE; n],
+            tri: vec![NONE; n],
+            hash: vec![NONE; n.isqrt() + 1],
+            center,
+        };
+        for (a, b) in [(i0, i1), (i1, i2), (i2, i0)] {
+            hull.next[a] = b;
+            hull.prev[b] = a;
+        }
+        let t = mesh.add_triangle([i0, i1, i2], [NONE; 3]);
+        for (k, i) in [i0, i1, i2].into_iter().enumerate() {
+            hull.tri[i] = t + k;
+            let key = hull.key(p(i));
+            hull.hash[key] = i;
+        }
+        let mut hull_start = i0;
+
+        let visible = |from: usize, to: usize, i: usize| orientation(p(from), p(to), p(i)) == Ordering::Less;
+        for &i in &unique {
+            if i == i0 || i == i1 || i == i2 {
+                continue;
+            }
+            let key = hull.key(p(i));
+            let start = (0..hull.hash.len())
+                .map(|j| hull.hash[(key + j) % hull.hash.len()])
+                .find(|&s| s != NONE && hull.next[s] != s)
+                .unwrap_or(hull_start);
+            let start = hull.prev[start];
+            let mut e = start;
+            while !visible(e, hull.next[e], i) {
+                e = hull.next[e];
+                if e == start {
+                    e = NONE;
+                    break;
+                }
+            }
+            if e == NONE {
+                // rounding in the sweep order, or a small seed circle, left it inside the hull
+                mesh.insert_inside(i, &mut hull);
+                continue;
+            }
+
+            let t = mesh.add_triangle([e, i, hull.next[e]], [NONE, NONE, hull.tri[e]]);
+            hull.tri[e] = t;
+            hull.tri[i] = t + 1;
+            mesh.legalize(t + 2, &mut hull.tri);
+
+            let mut n = hull.next[e];
+            while visible(n, hull.next[n], i) {
+                let q = hull.next[n];
+                let t = mesh.add_triangle([n, i, q], [hull.tri[i], NONE, hull.tri[n]]);
+                hull.tri[i] = t + 1;
+                mesh.legalize(t + 2, &mut hull.tri);
+                hull.next[n] = n;
+                n = q;
+            }
+            if e == start {
+                while visible(hull.prev[e], e, i) {
+                    let q = hull.prev[e];
+                    let t = mesh.add_triangle([q, i, e], [NONE, hull.tri[e], hull.tri[q]]);
+                    hull.tri[q] = t;
+                    mesh.legalize(t + 2, &mut hull.tri);
+                    hull.next[e] = e;
+                    e = q;
+                }
+            }
+
+            hull_start = e;
+            hull.prev[i] = e;
+            hull.next[e] = i;
+            hull.prev[n] = i;
+            hull.next[i] = n;
+            let (key_i, key_e) = (hull.key(p(i)), hull.key(p(e)));
+            hull.hash[key_i] = i;
+            hull.hash[key_e] = e;
+        }
+
+        let mut e = hull_start;
+        loop {
+            mesh.hull.push(e);
+            e = hull.next[e];
+            if e == hull_start {
+                break;
+            }
+        }
+        mesh
+    }
+    /// A triangulation of `points` that keeps the edges between the given pairs of points
+    pub fn with_constraints(points: &[Vec2], edges: &[[usize; 2]]) -> Result<Self, ConstraintError> {
+        let mut mesh = Delaunay::new(points);
+        for &[a, b] in edges {
+            mesh.constrain(a, b)?;
+        }
+        Ok(mesh)
+    }
+
+    /// A small triangle near the middle of the points to sweep out from
+    fn seed(&self, unique: &[usize]) -> Option<(usize, usize, usize)> {
+        let p = |i: usize| self.points[i];
+        let (mut lo, mut hi) = (p(*unique.first()?), p(*unique.first()?));
+        for &i in unique {
+            lo = Vec2::new(lo.x.min(p(i).x), lo.y.min(p(i).y));
+            hi = Vec2::new(hi.x.max(p(i).x), hi.y.max(p(i).y));
+        }
+        let (cx, cy) = ((lo.x as f64 + hi.x as f64) / 2., (lo.y as f64 + hi.y as f64) / 2.);
+        let closest = |x: f64, y: f64, skip: usize| {
+            unique.iter().copied().filter(|&i| i != skip).min_by(|&i, &j| {
+                distance_squared(p(i), x, y).partial_cmp(&distance_squared(p(j), x, y)).unwrap_or(Ordering::Equal)
+            })
+        };
+        let i0 = closest(cx, cy, NONE)?;
+        let i1 = closest(p(i0).x as f64, p(i0).y as f64, i0)?;
+        let radius = |i: usize| {
+            let (x, y) = circumcenter_offset(p(i0), p(i1), p(i));
+            x * x + y * y
+        };
+        let i2 = unique
+            .iter()
+            .copied()
+            .filter(|&i| i != i0 && i != i1 && orientation(p(i0), p(i1), p(i)) != Ordering::Equal)
+            .min_by(|&i, &j| radius(i).partial_cmp(&radius(j)).unwrap_or(Ordering::Equal))?;
+        if orientation(p(i0), p(i1), p(i2)) == Ordering::Less {
+            Some((i0, i2, i1))
+        } else {
+            Some((i0, i1, i2))
+        }
+    }
+    fn link(&mut self, a: usize, b: usize) {
+        self.halfedges[a] = b;
+        if b != NONE {
+            self.halfedges[b] = a;
+        }
+    }
+    /// Adds a counter clockwise triangle, `opposite[k]` is the twin of its edge leaving `corners[k]`
+    fn add_triangle(&mut self, corners: [usize; 3], opposite: [usize; 3]) -> usize {
+        let t = self.triangles.len();
+        for (k, corner) in corners.into_iter().enumerate() {
+            self.triangles.push(corner);
+            self.halfedges.push(NONE);
+            self.constrained.push(false);
+            self.leaving[corner] = t + k;
+        }
+        for (k, twin) in opposite.into_iter().enumerate() {
+            self.link(t + k, twin);
+        }
+        t
+    }
+    /// Whether the edge `h` fails the empty circumcircle test
+    fn illegal(&self, h: usize) -> bool {
+        let o = self.halfedges[h];
+        if o == NONE || self.constrained[h] {
+            return false;
+        }
+        let p = |h: usize| self.points[self.triangles[h]];
+        in_circle(p(h), p(next(h)), p(prev(h)), p(prev(o))) == Ordering::Greater
+    }
+    /// Turns the edge `h` into the other diagonal of the two triangles around it
+    ///
+    /// ```text
+    ///          pl                    pl
+    ///         /||\                  /  \
+    ///      al/ || \bl            al/    \h
+    ///       /  ||  \              /      \
+    ///      /  h||o  \    flip    /___ar___\
+    ///    p0\   ||   /p1   =>   p0\---bl---/p1
+    ///       \  ||  /              \      /
+    ///      ar\ || /br             o\    /br
+    ///         \||/                  \  /
+    ///          pr                    pr
+    /// ```
+    fn flip(&mut self, h: usize) {
+        let o = self.halfedges[h];
+        let (al, ar, bl, br) = (next(h), prev(h), prev(o), next(o));
+        let (p0, pr, pl, p1) = (self.triangles[ar], self.triangles[h], self.triangles[al], self.triangles[bl]);
+        self.triangles[h] = p1;
+        self.triangles[o] = p0;
+        let (hbl, har) = (self.halfedges[bl], self.halfedges[ar]);
+        let (cbl, car) = (self.constrained[bl], self.constrained[ar]);
+        self.link(h, hbl);
+        self.link(o, har);
+        self.link(ar, bl);
+        self.constrained[h] = cbl;
+        self.constrained[o] = car;
+        self.constrained[ar] = false;
+        self.constrained[bl] = false;
+        self.leaving[pr] = br;
+        self.leaving[pl] = al;
+    }
+    /// Adds point `i` inside the triangulation by splitting the triangle, or the edge, it is on
+    fn insert_inside(&mut self, i: usize, hull: &mut Sweep) {
+        let p = self.points[i];
+        let found = (0..self.triangle_count()).find_map(|t| {
+            let sides = [0, 1, 2].map(|k| {
+                let (a, b) = (self.triangles[3 * t + k], self.triangles[next(3 * t + k)]);
+                orientation(self.points[a], self.points[b], p)
+            });
+            (!sides.contains(&Ordering::Less)).then_some((t, sides))
+        });
+        let Some((t, sides)) = found else {
+            return;
+        };
+        let t = 3 * t;
+        let Some(k) = sides.iter().position(|&side| side == Ordering::Equal) else {
+            // three triangles around `i`
+            let [a, b, c] = [t, t + 1, t + 2].map(|h| self.triangles[h]);
+            let (hb, hc) = (self.halfedges[t + 1], self.halfedges[t + 2]);
+            self.triangles[t + 2] = i;
+            let t1 = self.add_triangle([b, c, i], [hb, NONE, t + 1]);
+            let t2 = self.add_triangle([c, a, i], [hc, t + 2, t1 + 1]);
+            self.leaving[b] = t + 1;
+            for (twin, from, h) in [(hb, b, t1), (hc, c, t2)] {
+                if twin == NONE {
+                    hull.tri[from] = h;
+                }
+            }
+            for h in [t, t1, t2] {
+                self.legalize(h, &mut hull.tri);
+            }
+            return;
+        };
+        // on the edge `h` from `u` to `v`, split it and the triangle behind it
+        let h = t + k;
+        let o = self.halfedges[h];
+        let (u, v, w) = (self.triangles[h], self.triangles[next(h)], self.triangles[prev(h)]);
+        let hvw = self.halfedges[next(h)];
+        self.triangles[next(h)] = i;
+        let n1 = self.add_triangle([i, v, w], [o, hvw, next(h)]);
+        self.leaving[u] = h;
+        if hvw == NONE {
+            hull.tri[v] = n1 + 1;
+        }
+        let mut exposed = vec![prev(h), n1 + 1];
+        if o == NONE {
+            self.halfedges[h] = NONE;
+            hull.next[u] = i;
+            hull.prev[i] = u;
+            hull.next[i] = v;
+            hull.prev[v] = i;
+            hull.tri[u] = h;
+            hull.tri[i] = n1;
+            let key = hull.key(p);
+            hull.hash[key] = i;
+        } else {
+            let z = self.triangles[prev(o)];
+            let huz = self.halfedges[next(o)];
+            self.triangles[next(o)] = i;
+            let n2 = self.add_triangle([i, u, z], [h, huz, next(o)]);
+            self.leaving[v] = o;
+            if huz == NONE {
+                hull.tri[u] = n2 + 1;
+            }
+            exposed.extend([prev(o), n2 + 1]);
+        }
+        for h in exposed {
+            self.legalize(h, &mut hull.tri);
+        }
+    }
+    /// Flips `h` and the edges it exposes until they are all Delaunay
+    fn legalize(&mut self, h: usize, hull_tri: &mut [usize]) {
+        let mut stack = vec![h];
+        while let Some(h) = stack.pop() {
+            if !self.illegal(h) {
+                continue;
+            }
+            let o = self.halfedges[h];
+            // hull edges of the pair move to other slots
+            if self.halfedges[prev(o)] == NONE {
+                hull_tri[self.triangles[prev(o)]] = h;
+            }
+            if self.halfedges[prev(h)] == NONE {
+                hull_tri[self.triangles[prev(h)]] = o;
+            }
+            self.flip(h);
+            stack.push(next(o));
+            stack.push(h);
+        }
+    }
+    /// The half-edges leaving `v`, counter clockwise, starting on the hull if `v` is on it
+    fn fan(&self, v: usize) -> Vec<usize> {
+        let start = self.leaving[v];
+        if start == NONE {
+            return Vec::new();
+        }
+        let mut first = start;
+        while self.halfedges[first] != NONE {
+            let h = next(self.halfedges[first]);
+            if h == start {
+                break;
+            }
+            first = h;
+        }
+        let mut fan = vec![first];
+        loop {
+            let h = self.halfedges[prev(*fan.last().unwrap())];
+            if h == NONE || h == first {
+                return fan;
+            }
+            fan.push(h);
+        }
+    }
+    fn find_edge(&self, from: usize, to: usize) -> Option<usize> {
+        self.fan(from).into_iter().find_map(|h| {
+            if self.triangles[next(h)] == to {
+                Some(h)
+            } else if self.triangles[prev(h)] == to {
+                Some(prev(h))
+            } else {
+                None
+            }
+        })
+    }
+    fn vertex(&self, i: usize) -> Result<usize, ConstraintError> {
+        match self.canonical.get(i) {
+            Some(&v) if self.leaving[v] != NONE => Ok(v),
+            _ => Err(ConstraintError::MissingPoint(i)),
+        }
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len() / 3
+    }
+    pub fn triangle(&self, t: usize) -> [usize; 3] {
+        [self.triangles[3 * t], self.triangles[3 * t + 1], self.triangles[3 * t + 2]]
+    }
+    pub fn triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        self.triangles.chunks_exact(3).map(|t| [t[0], t[1], t[2]])
+    }
+    /// The triangles across each edge of `t`, the edge from corner `k` to corner `k + 1` first
+    pub fn adjacent(&self, t: usize) -> [Option<usize>; 3] {
+        core::array::from_fn(|k| match self.halfedges[3 * t + k] {
+            NONE => None,
+            o => Some(o / 3),
+        })
+    }
+    /// The convex hull, counter clockwise. Points on its edges are included.
+    pub fn hull(&self) -> &[usize] {
+        &self.hull
+    }
+    pub fn circumcenter(&self, t: usize) -> Vec2 {
+        let [a, b, c] = self.triangle(t).map(|i| self.points[i]);
+        let (x, y) = circumcenter_offset(a, b, c);
+        Vec2::new((a.x as f64 + x) as f32, (a.y as f64 + y) as f32)
+    }
+
+    // ------------------- Constraints -----------------------------
+
+    pub fn is_constrained(&self, a: usize, b: usize) -> bool {
+        match (self.vertex(a), self.vertex(b)) {
+            (Ok(a), Ok(b)) => self.find_edge(a, b).is_some_and(|h| self.constrained[h]),
+            _ => false,
+        }
+    }
+    fn set_constrained(&mut self, h: usize) {
+        self.constrained[h] = true;
+        if self.halfedges[h] != NONE {
+            let o = self.halfedges[h];
+            self.constrained[o] = true;
+        }
+    }
+    /// Forces an edge between points `a` and `b` into the triangulation. An edge through other
+    /// points is split at them. Edges that are no longer Delaunay because of it stay as close
+    /// to it as the constraints allow.
+    pub fn constrain(&mut self, a: usize, b: usize) -> Result<(), ConstraintError> {
+        let (mut from, to) = (self.vertex(a)?, self.vertex(b)?);
+        while from != to {
+            from = self.constrain_towards(from, to)?;
+        }
+        Ok(())
+    }
+    /// Constrains the edge from `a` towards `b`, up to the first point on the way
+    fn constrain_towards(&mut self, a: usize, b: usize) -> Result<usize, ConstraintError> {
+        let p = |mesh: &Self, i: usize| mesh.points[i];
+        let (pa, pb) = (p(self, a), p(self, b));
+        // collinear `v` lies towards `b` rather than away from it
+        let ahead = |v: Vec2| {
+            if pb.x != pa.x {
+                v.x != pa.x && (v.x > pa.x) == (pb.x > pa.x)
+            } else {
+                v.y != pa.y && (v.y > pa.y) == (pb.y > pa.y)
+            }
+        };
+        let mut crossing = None;
+        for h in self.fan(a) {
+            let (x, y) = (self.triangles[next(h)], self.triangles[prev(h)]);
+            for (v, edge) in [(x, h), (y, prev(h))] {
+                if v == b || (orientation(pa, pb, p(self, v)) == Ordering::Equal && ahead(p(self, v))) {
+                    self.set_constrained(edge);
+                    return Ok(v);
+                }
+            }
+            if orientation(pa, pb, p(self, x)) == Ordering::Less && orientation(pa, pb, p(self, y)) == Ordering::Greater {
+                crossing = Some(next(h));
+                break;
+            }
+        }
+        let Some(mut e) = crossing else {
+            return Err(ConstraintError::MissingPoint(b));
+        };
+
+        // walk along the edge collecting what it crosses, each from its right end to its left
+        let mut crossed = VecDeque::new();
+        let end = loop {
+            let (right, left) = (self.triangles[e], self.triangles[next(e)]);
+            if self.constrained[e] {
+                return Err(ConstraintError::CrossesConstraint(right, left));
+            }
+            crossed.push_back((right, left));
+            let o = self.halfedges[e];
+            let z = self.triangles[prev(o)];
+            match orientation(pa, pb, p(self, z)) {
+                _ if z == b => break b,
+                Ordering::Equal => break z,
+                Ordering::Less => e = prev(o),
+                Ordering::Greater => e = next(o),
+            }
+        };
+
+        // flip the crossed edges away, trying the ones in non-convex spots again later
+        let pe = p(self, end);
+        let mut created = Vec::new();
+        while let Some((u, v)) = crossed.pop_front() {
+            let Some(h) = self.find_edge(u, v) else {
+                continue;
+            };
+            let o = self.halfedges[h];
+            let (p0, p1) = (self.triangles[prev(h)], self.triangles[prev(o)]);
+            let (s0, s1) = (p(self, p0), p(self, p1));
+            let (su, sv) = (orientation(s0, s1, p(self, u)), orientation(s0, s1, p(self, v)));
+            if su == Ordering::Equal || sv == Ordering::Equal || su == sv {
+                crossed.push_back((u, v));
+                continue;
+            }
+            self.flip(h);
+            let still_crossing = ![a, end].contains(&p0)
+                && ![a, end].contains(&p1)
+                && orientation(pa, pe, s0) != Ordering::Equal
+                && orientation(pa, pe, s0) == orientation(pa, pe, s1).reverse();
+            if still_crossing {
+                crossed.push_back((p0, p1));
+            } else {
+                created.push((p0, p1));
+            }
+        }
+        if let Some(h) = self.find_edge(a, end) {
+            self.set_constrained(h);
+        }
+
+        // restore the empty circumcircles around the new edges
+        let mut flipped = true;
+        while flipped {
+            flipped = false;
+            for edge in created.iter_mut() {
+                let Some(h) = self.find_edge(edge.0, edge.1) else {
+                    continue;
+                };
+                if self.illegal(h) {
+                    let o = self.halfedges[h];
+                    *edge = (self.triangles[prev(h)], self.triangles[prev(o)]);
+                    self.flip(h);
+                    flipped = true;
+                }
+            }
+        }
+        Ok(end)
+    }
+
+    // ------------------- Voronoi -----------------------------
+
+    /// The Voronoi cell of a point, clipped to the box from `min` to `max`, counter clockwise.
+    /// Inside the hull its corners are the circumcenters of the triangles around the point.
+    /// Empty when there are no triangles. Constrained edges that aren't Delaunay bend the cells
+    /// around them, so take the cells from an unconstrained triangulation.
+    pub fn voronoi_cell(&self, point: usize, min: Vec2, max: Vec2) -> Vec<Vec2> {
+        let Some(&v) = self.canonical.get(point) else {
+            return Vec::new();
+        };
+        let fan = self.fan(v);
+        let Some(&first) = fan.first() else {
+            return Vec::new();
+        };
+        let boxed = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+        let site = self.points[v];
+        let mut cell = if self.halfedges[first] != NONE {
+            fan.iter().map(|&h| self.circumcenter(h / 3)).collect()
+        } else {
+            // an open cell: cut the box down by the bisector with every neighbour
+            let last = *fan.last().unwrap();
+            let neighbours = fan.iter().map(|&h| self.triangles[next(h)]).chain([self.triangles[prev(last)]]);
+            neighbours.fold(boxed.to_vec(), |cell, u| {
+                let other = self.points[u];
+                let normal = other - site;
+                clip(&cell, normal, normal.dot((other + site) / 2.))
+            })
+        };
+        for (normal, offset) in [
+            (Vec2::new(-1., 0.), -min.x),
+            (Vec2::new(1., 0.), max.x),
+            (Vec2::new(0., -1.), -min.y),
+            (Vec2::new(0., 1.), max.y),
+        ] {
+            cell = clip(&cell, normal, offset);
+        }
+        cell
+    }
+    /// `voronoi_cell` for every point
+    pub fn voronoi_cells(&self, min: Vec2, max: Vec2) -> Vec<Vec<Vec2>> {
+        (0..self.points.len()).map(|i| self.voronoi_cell(i, min, max)).collect()
+    }
+}
+
+/// Sutherland–Hodgman: the part of a convex polygon where `normal · x <= offset`
+fn clip(polygon: &[Vec2], normal: Vec2, offset: f32) -> Vec<Vec2> {
+    let mut out = Vec::with_capacity(polygon.len() + 1);
+    for (i, &p) in polygon.iter().enumerate() {
+        let q = polygon[(i + 1) % polygon.len()];
+        let (dp, dq) = (normal.dot(p) - offset, normal.dot(q) - offset);
+        if dp <= 0. {
+            out.push(p);
+        }
+        if (dp <= 0.) != (dq <= 0.) {
+            out.push(p + (q - p) * (dp / (dp - dq)));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+    use crate::vec2;
+
+    fn area(polygon: &[Vec2]) -> f32 {
+        let n = polygon.len();
+        (0..n).map(|i| polygon[i].cross(polygon[(i + 1) % n])).sum::<f32>() / 2.
+    }
+    /// Every triangle counter clockwise, every unconstrained edge Delaunay, all points used
+    fn check(mesh: &Delaunay) {
+        let p = |i: usize| mesh.points()[i];
+        for [a, b, c] in mesh.triangles() {
+            assert_eq!(orientation(p(a), p(b), p(c)), Ordering::Greater);
+        }
+        for h in 0..mesh.triangles.len() {
+            assert!(!mesh.illegal(h), "edge {h} is not Delaunay");
+            if mesh.halfedges[h] != NONE {
+                assert_eq!(mesh.halfedges[mesh.halfedges[h]], h);
+                assert_eq!(mesh.constrained[mesh.halfedges[h]], mesh.constrained[h]);
+            }
+        }
+        let unique = (0..mesh.points().len()).filter(|&i| mesh.canonical[i] == i).count();
+        assert_eq!(mesh.triangle_count(), 2 * unique - mesh.hull().len() - 2);
+    }
+
+    #[test]
+    fn predicates() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        // integers up to 2^24 are exact in f32 and their determinants fit in an i128
+        let exact_orientation = |a: [i64; 2], b: [i64; 2], c: [i64; 2]| {
+            let [a, b, c] = [a, b, c].map(|p| p.map(|x| x as i128));
+            ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])).cmp(&0)
+        };
+        let exact_in_circle = |a: [i64; 2], b: [i64; 2], c: [i64; 2], d: [i64; 2]| {
+            let [a, b, c] = [a, b, c].map(|p| [(p[0] - d[0]) as i128, (p[1] - d[1]) as i128]);
+            let lift = |p: [i128; 2]| p[0] * p[0] + p[1] * p[1];
+            let cross = |p: [i128; 2], q: [i128; 2]| p[0] * q[1] - p[1] * q[0];
+            (lift(a) * cross(b, c) + lift(b) * cross(c, a) + lift(c) * cross(a, b)).cmp(&0)
+        };
+        let v = |p: [i64; 2]| vec2!(p[0] as f32, p[1] as f32);
+        for _ in 0..20000 {
+            let bound = 1 << (rng.next() % 24);
+            let mut point = || [rng.int(bound), rng.int(bound)];
+            let (a, b) = (point(), point());
+            // nearly on the line through a and b, one step off at most
+            let k = rng.int(4);
+            let c = [a[0] + k * (b[0] - a[0]) + rng.int(1), a[1] + k * (b[1] - a[1]) + rng.int(1)];
+            if c.iter().any(|x| x.abs() > 1 << 24) {
+                continue;
+            }
+            assert_eq!(orientation(v(a), v(b), v(c)), exact_orientation(a, b, c), "{a:?} {b:?} {c:?}");
+            // the fourth point of a rectangle is on the circle, nudge it around
+            let d = [a[0] + c[0] - b[0] + rng.int(1), a[1] + c[1] - b[1] + rng.int(1)];
+            if d.iter().any(|x| x.abs() > 1 << 24) {
+                continue;
+            }
+            let expected = exact_in_circle(a, b, c, d);
+            let expected = if exact_orientation(a, b, c) == Ordering::Less { expected.reverse() } else { expected };
+            let (a, c) = if exact_orientation(a, b, c) == Ordering::Less { (c, a) } else { (a, c) };
+            assert_eq!(in_circle(v(a), v(b), v(c), v(d)), expected, "{a:?} {b:?} {c:?} {d:?}");
+        }
+        // far apart magnitudes, where the f64 filter alone would round the answer away
+        let tiny = f32::MIN_POSITIVE;
+        assert_eq!(orientation(vec2!(0., 0.), vec2!(1e30, 1e30), vec2!(tiny, 0.)), Ordering::Less);
+        assert_eq!(orientation(vec2!(0., 0.), vec2!(1e30, 1e30), vec2!(2e30, 2e30)), Ordering::Equal);
+    }
+
+    #[test]
+    fn triangulation() {
+        // a grid is all cocircular quads
+        let grid: Vec<Vec2> = (0..100).map(|i| vec2!((i % 10) as f32, (i / 10) as f32)).collect();
+        let mesh = Delaunay::new(&grid);
+        check(&mesh);
+        assert_eq!(mesh.triangle_count(), 162);
+        assert_eq!(mesh.hull().len(), 36);
+
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for round in 0..50 {
+            let n = 3 + round * 20;
+            let mut points: Vec<Vec2> = (0..n).map(|_| vec2!(rng.range(-100., 100.), rng.range(-50., 50.))).collect();
+            // some copies and some points on a line
+            points.extend_from_within(..round);
+            points.extend((0..round).map(|i| vec2!(i as f32 - 10., 0.)));
+            let mesh = Delaunay::new(&points);
+            check(&mesh);
+            let covered: f32 = mesh.triangles().map(|t| area(&t.map(|i| points[i]))).sum();
+            let hull: Vec<Vec2> = mesh.hull().iter().map(|&i| points[i]).collect();
+            assert!((covered - area(&hull)).abs() < 1e-2 * area(&hull));
+            for (i, &copy) in mesh.canonical.iter().enumerate() {
+                assert_eq!(points[i], points[copy]);
+            }
+        }
+
+        // a sliver a billion times taller than wide, where the sweep order rounds badly
+        for _ in 0..500 {
+            let n = 3 + rng.next() % 200;
+            let points: Vec<Vec2> = (0..n).map(|_| vec2!(rng.range(-1e-3, 1e-3), rng.range(-1e6, 1e6))).collect();
+            let mut mesh = Delaunay::new(&points);
+            check(&mesh);
+            let _ = mesh.constrain(0, 1);
+            check(&mesh);
+        }
+
+        let line = Delaunay::new(&[vec2!(2., 2.), vec2!(0., 0.), vec2!(1., 1.), vec2!(0., 0.)]);
+        assert_eq!(line.triangle_count(), 0);
+        assert_eq!(line.hull(), &[1, 2, 0]);
+    }
+
+    #[test]
+    fn constraints() {
+        let grid: Vec<Vec2> = (0..100).map(|i| vec2!((i % 10) as f32, (i / 10) as f32)).collect();
+        // through grid points, so it is split at every one of them
+        let mut mesh = Delaunay::with_constraints(&grid, &[[0, 99]]).unwrap();
+        check(&mesh);
+        for i in 0..9 {
+            assert!(mesh.is_constrained(11 * i, 11 * (i + 1)));
+        }
+        // crossing it is refused, touching it is fine
+        assert!(matches!(mesh.constrain(9, 90), Err(ConstraintError::CrossesConstraint(..))));
+        mesh.constrain(9, 44).unwrap();
+        assert_eq!(mesh.constrain(0, 100), Err(ConstraintError::MissingPoint(100)));
+
+        let mut rng = Rng(0x1234_5678_9abc_def1);
+        for _ in 0..50 {
+            let points: Vec<Vec2> = (0..300).map(|_| vec2!(rng.range(0., 100.), rng.range(0., 100.))).collect();
+            let mut mesh = Delaunay::new(&points);
+            // a zig-zag across the whole set, its segments only meet at their ends
+            let mut path: Vec<usize> = (0..8).map(|_| (rng.next() % 300) as usize).collect();
+            path.sort_by(|&a, &b| points[a].x.partial_cmp(&points[b].x).unwrap());
+            path.dedup();
+            for w in path.windows(2) {
+                mesh.constrain(w[0], w[1]).unwrap();
+            }
+            check(&mesh);
+            for w in path.windows(2) {
+                assert!(mesh.is_constrained(w[0], w[1]), "{w:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn voronoi() {
+        let grid: Vec<Vec2> = (0..9).map(|i| vec2!((i % 3) as f32, (i / 3) as f32)).collect();
+        let mesh = Delaunay::new(&grid);
+        let (min, max) = (vec2!(-1., -1.), vec2!(3., 3.));
+        let middle = mesh.voronoi_cell(4, min, max);
+        assert!((area(&middle) - 1.).abs() < 1e-5);
+        assert!(middle.iter().all(|c| (c.x - 1.).abs() <= 0.5 + 1e-5 && (c.y - 1.).abs() <= 0.5 + 1e-5));
+        // a corner's cell runs out to the box
+        assert!((area(&mesh.voronoi_cell(0, min, max)) - 1.5 * 1.5).abs() < 1e-4);
+
+        let mut rng = Rng(0xdead_beef_cafe_f00d);
+        let points: Vec<Vec2> = (0..200).map(|_| vec2!(rng.range(0., 10.), rng.range(0., 10.))).collect();
+        let mesh = Delaunay::new(&points);
+        let (min, max) = (vec2!(-1., -1.), vec2!(11., 11.));
+        let cells = mesh.voronoi_cells(min, max);
+        // the cells tile the box, and every sample belongs to its nearest point's cell
+        let total: f32 = cells.iter().map(|cell| area(cell)).sum();
+        assert!((total - 144.).abs() < 1e-2, "{total}");
+        for _ in 0..1000 {
+            let sample = vec2!(rng.range(-1., 11.), rng.range(-1., 11.));
+            let nearest = (0..points.len())
+                .min_by(|&i, &j| (points[i] - sample).mag().partial_cmp(&(points[j] - sample).mag()).unwrap())
+                .unwrap();
+            let cell = &cells[nearest];
+            let inside = (0..cell.len()).all(|i| (cell[(i + 1) % cell.len()] - cell[i]).cross(sample - cell[i]) >= -1e-3);
+            assert!(inside, "{sample:?} is not in the cell of {nearest}");
+        }
+    }
+}
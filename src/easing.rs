@@ -0,0 +1,352 @@
+//! Interpolation and the standard easing functions for tweens.
+//!
+//! `Interpolate` blends two values linearly, and spherically for `Quaternion`. `Ease` holds the
+//! Penner easing set, which reshapes the blend factor before it gets there:
+//! ```
+//! # use my_math::prelude::*;
+//! let halfway = Vec2::new(0., 0.).ease(Vec2::new(10., 0.), 0.5, Ease::QuadIn);
+//! assert_eq!(halfway, Vec2::new(2.5, 0.));
+//! ```
+use core::f32::consts::{LOG2_E, PI};
+
+use crate::math;
+use crate::quat::Quaternion;
+use crate::vec::{Vec2, Vec3, Vec4};
+
+/// Values that can be blended
+pub trait Interpolate: Copy {
+    /// `self` at `t = 0` and `other` at `t = 1`, `t` outside `0..=1` extrapolates where the
+    /// type allows it
+    fn interpolate(self, other: Self, t: f32) -> Self;
+
+    /// `interpolate` with `t` clamped to `0..=1` and reshaped by `ease`
+    fn ease(self, other: Self, t: f32, ease: Ease) -> Self {
+        self.interpolate(other, ease.apply(t))
+    }
+}
+impl Interpolate for f32 {
+    fn interpolate(self, other: f32, t: f32) -> f32 {
+        self + (other - self) * t
+    }
+}
+macro_rules! implInterpolate {
+    ($($type:ident [$($field:ident),+]),+) => {
+        $(
+            impl Interpolate for $type {
+                fn interpolate(self, other: Self, t: f32) -> Self {
+                    $type { $($field: self.$field + (other.$field - self.$field) * t),+ }
+                }
+            }
+        )+
+    };
+}
+implInterpolate!(Vec2 [x, y], Vec3 [x, y, z], Vec4 [x, y, z, w]);
+/// Slerp, so `t` is clamped to `0..=1`
+impl Interpolate for Quaternion {
+    fn interpolate(self, other: Quaternion, t: f32) -> Quaternion {
+        self.slerp(other, t.clamp(0., 1.))
+    }
+}
+
+// ------------------- Easing -----------------------------
+
+/// The standard easing functions, each mapping `0..=1` to `0` at `0` and `1` at `1`
+///
+/// `In` eases start slowly, `Out` eases end slowly and `InOut` do both. `Back` overshoots and
+/// `Elastic` oscillates past the ends, so their values leave `0..=1`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ease {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+impl Ease {
+    /// Every variant, in declaration order
+    pub const ALL: [Ease; 31] = [
+        Ease::Linear,
+        Ease::QuadIn, Ease::QuadOut, Ease::QuadInOut,
+        Ease::CubicIn, Ease::CubicOut, Ease::CubicInOut,
+        Ease::QuartIn, Ease::QuartOut, Ease::QuartInOut,
+        Ease::QuintIn, Ease::QuintOut, Ease::QuintInOut,
+        Ease::SineIn, Ease::SineOut, Ease::SineInOut,
+        Ease::ExpoIn, Ease::ExpoOut, Ease::ExpoInOut,
+        Ease::CircIn, Ease::CircOut, Ease::CircInOut,
+        Ease::BackIn, Ease::BackOut, Ease::BackInOut,
+        Ease::ElasticIn, Ease::ElasticOut, Ease::ElasticInOut,
+        Ease::BounceIn, Ease::BounceOut, Ease::BounceInOut,
+    ];
+
+    /// The eased progress at `t`, which is clamped to `0..=1`
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => power_in(t, 2),
+            Ease::QuadOut => power_out(t, 2),
+            Ease::QuadInOut => power_in_out(t, 2),
+            Ease::CubicIn => power_in(t, 3),
+            Ease::CubicOut => power_out(t, 3),
+            Ease::CubicInOut => power_in_out(t, 3),
+            Ease::QuartIn => power_in(t, 4),
+            Ease::QuartOut => power_out(t, 4),
+            Ease::QuartInOut => power_in_out(t, 4),
+            Ease::QuintIn => power_in(t, 5),
+            Ease::QuintOut => power_out(t, 5),
+            Ease::QuintInOut => power_in_out(t, 5),
+            Ease::SineIn => 1. - math::cosf(t * PI / 2.),
+            Ease::SineOut => math::sinf(t * PI / 2.),
+            Ease::SineInOut => (1. - math::cosf(t * PI)) / 2.,
+            Ease::ExpoIn => expo_in(t),
+            Ease::ExpoOut => in_out(t, expo_in, Mode::Out),
+            Ease::ExpoInOut => in_out(t, expo_in, Mode::InOut),
+            Ease::CircIn => circ_in(t),
+            Ease::CircOut => in_out(t, circ_in, Mode::Out),
+            Ease::CircInOut => in_out(t, circ_in, Mode::InOut),
+            Ease::BackIn => back_in(t, BACK),
+            Ease::BackOut => in_out(t, |t| back_in(t, BACK), Mode::Out),
+            // the overshoot is rescaled so each half pulls back about 10%, as in Penner's
+            Ease::BackInOut => in_out(t, |t| back_in(t, BACK * 1.525), Mode::InOut),
+            Ease::ElasticIn => elastic_in(t, 0.3),
+            Ease::ElasticOut => in_out(t, |t| elastic_in(t, 0.3), Mode::Out),
+            Ease::ElasticInOut => in_out(t, |t| elastic_in(t, 0.45), Mode::InOut),
+            Ease::BounceIn => bounce_in(t),
+            Ease::BounceOut => bounce_out(t),
+            Ease::BounceInOut => in_out(t, bounce_in, Mode::InOut),
+        }
+    }
+}
+
+/// How much `Back` eases pull back, about 10% of the distance
+const BACK: f32 = 1.701_58;
+
+enum Mode {
+    Out,
+    InOut,
+}
+/// Builds the other eases from an `In` one: `Out` runs it backwards and `InOut` runs it forwards,
+/// then backwards, each at double speed
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32, mode: Mode) -> f32 {
+    match mode {
+        Mode::Out => 1. - ease_in(1. - t),
+        Mode::InOut if t < 0.5 => ease_in(2. * t) / 2.,
+        Mode::InOut => 1. - ease_in(2. - 2. * t) / 2.,
+    }
+}
+fn power_in(t: f32, n: u32) -> f32 {
+    (0..n).fold(1., |acc, _| acc * t)
+}
+fn power_out(t: f32, n: u32) -> f32 {
+    in_out(t, |t| power_in(t, n), Mode::Out)
+}
+fn power_in_out(t: f32, n: u32) -> f32 {
+    in_out(t, |t| power_in(t, n), Mode::InOut)
+}
+fn expo_in(t: f32) -> f32 {
+    if t <= 0. { 0. } else { math::exp2f(10. * t - 10.) }
+}
+fn circ_in(t: f32) -> f32 {
+    1. - math::sqrtf((1. - t * t).max(0.))
+}
+fn back_in(t: f32, overshoot: f32) -> f32 {
+    t * t * ((overshoot + 1.) * t - overshoot)
+}
+/// A decaying sine with the given period, as a fraction of the duration
+fn elastic_in(t: f32, period: f32) -> f32 {
+    if t <= 0. || t >= 1. {
+        return t;
+    }
+    let phase = period / 4.;
+    -math::exp2f(10. * t - 10.) * math::sinf((t - 1. - phase) * 2. * PI / period)
+}
+fn bounce_in(t: f32) -> f32 {
+    in_out(t, bounce_out, Mode::Out)
+}
+/// Four parabolic arcs, each a quarter as high as the last
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1. / D {
+        N * t * t
+    } else if t < 2. / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984_375
+    }
+}
+
+// ------------------- Smoothing -----------------------------
+
+/// Moves `current` towards `target` like a critically damped spring, the usual camera follow
+///
+/// Call it every frame with the frame's `dt` and the same `velocity`, which it updates. It
+/// arrives in about `smooth_time` without oscillating, and never overshoots a moving target.
+pub fn smooth_damp(current: Vec3, target: Vec3, velocity: &mut Vec3, smooth_time: f32, dt: f32) -> Vec3 {
+    let omega = 2. / smooth_time.max(1e-4);
+    let decay = math::exp2f(-omega * dt * LOG2_E);
+    // the spring's exact solution over the step, with the offset c and velocity v:
+    // x(t) = (c + (v + ωc)t)e^(-ωt)
+    let offset = current - target;
+    let drift = (*velocity + offset * omega) * dt;
+    let out = target + (offset + drift) * decay;
+    *velocity = (*velocity - drift * omega) * decay;
+
+    if (target - current).dot(out - target) > 0. {
+        *velocity = Vec3::new(0., 0., 0.);
+        return target;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assert_approx_eq, vec2, vec3};
+    #[cfg(feature = "alloc")]
+    use alloc::format;
+
+    #[test]
+    fn interpolation() {
+        assert_eq!(2f32.interpolate(6., 0.25), 3.);
+        assert_eq!(2f32.interpolate(6., 1.5), 8.);
+        assert_eq!(vec2!(0., 2.).interpolate(vec2!(4., 4.), 0.5), vec2!(2., 3.));
+        assert_eq!(vec3!(1., 1., 1.).interpolate(vec3!(3., 5., -1.), 0.5), vec3!(2., 3., 0.));
+        let v = Vec4::new(0., 0., 0., 1.).interpolate(Vec4::new(4., 8., 0., 1.), 0.75);
+        assert_eq!((v.x, v.y, v.z, v.w), (3., 6., 0., 1.));
+
+        // slerp turns at constant angular speed
+        let turn = |axis, deg: f32| Quaternion::from_axis_angle(axis, deg.to_radians());
+        let (from, to) = (turn(Vec3::Z, 0.), turn(Vec3::Z, 120.));
+        for i in 0..=10 {
+            let t = i as f32 / 10.;
+            assert_approx_eq!(from.interpolate(to, t), turn(Vec3::Z, 120. * t), epsilon = 1e-5);
+        }
+        // the shorter way round, even when the quaternions sit in opposite hemispheres
+        let negated = Quaternion::new(-to.w, to.v * -1.);
+        assert_approx_eq!(from.interpolate(negated, 0.5), turn(Vec3::Z, 60.), epsilon = 1e-5);
+        let far = turn(Vec3::Z, 300.);
+        assert_approx_eq!(from.interpolate(far, 0.5), turn(Vec3::Z, -30.), epsilon = 1e-5);
+        // nearly equal ones still come out normalized
+        let near = turn(Vec3::X, 0.01);
+        let mid = from.interpolate(near, 0.5);
+        assert_approx_eq!(mid.dot(mid), 1., epsilon = 1e-6);
+        assert_approx_eq!(mid, turn(Vec3::X, 0.005), epsilon = 1e-6);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn easing() {
+        for ease in Ease::ALL {
+            assert_approx_eq!(ease.apply(0.), 0., epsilon = 1e-6);
+            assert_approx_eq!(ease.apply(1.), 1., epsilon = 1e-6);
+            assert_eq!(ease.apply(-1.), ease.apply(0.));
+            assert_eq!(ease.apply(2.), ease.apply(1.));
+            // no jumps
+            for i in 0..1000 {
+                let (a, b) = (ease.apply(i as f32 / 1000.), ease.apply((i + 1) as f32 / 1000.));
+                assert!((a - b).abs() < 0.05, "{ease:?} jumps at {i}");
+            }
+        }
+        let at = |ease: Ease, t: f32| ease.apply(t);
+        assert_eq!(at(Ease::Linear, 0.3), 0.3);
+        assert_approx_eq!(at(Ease::QuadIn, 0.5), 0.25);
+        assert_approx_eq!(at(Ease::CubicOut, 0.5), 0.875);
+        assert_approx_eq!(at(Ease::QuartInOut, 0.25), 0.03125);
+        assert_approx_eq!(at(Ease::QuintInOut, 0.75), 0.984_375, epsilon = 1e-6);
+        assert_approx_eq!(at(Ease::SineIn, 0.5), 1. - 0.5f32.sqrt(), epsilon = 1e-6);
+        assert_approx_eq!(at(Ease::ExpoOut, 0.5), 1. - 1. / 32., epsilon = 1e-6);
+        assert_approx_eq!(at(Ease::CircOut, 0.5), 0.75f32.sqrt(), epsilon = 1e-6);
+        assert_approx_eq!(at(Ease::BounceOut, 0.5), 0.765_625, epsilon = 1e-6);
+        assert_approx_eq!(at(Ease::BounceIn, 0.5), 0.234_375, epsilon = 1e-6);
+        // the reference values of easings.net
+        assert_approx_eq!(at(Ease::BackIn, 0.5), -0.087_697_5, epsilon = 1e-6);
+        assert_approx_eq!(at(Ease::BackInOut, 0.25), -0.099_681_84, epsilon = 1e-6);
+        assert_approx_eq!(at(Ease::ElasticOut, 0.25), 0.911_611_6, epsilon = 1e-5);
+        assert_approx_eq!(at(Ease::ElasticInOut, 0.25), 0.011_969_44, epsilon = 1e-5);
+
+        // every in-out is symmetric about the middle
+        for ease in Ease::ALL.into_iter().filter(|e| format!("{e:?}").ends_with("InOut")) {
+            for i in 0..=20 {
+                let t = i as f32 / 20.;
+                assert_approx_eq!(ease.apply(t) + ease.apply(1. - t), 1., epsilon = 1e-5);
+            }
+        }
+        // back and elastic leave 0..=1, nothing else does
+        let wild = [Ease::BackIn, Ease::BackOut, Ease::BackInOut, Ease::ElasticIn, Ease::ElasticOut, Ease::ElasticInOut];
+        for ease in Ease::ALL {
+            let inside = (0..=100).map(|i| ease.apply(i as f32 / 100.)).all(|v| (0. ..=1.).contains(&v));
+            assert_eq!(inside, !wild.contains(&ease), "{ease:?}");
+        }
+        assert_eq!(vec2!(0., 0.).ease(vec2!(8., 4.), 0.5, Ease::QuadOut), vec2!(6., 3.));
+    }
+
+    #[test]
+    fn smoothing() {
+        // a target jumping away and stopping is reached without overshoot
+        let mut position = vec3!(0., 0., 0.);
+        let mut velocity = vec3!(0., 0., 0.);
+        let target = vec3!(10., 0., 0.);
+        let mut last = 0.;
+        for frame in 1..=120 {
+            position = smooth_damp(position, target, &mut velocity, 0.5, 1. / 60.);
+            assert!(position.x >= last && position.x <= 10.);
+            last = position.x;
+            if frame == 30 {
+                assert!(position.x > 5. && position.x < 9.5, "{}", position.x);
+            }
+        }
+        assert!(10. - position.x < 0.1);
+
+        // the step size barely matters, as the spring is solved exactly
+        let follow = |frames: usize| {
+            let (mut position, mut velocity) = (vec3!(0., 0., 0.), vec3!(0., 0., 0.));
+            for _ in 0..frames {
+                position = smooth_damp(position, vec3!(0., 4., 0.), &mut velocity, 0.3, 0.25 / frames as f32);
+            }
+            position
+        };
+        assert_approx_eq!(follow(8), follow(64), epsilon = 1e-4);
+
+        // heading past the target at speed clamps onto it
+        let mut velocity = vec3!(100., 0., 0.);
+        let out = smooth_damp(vec3!(9., 0., 0.), target, &mut velocity, 0.5, 0.1);
+        assert_eq!((out, velocity), (target, vec3!(0., 0., 0.)));
+        // and a zero smoothing time snaps rather than dividing by zero
+        let mut velocity = vec3!(0., 0., 0.);
+        let out = smooth_damp(vec3!(0., 0., 0.), target, &mut velocity, 0., 0.1);
+        assert!(out.x.is_finite() && (out.x - 10.).abs() < 1e-3);
+    }
+}
@@ -1,9 +1,21 @@
-use std::cmp::PartialEq;
-use std::cmp::{self, Ordering};
-use std::convert::From;
-use std::fmt::{Debug, Display, Formatter, Result};
-use std::marker::Copy;
-use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+use core::cmp::PartialEq;
+use core::cmp::{self, Ordering};
+use core::convert::From;
+use core::fmt::{Debug, Display, Formatter, Result};
+use core::error::Error;
+use core::hash::Hash;
+use core::iter::{Product, Sum};
+use core::marker::Copy;
+use core::num::{IntErrorKind, ParseIntError};
+use core::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+use core::str::FromStr;
+
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::math;
 
 #[macro_export]
 macro_rules! fr {
@@ -15,107 +27,348 @@ macro_rules! fr {
     };
 }
 
-fn gcd(mut n:i128,mut d:i128) -> i128 {
-    assert_ne!(d,0,"dvision by zero in gcd");
-    n = n.abs();
-    d = d.abs();
-    while n != 0 {
-    if n < d {
-      std::mem::swap(&mut n, &mut d);
+/// Signed primitive integer that can back a [`Ratio`]
+pub trait SignedInt:
+    Copy
+    + Ord
+    + Hash
+    + Debug
+    + Display
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + Neg<Output = Self>
+    + Add<Output = Self>
+    + FromStr<Err = ParseIntError>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn div_euclid(self, rhs: Self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+    fn from_i128(value: i128) -> Option<Self>;
+    fn to_i128(self) -> i128;
+    fn to_f64(self) -> f64;
+}
+macro_rules! implSignedInt {
+    ($($type:ty),*) => { $(
+        impl SignedInt for $type {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$type>::checked_add(self, rhs)
+            }
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$type>::checked_sub(self, rhs)
+            }
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$type>::checked_mul(self, rhs)
+            }
+            fn checked_neg(self) -> Option<Self> {
+                <$type>::checked_neg(self)
+            }
+            fn div_euclid(self, rhs: Self) -> Self {
+                <$type>::div_euclid(self, rhs)
+            }
+            fn rem_euclid(self, rhs: Self) -> Self {
+                <$type>::rem_euclid(self, rhs)
+            }
+            fn from_i128(value: i128) -> Option<Self> {
+                <$type>::try_from(value).ok()
+            }
+            fn to_i128(self) -> i128 {
+                self as i128
+            }
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+        )*
+    };
+}
+implSignedInt!(isize, i8, i16, i32, i64, i128);
+
+fn gcd<T: SignedInt>(mut n: T, mut d: T) -> T {
+    assert_ne!(d, T::ZERO, "dvision by zero in gcd");
+    // Work with non-positive values, T::MIN has no positive counterpart
+    if n > T::ZERO {
+        n = -n;
+    }
+    if d > T::ZERO {
+        d = -d;
     }
-    n %= d;
-  }
-  d
+    while n != T::ZERO {
+        // T::MIN % -1 overflows even though the result is 0
+        let rem = if n == -T::ONE { T::ZERO } else { d % n };
+        d = n;
+        n = rem;
+    }
+    d.checked_neg().expect("fraction overflow")
+}
+
+/// Converts an operand to the backing integer, panics if it doesn't fit
+fn int<T: SignedInt, I: TryInto<T> + Display + Copy>(value: I) -> T {
+    value.try_into().unwrap_or_else(|_| {
+        panic!("{value} is out of range for the fraction's integer type")
+    })
 }
 
-#[derive(Debug, Clone, Copy, Eq)]
-pub struct Fraction {
-    numerator: i128,
-    denominator: i128,
+/// Exact fraction over any signed integer width, always kept in lowest terms with a positive denominator.
+///
+/// Every operation panics on overflow of the backing integer, use the `checked_*` methods to handle it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio<T> {
+    pub(crate) numerator: T,
+    pub(crate) denominator: T,
 }
-impl Fraction {
-    pub fn new(num: i128, den: i128) -> Self {
-        assert_ne!(den, 0);
+pub type Fraction = Ratio<i128>;
+
+impl<T: SignedInt> Ratio<T> {
+    pub fn new(num: T, den: T) -> Self {
+        assert_ne!(den, T::ZERO);
         Self {
             numerator: num,
             denominator: den,
         }
         .simplify()
     }
-    fn simplify(mut self) -> Self {
-        let max_factor: i128 = gcd(self.numerator, self.denominator);
+    /// Like [`Ratio::new`] but `None` on a zero denominator or if the reduced value doesn't fit, e.g. `-128/-1` as `i8`
+    pub fn checked_new(num: T, den: T) -> Option<Self> {
+        if den == T::ZERO {
+            return None;
+        }
+        Self {
+            numerator: num,
+            denominator: den,
+        }
+        .checked_simplify()
+    }
+    fn simplify(self) -> Self {
+        self.checked_simplify().expect("fraction overflow")
+    }
+    fn checked_simplify(mut self) -> Option<Self> {
+        // The gcd of 0/MIN or MIN/MIN is |T::MIN| which doesn't fit, both reduce without it
+        if self.numerator == T::ZERO {
+            return Some(Self::from_int(T::ZERO));
+        }
+        if self.numerator == self.denominator {
+            return Some(Self::from_int(T::ONE));
+        }
+        let max_factor = gcd(self.numerator, self.denominator);
 
-        if self.denominator.is_negative() {
-            self.numerator *= -1;
-            self.denominator *= -1;
+        self.numerator = self.numerator / max_factor;
+        self.denominator = self.denominator / max_factor;
+        if self.denominator < T::ZERO {
+            self.numerator = self.numerator.checked_neg()?;
+            self.denominator = self.denominator.checked_neg()?;
         }
-        self.numerator /= max_factor;
-        self.denominator /= max_factor;
-        assert_ne!(self.denominator, 0);
-        self
+        assert_ne!(self.denominator, T::ZERO);
+        Some(self)
     }
     pub fn as_f64(&self) -> f64 {
-        self.numerator as f64 / self.denominator as f64
+        self.numerator.to_f64() / self.denominator.to_f64()
     }
+    #[cfg(feature = "alloc")]
     pub fn as_mixed(&self) -> String {
         let rem = self.numerator % self.denominator;
-        if self.denominator == 1 {
+        if self.denominator == T::ONE {
             return format!("{}", self.numerator);
         }
         if self.numerator < self.denominator {
             return format!("({}/{})", self.numerator, self.denominator);
         }
-        let whole_part = (self.numerator - rem) / self.denominator;
+        let whole_part = self.numerator / self.denominator;
         format!("{whole_part}({rem}/{})", self.denominator)
     }
-    pub fn abs(&self) -> f64 {
-        self.denominator.abs() as f64 / self.numerator as f64
+    pub fn numer(&self) -> T {
+        self.numerator
     }
-}
-
-//==========================================================//
-
-impl Sub<Fraction> for Fraction {
-    type Output = Fraction;
-
-    fn sub(self, rhs: Fraction) -> Self::Output {
+    pub fn denom(&self) -> T {
+        self.denominator
+    }
+    pub fn abs(&self) -> Self {
+        if self.numerator < T::ZERO {
+            -*self
+        } else {
+            *self
+        }
+    }
+    /// `-1`, `0` or `1`
+    pub fn signum(&self) -> Self {
+        Self::from_int(match self.numerator.cmp(&T::ZERO) {
+            Ordering::Less => -T::ONE,
+            Ordering::Equal => T::ZERO,
+            Ordering::Greater => T::ONE,
+        })
+    }
+    pub fn recip(&self) -> Self {
+        Self::from_int(T::ONE) / *self
+    }
+    /// Raises to an integer power, negative exponents raise the reciprocal
+    pub fn pow(&self, exp: i32) -> Self {
+        let base = if exp < 0 { self.recip() } else { *self };
+        // Numerator and denominator are coprime so their powers are too, no need to simplify
+        let pow = |mut base: T| {
+            let mut exp = exp.unsigned_abs();
+            let mut out = T::ONE;
+            while exp > 0 {
+                if exp & 1 == 1 {
+                    out = out.checked_mul(base).expect("fraction overflow in pow");
+                }
+                exp >>= 1;
+                if exp > 0 {
+                    base = base.checked_mul(base).expect("fraction overflow in pow");
+                }
+            }
+            out
+        };
+        Self {
+            numerator: pow(base.numerator),
+            denominator: pow(base.denominator),
+        }
+    }
+    /// Largest integer less than or equal to the fraction
+    pub fn floor(&self) -> Self {
+        Self::from_int(self.numerator.div_euclid(self.denominator))
+    }
+    /// Smallest integer greater than or equal to the fraction
+    pub fn ceil(&self) -> Self {
+        let floor = self.floor();
+        if floor == *self {
+            floor
+        } else {
+            floor + Self::from_int(T::ONE)
+        }
+    }
+    /// Nearest integer, halfway cases round away from zero
+    pub fn round(&self) -> Self {
+        let half = Self::new(T::ONE, T::ONE + T::ONE);
+        let trunc = self.trunc();
+        if (*self - trunc).abs() >= half {
+            trunc + self.signum()
+        } else {
+            trunc
+        }
+    }
+    /// Integer part, rounds towards zero
+    pub fn trunc(&self) -> Self {
+        Self::from_int(self.numerator / self.denominator)
+    }
+    /// Fractional part, has the same sign as the fraction so `trunc() + fract()` is the fraction
+    pub fn fract(&self) -> Self {
+        Self {
+            numerator: self.numerator % self.denominator,
+            denominator: self.denominator,
+        }
+        .simplify()
+    }
+    /// Converts to another backing width, `None` if the values don't fit
+    pub fn cast<U: SignedInt>(self) -> Option<Ratio<U>>
+    where
+        T: TryInto<U>,
+    {
+        Some(Ratio {
+            numerator: self.numerator.try_into().ok()?,
+            denominator: self.denominator.try_into().ok()?,
+        })
+    }
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.checked_add_sub(rhs, T::checked_add)
+    }
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add_sub(rhs, T::checked_sub)
+    }
+    fn checked_add_sub(self, rhs: Self, op: fn(T, T) -> Option<T>) -> Option<Self> {
         if self.denominator == rhs.denominator {
-            return Self {
-                numerator: self.numerator - rhs.numerator,
-                denominator: self.denominator,
+            return Some(
+                Self {
+                    numerator: op(self.numerator, rhs.numerator)?,
+                    denominator: self.denominator,
+                }
+                .simplify(),
+            );
+        }
+        // Going through the lcm of the denominators keeps the intermediate values small
+        let factor = gcd(self.denominator, rhs.denominator);
+        let lhs_scale = rhs.denominator / factor;
+        let rhs_scale = self.denominator / factor;
+        Some(
+            Self {
+                numerator: op(
+                    self.numerator.checked_mul(lhs_scale)?,
+                    rhs.numerator.checked_mul(rhs_scale)?,
+                )?,
+                denominator: self.denominator.checked_mul(lhs_scale)?,
+            }
+            .simplify(),
+        )
+    }
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // Cross cancel first so the products only overflow if the result does
+        if self.numerator == T::ZERO || rhs.numerator == T::ZERO {
+            return Some(Self::from_int(T::ZERO));
+        }
+        let lhs_factor = gcd(self.numerator, rhs.denominator);
+        let rhs_factor = gcd(rhs.numerator, self.denominator);
+        Some(Self {
+            numerator: (self.numerator / lhs_factor).checked_mul(rhs.numerator / rhs_factor)?,
+            denominator: (self.denominator / rhs_factor)
+                .checked_mul(rhs.denominator / lhs_factor)?,
+        })
+    }
+    /// `None` on overflow or when dividing by zero
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.numerator == T::ZERO {
+            return None;
+        }
+        let reciprocal = if rhs.numerator < T::ZERO {
+            Self {
+                numerator: rhs.denominator.checked_neg()?,
+                denominator: rhs.numerator.checked_neg()?,
             }
-            .simplify();
         } else {
             Self {
-                numerator: self.numerator * rhs.denominator - rhs.numerator * self.denominator,
-                denominator: self.denominator * rhs.denominator,
+                numerator: rhs.denominator,
+                denominator: rhs.numerator,
             }
-            .simplify()
+        };
+        self.checked_mul(reciprocal)
+    }
+    fn from_int(value: T) -> Self {
+        Self {
+            numerator: value,
+            denominator: T::ONE,
         }
     }
 }
+
+//==========================================================//
+
+impl<T: SignedInt> Sub<Ratio<T>> for Ratio<T> {
+    type Output = Ratio<T>;
+
+    fn sub(self, rhs: Ratio<T>) -> Self::Output {
+        self.checked_sub(rhs).expect("fraction overflow in subtraction")
+    }
+}
 macro_rules! implSub {
     ($($type:ty),*) => { $(
-        impl Sub<$type> for Fraction {
-            type Output = Fraction;
+        impl<T: SignedInt> Sub<$type> for Ratio<T> where $type: TryInto<T> {
+            type Output = Ratio<T>;
 
             fn sub(self, rhs: $type) -> Self::Output {
-                Self {
-                    numerator: self.numerator - rhs as i128 * self.denominator,
-                    denominator: self.denominator,
-                }
-                .simplify()
+                self - Ratio::from_int(int(rhs))
             }
         }
-        impl Sub<Fraction> for $type {
-            type Output = Fraction;
+        impl<T: SignedInt> Sub<Ratio<T>> for $type where $type: TryInto<T> {
+            type Output = Ratio<T>;
 
-            fn sub(self, rhs: Fraction) -> Self::Output {
-                Fraction {
-                    numerator: self as i128 * rhs.denominator - rhs.numerator,
-                    denominator: rhs.denominator,
-                }
-                .simplify()
+            fn sub(self, rhs: Ratio<T>) -> Self::Output {
+                Ratio::from_int(int(self)) - rhs
             }
         }
         )*
@@ -125,47 +378,27 @@ implSub!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
 
 //==========================================================//
 
-impl Add<Fraction> for Fraction {
-    type Output = Fraction;
+impl<T: SignedInt> Add<Ratio<T>> for Ratio<T> {
+    type Output = Ratio<T>;
 
-    fn add(self, rhs: Fraction) -> Self::Output {
-        if self.denominator == rhs.denominator {
-            return Self {
-                numerator: self.numerator + rhs.numerator,
-                denominator: self.denominator,
-            }
-            .simplify();
-        } else {
-            Self {
-                numerator: self.numerator * rhs.denominator + rhs.numerator * self.denominator,
-                denominator: self.denominator * rhs.denominator,
-            }
-            .simplify()
-        }
+    fn add(self, rhs: Ratio<T>) -> Self::Output {
+        self.checked_add(rhs).expect("fraction overflow in addition")
     }
 }
 macro_rules! implAdd {
     ($($type:ty),*) => { $(
-        impl Add<$type> for Fraction {
-            type Output = Fraction;
+        impl<T: SignedInt> Add<$type> for Ratio<T> where $type: TryInto<T> {
+            type Output = Ratio<T>;
 
             fn add(self, rhs: $type) -> Self::Output {
-                Self {
-                    numerator: self.numerator + rhs as i128 * self.denominator,
-                    denominator: self.denominator,
-                }
-                .simplify()
+                self + Ratio::from_int(int(rhs))
             }
         }
-        impl Add<Fraction> for $type {
-            type Output = Fraction;
+        impl<T: SignedInt> Add<Ratio<T>> for $type where $type: TryInto<T> {
+            type Output = Ratio<T>;
 
-            fn add(self, rhs: Fraction) -> Self::Output {
-                Fraction {
-                    numerator: self as i128 * rhs.denominator + rhs.numerator,
-                    denominator: rhs.denominator,
-                }
-                .simplify()
+            fn add(self, rhs: Ratio<T>) -> Self::Output {
+                Ratio::from_int(int(self)) + rhs
             }
         }
         )*
@@ -175,39 +408,27 @@ implAdd!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
 
 //==========================================================//
 
-impl Mul<Fraction> for Fraction {
-    type Output = Fraction;
+impl<T: SignedInt> Mul<Ratio<T>> for Ratio<T> {
+    type Output = Ratio<T>;
 
-    fn mul(self, rhs: Fraction) -> Self::Output {
-        Self {
-            numerator: self.numerator * rhs.numerator,
-            denominator: self.denominator * rhs.denominator,
-        }
-        .simplify()
+    fn mul(self, rhs: Ratio<T>) -> Self::Output {
+        self.checked_mul(rhs).expect("fraction overflow in multiplication")
     }
 }
 macro_rules! implMul {
     ($($type:ty),*) => { $(
-        impl Mul<$type> for Fraction {
-            type Output = Fraction;
+        impl<T: SignedInt> Mul<$type> for Ratio<T> where $type: TryInto<T> {
+            type Output = Ratio<T>;
 
             fn mul(self, rhs: $type) -> Self::Output {
-                Self {
-                    numerator: self.numerator * rhs as i128,
-                    denominator: self.denominator,
-                }
-                .simplify()
+                self * Ratio::from_int(int(rhs))
             }
         }
-        impl Mul<Fraction> for $type {
-            type Output = Fraction;
+        impl<T: SignedInt> Mul<Ratio<T>> for $type where $type: TryInto<T> {
+            type Output = Ratio<T>;
 
-            fn mul(self, rhs: Fraction) -> Self::Output {
-                Fraction {
-                    numerator: rhs.numerator * self as i128,
-                    denominator: rhs.denominator,
-                }
-                .simplify()
+            fn mul(self, rhs: Ratio<T>) -> Self::Output {
+                Ratio::from_int(int(self)) * rhs
             }
         }
         )*
@@ -217,41 +438,28 @@ implMul!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
 
 //==========================================================//
 
-impl Div<Fraction> for Fraction {
-    type Output = Fraction;
+impl<T: SignedInt> Div<Ratio<T>> for Ratio<T> {
+    type Output = Ratio<T>;
 
-    fn div(self, rhs: Fraction) -> Self::Output {
-        assert_ne!(self.denominator * rhs.numerator, 0);
-        Self {
-            numerator: self.numerator * rhs.denominator,
-            denominator: self.denominator * rhs.numerator,
-        }
-        .simplify()
+    fn div(self, rhs: Ratio<T>) -> Self::Output {
+        assert!(rhs.numerator != T::ZERO, "division by zero");
+        self.checked_div(rhs).expect("fraction overflow in division")
     }
 }
 macro_rules! implDiv {
     ($($type:ty),*) => { $(
-        impl Div<Fraction> for $type {
-            type Output = Fraction;
+        impl<T: SignedInt> Div<Ratio<T>> for $type where $type: TryInto<T> {
+            type Output = Ratio<T>;
 
-            fn div(self, rhs: Fraction) -> Self::Output {
-                Fraction {
-                    numerator: (self as i128) * rhs.denominator,
-                    denominator: rhs.numerator,
-                }
-                .simplify()
+            fn div(self, rhs: Ratio<T>) -> Self::Output {
+                Ratio::from_int(int(self)) / rhs
             }
         }
-        impl Div<$type> for Fraction {
-            type Output = Fraction;
+        impl<T: SignedInt> Div<$type> for Ratio<T> where $type: TryInto<T> {
+            type Output = Ratio<T>;
 
             fn div(self, rhs: $type) -> Self::Output {
-                assert_ne!(self.denominator * rhs as i128, 0);
-                Self {
-                    numerator: self.numerator,
-                    denominator: self.denominator * rhs as i128,
-                }
-                .simplify()
+                self / Ratio::from_int(int(rhs))
             }
         }
         )*
@@ -261,48 +469,102 @@ implDiv!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
 
 //==========================================================//
 
-impl SubAssign for Fraction {
+/// Remainder of truncated division, `a % b = a - b * (a / b).trunc()`, takes the sign of `a`
+impl<T: SignedInt> Rem<Ratio<T>> for Ratio<T> {
+    type Output = Ratio<T>;
+
+    fn rem(self, rhs: Ratio<T>) -> Self::Output {
+        self - rhs * (self / rhs).trunc()
+    }
+}
+macro_rules! implRem {
+    ($($type:ty),*) => { $(
+        impl<T: SignedInt> Rem<Ratio<T>> for $type where $type: TryInto<T> {
+            type Output = Ratio<T>;
+
+            fn rem(self, rhs: Ratio<T>) -> Self::Output {
+                Ratio::from_int(int(self)) % rhs
+            }
+        }
+        impl<T: SignedInt> Rem<$type> for Ratio<T> where $type: TryInto<T> {
+            type Output = Ratio<T>;
+
+            fn rem(self, rhs: $type) -> Self::Output {
+                self % Ratio::from_int(int(rhs))
+            }
+        }
+        )*
+    };
+}
+implRem!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
+
+//==========================================================//
+
+impl<T: SignedInt> Neg for Ratio<T> {
+    type Output = Ratio<T>;
+
+    fn neg(self) -> Self::Output {
+        Ratio {
+            numerator: self.numerator.checked_neg().expect("fraction overflow in negation"),
+            denominator: self.denominator,
+        }
+    }
+}
+
+//==========================================================//
+
+impl<T: SignedInt> SubAssign for Ratio<T> {
     fn sub_assign(&mut self, rhs: Self) {
         *self = *self - rhs;
     }
 }
-impl AddAssign for Fraction {
+impl<T: SignedInt> AddAssign for Ratio<T> {
     fn add_assign(&mut self, rhs: Self) {
         *self = *self + rhs;
     }
 }
-impl MulAssign for Fraction {
+impl<T: SignedInt> MulAssign for Ratio<T> {
     fn mul_assign(&mut self, rhs: Self) {
         *self = *self * rhs;
     } 
 }
-impl DivAssign for Fraction {
+impl<T: SignedInt> DivAssign for Ratio<T> {
     fn div_assign(&mut self, rhs: Self) {
         *self = *self / rhs;
     } 
 }
+impl<T: SignedInt> RemAssign for Ratio<T> {
+    fn rem_assign(&mut self, rhs: Self) {
+        *self = *self % rhs;
+    }
+}
 macro_rules! implAssign {
     ($($type:ty),*) => { $(
-        impl SubAssign<$type> for Fraction {
+        impl<T: SignedInt> SubAssign<$type> for Ratio<T> where $type: TryInto<T> {
             fn sub_assign(&mut self, rhs: $type) {
                 *self = *self - rhs;
             }
         }
-        impl AddAssign<$type> for Fraction {
+        impl<T: SignedInt> AddAssign<$type> for Ratio<T> where $type: TryInto<T> {
             fn add_assign(&mut self, rhs: $type) {
                 *self = *self + rhs;
             }
         }
-        impl MulAssign<$type> for Fraction {
+        impl<T: SignedInt> MulAssign<$type> for Ratio<T> where $type: TryInto<T> {
             fn mul_assign(&mut self, rhs: $type) {
                 *self = *self * rhs;
             }
         }
-        impl DivAssign<$type> for Fraction {
+        impl<T: SignedInt> DivAssign<$type> for Ratio<T> where $type: TryInto<T> {
             fn div_assign(&mut self, rhs: $type) {
                 *self = *self / rhs;
             }
         }
+        impl<T: SignedInt> RemAssign<$type> for Ratio<T> where $type: TryInto<T> {
+            fn rem_assign(&mut self, rhs: $type) {
+                *self = *self % rhs;
+            }
+        }
         )*
     };
 }
@@ -310,54 +572,76 @@ implAssign!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
 
 //==========================================================//
 
-impl PartialEq for Fraction {
-    fn eq(&self, other: &Self) -> bool {
-        self.denominator == other.denominator && self.numerator == other.numerator
-    }
-}
-impl PartialOrd for Fraction {
+impl<T: SignedInt> PartialOrd for Ratio<T> {
     fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
         Some(self.cmp(other))
     }
 }
-impl Ord for Fraction {
+impl<T: SignedInt> Ord for Ratio<T> {
     fn cmp(&self, other: &Self) -> cmp::Ordering {
         if self.denominator == other.denominator {
             return self.numerator.cmp(&other.numerator);
-        } else {
-            return (self.numerator * other.denominator).cmp(&(other.numerator * self.denominator));
+        }
+        if let (Some(lhs), Some(rhs)) = (
+            self.numerator.checked_mul(other.denominator),
+            other.numerator.checked_mul(self.denominator),
+        ) {
+            return lhs.cmp(&rhs);
+        }
+        // Cross products overflow, compare the continued fraction expansions instead
+        let (mut a_num, mut a_den) = (self.numerator, self.denominator);
+        let (mut b_num, mut b_den) = (other.numerator, other.denominator);
+        let mut reversed = false;
+        loop {
+            let a_int = a_num.div_euclid(a_den);
+            let b_int = b_num.div_euclid(b_den);
+            let a_rem = a_num.rem_euclid(a_den);
+            let b_rem = b_num.rem_euclid(b_den);
+            let ord = match (a_int.cmp(&b_int), a_rem == T::ZERO, b_rem == T::ZERO) {
+                (Ordering::Equal, true, true) => Ordering::Equal,
+                (Ordering::Equal, true, false) => Ordering::Less,
+                (Ordering::Equal, false, true) => Ordering::Greater,
+                (Ordering::Equal, false, false) => {
+                    // Compare the reciprocals of the remainders, which flips the order
+                    (a_num, a_den, b_num, b_den) = (a_den, a_rem, b_den, b_rem);
+                    reversed = !reversed;
+                    continue;
+                }
+                (ord, _, _) => ord,
+            };
+            return if reversed { ord.reverse() } else { ord };
         }
     }
 }
 macro_rules! implCmp {
     ($($type:ty),*) => { $(
-        impl PartialEq<$type> for Fraction {
+        impl<T: SignedInt> PartialEq<$type> for Ratio<T> where $type: TryInto<T> {
             fn eq(&self, other: &$type) -> bool {
-                self.denominator == 1 && self.numerator == *other as i128
+                match (*other).try_into() {
+                    Ok(other) => self.denominator == T::ONE && self.numerator == other,
+                    Err(_) => false,
+                }
             }
         }
-        impl PartialOrd<$type> for Fraction {
+        impl<T: SignedInt> PartialOrd<$type> for Ratio<T> where $type: TryInto<T> {
             fn partial_cmp(&self, other: &$type) -> Option<Ordering> {
-                if self.denominator == 1 {
-                    return Some(self.numerator.cmp(&(*other as i128)));
-                } else {
-                    return Some(self.numerator.cmp(&(*other as i128 * self.denominator)));
+                match (*other).try_into() {
+                    Ok(other) => Some(self.cmp(&Ratio::from_int(other))),
+                    // Out of range for T means past every value a Ratio<T> can hold
+                    Err(_) if *other > <$type>::default() => Some(Ordering::Less),
+                    Err(_) => Some(Ordering::Greater),
                 }
             }
         }
 
-        impl PartialEq<Fraction> for $type {
-            fn eq(&self, other: &Fraction) -> bool {
-                other.denominator == 1 && other.numerator == *self as i128
+        impl<T: SignedInt> PartialEq<Ratio<T>> for $type where $type: TryInto<T> {
+            fn eq(&self, other: &Ratio<T>) -> bool {
+                other == self
             }
         }
-        impl PartialOrd<Fraction> for $type {
-            fn partial_cmp(&self, other: &Fraction) -> Option<Ordering> {
-                if other.denominator == 1 {
-                    return Some((*self as i128).cmp(&other.numerator));
-                } else {
-                    return Some(((*self as i128) * other.denominator).cmp(&other.numerator));
-                }
+        impl<T: SignedInt> PartialOrd<Ratio<T>> for $type where $type: TryInto<T> {
+            fn partial_cmp(&self, other: &Ratio<T>) -> Option<Ordering> {
+                other.partial_cmp(self).map(Ordering::reverse)
             }
         }
         )*
@@ -369,12 +653,9 @@ implCmp!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
 
 macro_rules! implFrom {
     ($($type:ty),*) => { $(
-        impl From<$type> for Fraction {
+        impl<T: SignedInt> From<$type> for Ratio<T> where $type: TryInto<T> {
             fn from(value: $type) -> Self {
-                Fraction {
-                    numerator: value as i128,
-                    denominator: 1,
-                }
+                Ratio::from_int(int(value))
             }
         }
         )*
@@ -384,9 +665,39 @@ implFrom!(usize, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128);
 
 //==========================================================//
 
-impl Display for Fraction {
+impl<T: SignedInt> Sum for Ratio<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Ratio::from_int(T::ZERO), |acc, x| acc + x)
+    }
+}
+impl<'a, T: SignedInt> Sum<&'a Ratio<T>> for Ratio<T> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Ratio::from_int(T::ZERO), |acc, x| acc + *x)
+    }
+}
+impl<T: SignedInt> Product for Ratio<T> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Ratio::from_int(T::ONE), |acc, x| acc * x)
+    }
+}
+impl<'a, T: SignedInt> Product<&'a Ratio<T>> for Ratio<T> {
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Ratio::from_int(T::ONE), |acc, x| acc * *x)
+    }
+}
+
+//==========================================================//
+
+/// `{:#}` prints the decimal expansion with the repeating part in parentheses, e.g. `0.1(6)`.
+/// The precision caps the number of digits after the point (100 by default).
+impl<T: SignedInt> Display for Ratio<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        if self.denominator == 1 {
+        #[cfg(feature = "alloc")]
+        if f.alternate() {
+            let max_digits = f.precision().unwrap_or(DEFAULT_DECIMAL_DIGITS);
+            return write!(f, "{}", self.decimal_expansion_capped(max_digits));
+        }
+        if self.denominator == T::ONE {
             write!(f, "{}", self.numerator)?;
         } else {
             write!(f, "({}/{})", self.numerator, self.denominator)?;
@@ -395,10 +706,463 @@ impl Display for Fraction {
     }
 }
 
+//==========================================================//
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FractionErrorKind {
+    /// Nothing to parse
+    Empty,
+    /// A character that isn't a digit where one is expected
+    InvalidDigit,
+    /// Digits are fine but they don't form a fraction, e.g. `(1/2` or `0.1()`
+    InvalidFormat,
+    /// `n/0`
+    ZeroDenominator,
+    /// The value doesn't fit in the backing integer
+    Overflow,
+}
+
+/// Error returned when parsing a [`Ratio`] from a string fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFractionError {
+    kind: FractionErrorKind,
+}
+impl ParseFractionError {
+    pub fn kind(&self) -> FractionErrorKind {
+        self.kind
+    }
+}
+impl Display for ParseFractionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let msg = match self.kind {
+            FractionErrorKind::Empty => "cannot parse fraction from empty string",
+            FractionErrorKind::InvalidDigit => "invalid digit found in fraction",
+            FractionErrorKind::InvalidFormat => "invalid fraction format",
+            FractionErrorKind::ZeroDenominator => "fraction has a zero denominator",
+            FractionErrorKind::Overflow => "fraction too large for its integer type",
+        };
+        write!(f, "{msg}")
+    }
+}
+impl Error for ParseFractionError {}
+
+impl From<FractionErrorKind> for ParseFractionError {
+    fn from(kind: FractionErrorKind) -> Self {
+        ParseFractionError { kind }
+    }
+}
+impl From<ParseIntError> for ParseFractionError {
+    fn from(err: ParseIntError) -> Self {
+        let kind = match err.kind() {
+            IntErrorKind::Empty => FractionErrorKind::Empty,
+            IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => FractionErrorKind::Overflow,
+            _ => FractionErrorKind::InvalidDigit,
+        };
+        ParseFractionError { kind }
+    }
+}
+
+/// Parses an integer, unlike `T::from_str` a sign is only allowed when `signed` is set
+fn parse_int<T: SignedInt>(s: &str, signed: bool) -> core::result::Result<T, ParseFractionError> {
+    let digits = if signed { s.strip_prefix(['-', '+']).unwrap_or(s) } else { s };
+    if digits.is_empty() {
+        return Err(FractionErrorKind::Empty.into());
+    }
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(FractionErrorKind::InvalidDigit.into());
+    }
+    Ok(s.parse()?)
+}
+
+fn pow10<T: SignedInt>(exp: usize) -> Option<T> {
+    let ten = T::from_i128(10)?;
+    let mut out = T::ONE;
+    for _ in 0..exp {
+        out = out.checked_mul(ten)?;
+    }
+    Some(out)
+}
+
+impl<T: SignedInt> Ratio<T> {
+    /// `n/d` with optional signs on both parts
+    fn parse_simple(s: &str) -> core::result::Result<Self, ParseFractionError> {
+        let (num, den) = s.split_once('/').ok_or(FractionErrorKind::InvalidFormat)?;
+        let num = parse_int(num.trim(), true)?;
+        let den = parse_int(den.trim(), true)?;
+        if den == T::ZERO {
+            return Err(FractionErrorKind::ZeroDenominator.into());
+        }
+        Ratio::checked_new(num, den).ok_or(FractionErrorKind::Overflow.into())
+    }
+    /// Integers and decimals with an optional repeating part, `-12`, `0.125`, `0.1(6)`
+    fn parse_decimal(s: &str) -> core::result::Result<Self, ParseFractionError> {
+        let overflow = || ParseFractionError::from(FractionErrorKind::Overflow);
+        let negative = s.starts_with('-');
+        let unsigned = s.strip_prefix(['-', '+']).unwrap_or(s);
+        let (int_part, frac_part) = match unsigned.split_once('.') {
+            Some(parts) => parts,
+            None => return Ok(Ratio::from_int(parse_int(s, true)?)),
+        };
+        let (fixed, repeating) = match frac_part.split_once('(') {
+            Some((fixed, rest)) => {
+                let repeating = rest.strip_suffix(')').ok_or(FractionErrorKind::InvalidFormat)?;
+                if repeating.is_empty() {
+                    return Err(FractionErrorKind::InvalidFormat.into());
+                }
+                (fixed, repeating)
+            }
+            None => (frac_part, ""),
+        };
+        if int_part.is_empty() && fixed.is_empty() && repeating.is_empty() {
+            return Err(FractionErrorKind::Empty.into());
+        }
+
+        let mut out = if int_part.is_empty() {
+            Ratio::from_int(T::ZERO)
+        } else {
+            Ratio::from_int(parse_int::<T>(int_part, false)?)
+        };
+        let fixed_scale = pow10::<T>(fixed.len()).ok_or_else(overflow)?;
+        if !fixed.is_empty() {
+            let digits = Ratio::new(parse_int(fixed, false)?, fixed_scale);
+            out = out.checked_add(digits).ok_or_else(overflow)?;
+        }
+        if !repeating.is_empty() {
+            // 0.(abc) = abc / 999, shifted past the fixed digits
+            let nines = pow10::<T>(repeating.len())
+                .and_then(|scale| scale.checked_sub(T::ONE))
+                .ok_or_else(overflow)?;
+            let den = nines.checked_mul(fixed_scale).ok_or_else(overflow)?;
+            let digits = Ratio::new(parse_int(repeating, false)?, den);
+            out = out.checked_add(digits).ok_or_else(overflow)?;
+        }
+        if negative {
+            out.numerator = out.numerator.checked_neg().ok_or_else(overflow)?;
+        }
+        Ok(out)
+    }
+}
+
+/// Accepts everything `Display` and `as_mixed()` produce (`3/4`, `(-7/2)`, `2(1/3)`),
+/// integers, finite decimals (`0.125`) and repeating decimals (`0.(3)`, `0.1(6)`).
+impl<T: SignedInt> FromStr for Ratio<T> {
+    type Err = ParseFractionError;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(FractionErrorKind::Empty.into());
+        }
+        if !s.contains('/') {
+            return Ratio::parse_decimal(s);
+        }
+        let inner = match (s.find('('), s.strip_suffix(')')) {
+            (Some(open), Some(inner)) => &inner[open + 1..],
+            (None, None) => return Ratio::parse_simple(s),
+            _ => return Err(FractionErrorKind::InvalidFormat.into()),
+        };
+        let whole = s[..s.find('(').unwrap()].trim();
+        if whole.is_empty() {
+            return Ratio::parse_simple(inner);
+        }
+        // Mixed number, the sign of the whole part applies to the fraction too
+        let whole: T = parse_int(whole, true)?;
+        let (num, den) = inner.split_once('/').ok_or(FractionErrorKind::InvalidFormat)?;
+        let num: T = parse_int(num.trim(), false)?;
+        let den: T = parse_int(den.trim(), false)?;
+        if den == T::ZERO {
+            return Err(FractionErrorKind::ZeroDenominator.into());
+        }
+        let num = if s.starts_with('-') { -num } else { num };
+        Ratio::checked_new(num, den)
+            .and_then(|part| Ratio::from_int(whole).checked_add(part))
+            .ok_or(FractionErrorKind::Overflow.into())
+    }
+}
+
+//==========================================================//
+
+/// Exact value of a finite `x` as `num / 2^k` with `k <= 126`, `None` if `|x| >= 2^127`.
+/// Values needing more than 126 fractional bits are rounded, an error below 2^-127.
+fn f64_as_dyadic(x: f64) -> Option<(i128, i128)> {
+    let exp = ((x.to_bits() >> 52) & 0x7ff) as i32 - 1023;
+    if exp > 126 {
+        return None;
+    }
+    // |x| < 2^(exp+1) so the numerator stays below 2^127
+    let k = i32::min(126, 126 - exp);
+    // 2^k built from its exponent bits, exact for every k used here
+    let scale = f64::from_bits(((1023 + k) as u64) << 52);
+    let num = math::round(x * scale) as i128;
+    Some((num, 1 << k))
+}
+
+impl<T: SignedInt> Ratio<T> {
+    /// Best rational approximation of `x` with a denominator of at most `max_denominator`.
+    ///
+    /// Returns `None` for NaN and infinities, and when the result doesn't fit in `T`.
+    pub fn from_f64(x: f64, max_denominator: T) -> Option<Self> {
+        assert!(max_denominator >= T::ONE, "max_denominator has to be at least 1");
+        if !x.is_finite() {
+            return None;
+        }
+        let max_den = max_denominator.to_i128();
+        let (mut n, mut d) = f64_as_dyadic(x)?;
+
+        // Convergents p0/q0, p1/q1 of the continued fraction of n/d
+        let (mut p0, mut q0, mut p1, mut q1): (i128, i128, i128, i128) = (0, 1, 1, 0);
+        loop {
+            let a = n.div_euclid(d);
+            let next = a
+                .checked_mul(q1)
+                .and_then(|aq| aq.checked_add(q0))
+                .filter(|q2| *q2 <= max_den)
+                .and_then(|q2| Some((a.checked_mul(p1)?.checked_add(p0)?, q2)));
+            let Some((p2, q2)) = next else {
+                // The next convergent is out of reach, the answer is either p1/q1 or the
+                // largest semiconvergent (p0 + k*p1)/(q0 + k*q1) that fits.
+                // With t = n/d the semiconvergent is closer only if t < 2k + q0/q1.
+                let k = (max_den - q0) / q1;
+                let rem = n - a * d;
+                let semi_closer = match k.checked_mul(2).map_or(Ordering::Less, |k2| a.cmp(&k2)) {
+                    Ordering::Less => true,
+                    Ordering::Greater => false,
+                    Ordering::Equal => Ratio::new(rem, d) < Ratio::new(q0, q1),
+                };
+                let semi = k.checked_mul(p1).and_then(|kp| kp.checked_add(p0));
+                match semi {
+                    Some(num) if semi_closer => return Ratio::from_i128_parts(num, q0 + k * q1),
+                    _ => break,
+                }
+            };
+            (p0, q0, p1, q1) = (p1, q1, p2, q2);
+            (n, d) = (d, n - a * d);
+            if d == 0 {
+                break;
+            }
+        }
+        Ratio::from_i128_parts(p1, q1)
+    }
+    /// Fraction with the smallest denominator within `tolerance` of `x`.
+    ///
+    /// Returns `None` for NaN and infinities, a NaN or negative tolerance, and when the
+    /// result doesn't fit in `T`.
+    pub fn approximate(x: f64, tolerance: f64) -> Option<Self> {
+        if !x.is_finite() || tolerance.is_nan() || tolerance < 0. {
+            return None;
+        }
+        let (mut n, mut d) = f64_as_dyadic(x)?;
+        let close = |p: i128, q: i128| (x - p as f64 / q as f64).abs() <= tolerance;
+
+        let (mut p0, mut q0, mut p1, mut q1): (i128, i128, i128, i128) = (0, 1, 1, 0);
+        loop {
+            let a = n.div_euclid(d);
+            let p2 = a.checked_mul(p1)?.checked_add(p0)?;
+            let q2 = a.checked_mul(q1)?.checked_add(q0)?;
+            if close(p2, q2) {
+                // Semiconvergents between p1/q1 and p2/q2 get closer as k grows,
+                // binary search the first one that is close enough
+                let (mut lo, mut hi) = (if q1 == 0 { a } else { 1 }, a);
+                while lo < hi {
+                    let k = lo + (hi - lo) / 2;
+                    if close(p0 + k * p1, q0 + k * q1) {
+                        hi = k;
+                    } else {
+                        lo = k + 1;
+                    }
+                }
+                return Ratio::from_i128_parts(p0 + lo * p1, q0 + lo * q1);
+            }
+            (p0, q0, p1, q1) = (p1, q1, p2, q2);
+            (n, d) = (d, n - a * d);
+            if d == 0 {
+                // Exact value reached and still not close, only possible through float rounding
+                return Ratio::from_i128_parts(p1, q1);
+            }
+        }
+    }
+    fn from_i128_parts(num: i128, den: i128) -> Option<Self> {
+        Some(Ratio::new(T::from_i128(num)?, T::from_i128(den)?))
+    }
+    /// Simple continued fraction `[a0; a1, a2, ...]`, `a0` is the floor and every later term is positive
+    #[cfg(feature = "alloc")]
+    pub fn continued_fraction(&self) -> Vec<T> {
+        let mut out = vec![];
+        let (mut n, mut d) = (self.numerator, self.denominator);
+        while d != T::ZERO {
+            out.push(n.div_euclid(d));
+            (n, d) = (d, n.rem_euclid(d));
+        }
+        out
+    }
+    /// Inverse of [`Ratio::continued_fraction`]. `None` without terms, if a term after the
+    /// first isn't positive or if the value overflows `T`.
+    pub fn from_continued_fraction(terms: &[T]) -> Option<Self> {
+        if terms.is_empty() || terms[1..].iter().any(|&term| term <= T::ZERO) {
+            return None;
+        }
+        let (mut p0, mut q0, mut p1, mut q1) = (T::ZERO, T::ONE, T::ONE, T::ZERO);
+        for term in terms {
+            let step = |prev: T, prev_prev: T| term.checked_mul(prev)?.checked_add(prev_prev);
+            (p0, q0, p1, q1) = (p1, q1, step(p1, p0)?, step(q1, q0)?);
+        }
+        Ratio::checked_new(p1, q1)
+    }
+}
+
+//==========================================================//
+
+#[cfg(feature = "alloc")]
+const DEFAULT_DECIMAL_DIGITS: usize = 100;
+
+#[cfg(feature = "alloc")]
+/// Decimal form of a [`Ratio`] split into its parts, `-1.2(34)` is
+/// `negative`, `integer = -1`, `non_repeating = [2]`, `repeating = [3, 4]`.
+///
+/// `Display` prints that form and round trips through `FromStr`, a precision (`{:.N}`)
+/// caps the digits after the point and marks a cut off expansion with `...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecimalExpansion<T> {
+    pub negative: bool,
+    /// Integer part, truncated towards zero
+    pub integer: T,
+    pub non_repeating: Vec<u8>,
+    pub repeating: Vec<u8>,
+    /// The digits were cut off before the expansion ended or its cycle was found,
+    /// `non_repeating` then holds all the digits that were computed.
+    pub truncated: bool,
+}
+#[cfg(feature = "alloc")]
+impl<T: SignedInt> DecimalExpansion<T> {
+    fn write_capped(&self, f: &mut Formatter<'_>, max_digits: usize) -> Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", self.integer.to_i128().unsigned_abs())?;
+        let digit_count = self.non_repeating.len() + self.repeating.len();
+        if digit_count == 0 && !self.truncated {
+            return Ok(());
+        }
+        write!(f, ".")?;
+        if digit_count <= max_digits && !self.truncated {
+            for digit in &self.non_repeating {
+                write!(f, "{digit}")?;
+            }
+            if !self.repeating.is_empty() {
+                write!(f, "(")?;
+                for digit in &self.repeating {
+                    write!(f, "{digit}")?;
+                }
+                write!(f, ")")?;
+            }
+            return Ok(());
+        }
+        // Unroll the cycle up to the cap
+        let digits = self
+            .non_repeating
+            .iter()
+            .chain(self.repeating.iter().cycle())
+            .take(max_digits);
+        for digit in digits {
+            write!(f, "{digit}")?;
+        }
+        write!(f, "...")
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T: SignedInt> Display for DecimalExpansion<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        self.write_capped(f, f.precision().unwrap_or(usize::MAX))
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// One step of long division, `r < d`. Falls back to repeated modular addition when `10 * r` overflows.
+fn next_digit(r: u128, d: u128) -> (u8, u128) {
+    if let Some(r10) = r.checked_mul(10) {
+        return ((r10 / d) as u8, r10 % d);
+    }
+    let (mut digit, mut acc) = (0, 0u128);
+    for _ in 0..10 {
+        if acc >= d - r {
+            acc -= d - r;
+            digit += 1;
+        } else {
+            acc += r;
+        }
+    }
+    (digit, acc)
+}
+
+#[cfg(feature = "alloc")]
+impl<T: SignedInt> Ratio<T> {
+    /// Exact decimal expansion. The repeating cycle can be almost as long as the denominator,
+    /// use [`Ratio::decimal_expansion_capped`] for large denominators.
+    pub fn decimal_expansion(&self) -> DecimalExpansion<T> {
+        self.decimal_expansion_capped(usize::MAX)
+    }
+    /// Decimal expansion computing at most `max_digits` digits after the point
+    pub fn decimal_expansion_capped(&self, max_digits: usize) -> DecimalExpansion<T> {
+        let d = self.denominator.to_i128() as u128;
+        let mut r = (self.numerator % self.denominator).to_i128().unsigned_abs();
+        let mut out = DecimalExpansion {
+            negative: self.numerator < T::ZERO,
+            integer: self.numerator / self.denominator,
+            non_repeating: vec![],
+            repeating: vec![],
+            truncated: false,
+        };
+
+        // The digits only start repeating after max(twos, fives) of the denominator
+        let (mut twos, mut fives, mut rest) = (0, 0, d);
+        while rest % 2 == 0 {
+            rest /= 2;
+            twos += 1;
+        }
+        while rest % 5 == 0 {
+            rest /= 5;
+            fives += 1;
+        }
+        let prefix_len = usize::max(twos, fives);
+
+        while r != 0 && out.non_repeating.len() < prefix_len {
+            if out.non_repeating.len() == max_digits {
+                out.truncated = true;
+                return out;
+            }
+            let (digit, next) = next_digit(r, d);
+            out.non_repeating.push(digit);
+            r = next;
+        }
+        if r == 0 {
+            return out;
+        }
+
+        // From here the remainders are purely periodic, the cycle ends when r comes back
+        let cycle_start = r;
+        loop {
+            if out.non_repeating.len() + out.repeating.len() == max_digits {
+                out.non_repeating.append(&mut out.repeating);
+                out.truncated = true;
+                return out;
+            }
+            let (digit, next) = next_digit(r, d);
+            out.repeating.push(digit);
+            r = next;
+            if r == cycle_start {
+                return out;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    #[cfg(feature = "alloc")]
+    use alloc::string::ToString;
 
+    #[cfg(feature = "alloc")]
     macro_rules! assert_print {
         ($left:expr, $op:tt, $right:expr,$($type:ty),*) => {
             assert!($left $op $right, "{} {} {}", $left, stringify!($op), $right);
@@ -406,6 +1170,7 @@ mod test {
     }
 
     #[test]
+    #[cfg(feature = "alloc")]
     fn test() {
         assert_eq!(fr!(10, 2), fr!(5, 1));
         assert_print!(fr!(152541421414141124124124, 152541421414141124124124),==,1,
@@ -438,7 +1203,233 @@ mod test {
 
         assert_eq!(fr!(1, 3).as_f64(), 1. / 3.);
 
+        #[cfg(feature = "std")]
         println!("{}", fr!(21231, 421).as_mixed());
     }
-}
 
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn widths() {
+        let a = Ratio::<i32>::new(6, -4);
+        assert_eq!(format!("{a}"), "(-3/2)");
+        assert_eq!(a + 2, Ratio::new(1, 2));
+        assert_eq!(a * Ratio::new(2, 3), -1);
+        assert!(a < 0u8 && 0i64 > a);
+        assert_eq!(a.cast::<i128>(), Some(fr!(-3, 2)));
+
+        // Cross cancelling keeps results that fit in range
+        let b = Ratio::<i8>::new(100, 3);
+        assert_eq!(b * Ratio::new(3, 100), 1);
+        assert_eq!(b.checked_add(b), None);
+        assert_eq!(Ratio::<i8>::new(-128, 2), -64);
+        assert!(Ratio::<i8>::new(127, 126) < Ratio::new(126, 125));
+        assert!(Ratio::<i8>::new(1, 2) < 300u16);
+        assert!(fr!(i128::MAX, i128::MAX - 1) < fr!(i128::MAX - 1, i128::MAX - 2));
+    }
+
+    #[test]
+    fn min_values() {
+        assert_eq!(Ratio::<i8>::new(0, -128), Ratio::new(0, 1));
+        assert_eq!(Ratio::<i8>::new(-128, -128), Ratio::new(1, 1));
+        assert_eq!(Ratio::<i8>::new(-128, -128).denom(), 1);
+        assert_eq!(Ratio::<i8>::new(-64, -128), Ratio::new(1, 2));
+        assert_eq!(Ratio::<i128>::new(0, i128::MIN), 0);
+        assert_eq!(Ratio::<i128>::new(i128::MIN, i128::MIN), 1);
+    }
+
+    #[test]
+    fn checked_div() {
+        assert_eq!(fr!(1, 2).checked_div(fr!(-3, 4)), Some(fr!(-2, 3)));
+        assert_eq!(fr!(1, 2).checked_div(fr!(0)), None);
+        assert_eq!(Ratio::<i8>::new(-128, 1).checked_div(Ratio::new(-1, 1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn division_by_zero() {
+        let _ = fr!(1, 2) / 0;
+    }
+
+    #[test]
+    #[should_panic(expected = "fraction overflow")]
+    fn width_overflow() {
+        let _ = Ratio::<i16>::new(i16::MAX, 1) + 1;
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn parse() {
+        let values = [fr!(3, 4), fr!(-7, 2), fr!(7, 3), fr!(0), fr!(-12), fr!(21231, 421)];
+        for value in values {
+            assert_eq!(format!("{value}").parse::<Fraction>(), Ok(value));
+            assert_eq!(value.as_mixed().parse::<Fraction>(), Ok(value));
+        }
+        assert_eq!("3/4".parse(), Ok(fr!(3, 4)));
+        assert_eq!(" 6 / -8 ".parse(), Ok(fr!(-3, 4)));
+        assert_eq!("2(1/3)".parse(), Ok(fr!(7, 3)));
+        assert_eq!("-2(1/3)".parse(), Ok(fr!(-7, 3)));
+        assert_eq!("0.125".parse(), Ok(fr!(1, 8)));
+        assert_eq!("-1.5".parse(), Ok(fr!(-3, 2)));
+        assert_eq!(".5".parse(), Ok(fr!(1, 2)));
+        assert_eq!("0.(3)".parse(), Ok(fr!(1, 3)));
+        assert_eq!("0.1(6)".parse(), Ok(fr!(1, 6)));
+        assert_eq!("1.(142857)".parse(), Ok(fr!(8, 7)));
+        assert_eq!("0.(9)".parse(), Ok(fr!(1)));
+        assert_eq!("-0.(142857)".parse::<Ratio<i32>>(), Ok(Ratio::new(-1, 7)));
+
+        let kind = |s: &str| s.parse::<Fraction>().unwrap_err().kind();
+        assert_eq!(kind(""), FractionErrorKind::Empty);
+        assert_eq!(kind("1/"), FractionErrorKind::Empty);
+        assert_eq!(kind("1/0"), FractionErrorKind::ZeroDenominator);
+        assert_eq!(kind("1.2.3"), FractionErrorKind::InvalidDigit);
+        assert_eq!(kind("a/2"), FractionErrorKind::InvalidDigit);
+        assert_eq!(kind("2(-1/3)"), FractionErrorKind::InvalidDigit);
+        assert_eq!(kind("1/2/3"), FractionErrorKind::InvalidDigit);
+        assert_eq!(kind("(1/2"), FractionErrorKind::InvalidFormat);
+        assert_eq!(kind("0.1()"), FractionErrorKind::InvalidFormat);
+        assert_eq!(kind("0.1(2"), FractionErrorKind::InvalidFormat);
+        assert_eq!("200".parse::<Ratio<i8>>().unwrap_err().kind(), FractionErrorKind::Overflow);
+        assert_eq!("0.001".parse::<Ratio<i8>>().unwrap_err().kind(), FractionErrorKind::Overflow);
+
+        let kind_i8 = |s: &str| s.parse::<Ratio<i8>>().map_err(|err| err.kind());
+        assert_eq!(kind_i8("-128/-1"), Err(FractionErrorKind::Overflow));
+        assert_eq!(kind_i8("(-128/-1)"), Err(FractionErrorKind::Overflow));
+        assert_eq!(kind_i8("0/-128"), Ok(Ratio::new(0, 1)));
+        assert_eq!(kind_i8("-128/-128"), Ok(Ratio::new(1, 1)));
+        assert_eq!(kind_i8("-128/2"), Ok(Ratio::new(-64, 1)));
+        assert_eq!(kind_i8("1(127/1)"), Err(FractionErrorKind::Overflow));
+        assert_eq!(kind_i8("-1(127/1)"), Ok(Ratio::new(-128, 1)));
+        assert_eq!(Ratio::<i8>::checked_new(-128, -1), None);
+        assert_eq!(Ratio::<i8>::checked_new(1, 0), None);
+        assert_eq!(Ratio::<i8>::checked_new(6, -4), Some(Ratio::new(-3, 2)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn approximations() {
+        use core::f64::consts::{E, PI, SQRT_2};
+
+        assert_eq!(Fraction::from_f64(PI, 1000), Some(fr!(355, 113)));
+        assert_eq!(Fraction::from_f64(PI, 100), Some(fr!(311, 99)));
+        assert_eq!(Fraction::from_f64(PI, 7), Some(fr!(22, 7)));
+        assert_eq!(Fraction::from_f64(E, 1000), Some(fr!(1457, 536)));
+        assert_eq!(Fraction::from_f64(SQRT_2, 100), Some(fr!(140, 99)));
+        assert_eq!(Fraction::from_f64(-SQRT_2, 100), Some(fr!(-140, 99)));
+        assert_eq!(Fraction::from_f64(0.1, 1_000_000), Some(fr!(1, 10)));
+        // Ties go to the convergent, which is the floor here
+        assert_eq!(Fraction::from_f64(-2.5, 1), Some(fr!(-3)));
+        assert_eq!(Fraction::from_f64(0.75, i128::MAX), Some(fr!(3, 4)));
+
+        assert_eq!(Fraction::approximate(PI, 1e-2), Some(fr!(22, 7)));
+        assert_eq!(Fraction::approximate(PI, 1e-3), Some(fr!(201, 64)));
+        assert_eq!(Fraction::approximate(PI, 1e-6), Some(fr!(355, 113)));
+        assert_eq!(Fraction::approximate(E, 1e-4), Some(fr!(193, 71)));
+        assert_eq!(Fraction::approximate(SQRT_2, 1e-5), Some(fr!(577, 408)));
+        assert_eq!(Fraction::approximate(3.7, 0.5), Some(fr!(4)));
+        // Distances are measured in f64, 3/10 rounds to the same float as 0.3
+        assert_eq!(Fraction::approximate(0.3, 0.), Some(fr!(3, 10)));
+
+        for x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e40] {
+            assert_eq!(Fraction::from_f64(x, 10), None);
+            assert_eq!(Fraction::approximate(x, 0.1), None);
+        }
+        assert_eq!(Fraction::approximate(1., -1.), None);
+        assert_eq!(Ratio::<i8>::from_f64(300., 5), None);
+        assert_eq!(Ratio::<i8>::from_f64(PI, 7), Some(Ratio::new(22, 7)));
+        assert_eq!(Ratio::<i8>::from_f64(PI, 127), None);
+        assert_eq!(Ratio::<i16>::from_f64(PI, 127), Some(Ratio::new(355, 113)));
+
+        let pi = Fraction::from_f64(PI, i128::MAX).unwrap();
+        assert_eq!(pi.as_f64(), PI);
+        assert_eq!(pi.continued_fraction()[..8], [3, 7, 15, 1, 292, 1, 1, 1]);
+        assert_eq!(fr!(-7, 3).continued_fraction(), [-3, 1, 2]);
+        assert_eq!(Fraction::from_continued_fraction(&[-3, 1, 2]), Some(fr!(-7, 3)));
+        assert_eq!(Fraction::from_continued_fraction(&pi.continued_fraction()), Some(pi));
+        assert_eq!(Fraction::from_continued_fraction(&[1, 2, 2, 2, 2]), Some(fr!(41, 29)));
+        assert_eq!(Fraction::from_continued_fraction(&[]), None);
+        assert_eq!(Fraction::from_continued_fraction(&[1, 0]), None);
+        assert_eq!(Fraction::from_continued_fraction(&[1, 2, -3]), None);
+        assert_eq!(Ratio::<i8>::from_continued_fraction(&[1, 100, 2]), None);
+        assert_eq!(Ratio::<i8>::from_continued_fraction(&[-128]), Some(Ratio::new(-128, 1)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn decimal() {
+        let expansion = fr!(-7, 6).decimal_expansion();
+        assert_eq!(expansion.integer, -1);
+        assert_eq!(expansion.non_repeating, [1]);
+        assert_eq!(expansion.repeating, [6]);
+        assert!(expansion.negative && !expansion.truncated);
+
+        assert_eq!(format!("{:#}", fr!(1, 6)), "0.1(6)");
+        assert_eq!(format!("{:#}", fr!(1, 7)), "0.(142857)");
+        assert_eq!(format!("{:#}", fr!(1, 8)), "0.125");
+        assert_eq!(format!("{:#}", fr!(-22, 7)), "-3.(142857)");
+        assert_eq!(format!("{:#}", fr!(-1, 2)), "-0.5");
+        assert_eq!(format!("{:#}", fr!(5)), "5");
+        assert_eq!(format!("{:#}", fr!(1, 12)), "0.08(3)");
+        assert_eq!(format!("{:#.3}", fr!(1, 7)), "0.142...");
+        assert_eq!(format!("{:#.3}", fr!(1, 6)), "0.1(6)");
+        assert_eq!(format!("{:.4}", fr!(1, 7).decimal_expansion()), "0.1428...");
+        assert_eq!(format!("{:#.2}", fr!(1, 1024)), "0.00...");
+        assert_eq!(format!("{:#}", Ratio::<i8>::new(-128, 1)), "-128");
+
+        // Cycle longer than the default cap
+        let long = format!("{:#}", fr!(1, 997));
+        assert!(long.ends_with("...") && long.len() == 2 + 100 + 3);
+        assert_eq!(fr!(1, 997).decimal_expansion().repeating.len(), 166);
+
+        // Remainders past 10 * r overflowing u128
+        let big = fr!(i128::MAX - 1, i128::MAX);
+        assert!(format!("{:#.50}", big).starts_with("0.99999999999999999999999999999999999999"));
+        assert_eq!(format!("{:#.5}", fr!(1, 1i128 << 126)), "0.00000...");
+
+        for value in [fr!(1, 6), fr!(-22, 7), fr!(3, 40), fr!(1, 17), fr!(12)] {
+            assert_eq!(format!("{value:#}").parse::<Fraction>(), Ok(value));
+        }
+    }
+
+    #[test]
+    fn numeric() {
+        assert_eq!(fr!(-3, 4).abs(), fr!(3, 4));
+        assert_eq!(fr!(3, 4).abs(), fr!(3, 4));
+        assert_eq!(-fr!(3, 4), fr!(-3, 4));
+        assert_eq!(fr!(-3, 4).signum(), -1);
+        assert_eq!(fr!(0).signum(), 0);
+        assert_eq!(fr!(-3, 4).recip(), fr!(-4, 3));
+        assert_eq!(fr!(-3, 4).numer(), -3);
+        assert_eq!(fr!(-3, 4).denom(), 4);
+
+        assert_eq!(fr!(2, 3).pow(3), fr!(8, 27));
+        assert_eq!(fr!(-2, 3).pow(-3), fr!(-27, 8));
+        assert_eq!(fr!(5, 7).pow(0), 1);
+
+        assert_eq!(fr!(7, 2) % fr!(1), fr!(1, 2));
+        assert_eq!(fr!(-7, 2) % 2, fr!(-3, 2));
+        assert_eq!(fr!(7, 3) % fr!(1, 2), fr!(1, 3));
+        assert_eq!(5 % fr!(3, 2), fr!(1, 2));
+        let mut x = fr!(17, 4);
+        x %= fr!(3, 2);
+        assert_eq!(x, fr!(5, 4));
+
+        for (value, floor, ceil, round, trunc) in [
+            (fr!(7, 2), 3, 4, 4, 3),
+            (fr!(-7, 2), -4, -3, -4, -3),
+            (fr!(7, 3), 2, 3, 2, 2),
+            (fr!(-7, 3), -3, -2, -2, -2),
+            (fr!(5), 5, 5, 5, 5),
+        ] {
+            assert_eq!(value.floor(), floor, "floor {value}");
+            assert_eq!(value.ceil(), ceil, "ceil {value}");
+            assert_eq!(value.round(), round, "round {value}");
+            assert_eq!(value.trunc(), trunc, "trunc {value}");
+            assert_eq!(value.trunc() + value.fract(), value);
+        }
+        assert_eq!(fr!(-7, 3).fract(), fr!(-1, 3));
+
+        let values = [fr!(1, 2), fr!(1, 3), fr!(1, 6)];
+        assert_eq!(values.iter().sum::<Fraction>(), 1);
+        assert_eq!(values.into_iter().product::<Fraction>(), fr!(1, 36));
+    }
+}
@@ -0,0 +1,701 @@
+//! Shapes built on `Vec3`: rays, segments, planes, spheres, boxes and triangles.
+//!
+//! Every shape has `closest_point`, `distance` and `contains` queries and can be moved by a
+//! `Matrix<4,4>` (`transform`, the matrix is treated as affine) or a `Quaternion` (`rotate`,
+//! around the origin). Axis aligned boxes are re-fitted around the moved box, so they grow.
+use crate::matrix::Matrix;
+use crate::quat::{rot_vec_by_quat, Quaternion};
+use crate::vec::{Vec3, Vec4};
+use crate::vec3;
+
+/// How far a point may be off a ray, segment, plane or triangle and still be `contains`-ed by it
+pub const EPSILON: f32 = 1e-5;
+
+fn transform_point(mat: &Matrix<4, 4>, point: Vec3) -> Vec3 {
+    (*mat * point.to_vec4(1.)).to_vec3()
+}
+fn transform_vector(mat: &Matrix<4, 4>, vector: Vec3) -> Vec3 {
+    (*mat * Vec4::new(vector.x, vector.y, vector.z, 0.)).to_vec3()
+}
+/// Any unit vector perpendicular to the unit vector `n`
+pub(crate) fn perpendicular(n: Vec3) -> Vec3 {
+    let other = if n.x.abs() < 0.9 { Vec3::X } else { Vec3::Y };
+    n.cross(other).norm()
+}
+
+// ------------------- Ray -----------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ray {
+    pub origin: Vec3,
+    /// Always normalized
+    pub dir: Vec3,
+}
+impl Ray {
+    pub fn new(origin: Vec3, dir: Vec3) -> Self {
+        Ray { origin, dir: dir.norm() }
+    }
+    pub fn from_points(from: Vec3, towards: Vec3) -> Self {
+        Ray::new(from, towards - from)
+    }
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.dir * t
+    }
+    /// The distance along the ray to its point closest to `point`, never negative
+    pub fn closest_t(&self, point: Vec3) -> f32 {
+        (point - self.origin).dot(self.dir).max(0.)
+    }
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        self.at(self.closest_t(point))
+    }
+    pub fn distance(&self, point: Vec3) -> f32 {
+        (point - self.closest_point(point)).mag()
+    }
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.distance(point) <= EPSILON
+    }
+    pub fn transform(&self, mat: &Matrix<4, 4>) -> Self {
+        Ray::new(transform_point(mat, self.origin), transform_vector(mat, self.dir))
+    }
+    pub fn rotate(&self, quat: &Quaternion) -> Self {
+        Ray::new(rot_vec_by_quat(self.origin, quat), rot_vec_by_quat(self.dir, quat))
+    }
+}
+
+// ------------------- Segment -----------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Segment {
+    pub start: Vec3,
+    pub end: Vec3,
+}
+impl Segment {
+    pub fn new(start: Vec3, end: Vec3) -> Self {
+        Segment { start, end }
+    }
+    /// `start` at 0, `end` at 1
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.start + (self.end - self.start) * t
+    }
+    pub fn length(&self) -> f32 {
+        (self.end - self.start).mag()
+    }
+    pub fn midpoint(&self) -> Vec3 {
+        self.at(0.5)
+    }
+    /// The `t` of the closest point, clamped to `[0, 1]`
+    pub fn closest_t(&self, point: Vec3) -> f32 {
+        let dir = self.end - self.start;
+        let len_sq = dir.dot(dir);
+        if len_sq == 0. {
+            return 0.;
+        }
+        ((point - self.start).dot(dir) / len_sq).clamp(0., 1.)
+    }
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        self.at(self.closest_t(point))
+    }
+    /// The closest pair of points, the first on `self`, the second on `other`
+    pub fn closest_points(&self, other: &Segment) -> (Vec3, Vec3) {
+        let d1 = self.end - self.start;
+        let d2 = other.end - other.start;
+        let r = self.start - other.start;
+        let (a, e, f) = (d1.dot(d1), d2.dot(d2), d2.dot(r));
+
+        if a == 0. {
+            return (self.start, other.closest_point(self.start));
+        }
+        let c = d1.dot(r);
+        if e == 0. {
+            return (self.closest_point(other.start), other.start);
+        }
+        let b = d1.dot(d2);
+        let denom = a * e - b * b;
+        // parallel segments have a line of closest pairs, any start works
+        let mut s = if denom != 0. { ((b * f - c * e) / denom).clamp(0., 1.) } else { 0. };
+        let mut t = (b * s + f) / e;
+        if t < 0. {
+            t = 0.;
+            s = (-c / a).clamp(0., 1.);
+        } else if t > 1. {
+            t = 1.;
+            s = ((b - c) / a).clamp(0., 1.);
+        }
+        (self.at(s), other.at(t))
+    }
+    pub fn distance(&self, point: Vec3) -> f32 {
+        (point - self.closest_point(point)).mag()
+    }
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.distance(point) <= EPSILON
+    }
+    pub fn aabb(&self) -> Aabb {
+        Aabb::new(self.start, self.end)
+    }
+    pub fn transform(&self, mat: &Matrix<4, 4>) -> Self {
+        Segment::new(transform_point(mat, self.start), transform_point(mat, self.end))
+    }
+    pub fn rotate(&self, quat: &Quaternion) -> Self {
+        Segment::new(rot_vec_by_quat(self.start, quat), rot_vec_by_quat(self.end, quat))
+    }
+}
+
+// ------------------- Plane -----------------------------
+
+/// The points `p` with `normal.dot(p) == d`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plane {
+    /// Always normalized
+    pub normal: Vec3,
+    pub d: f32,
+}
+impl Plane {
+    /// Normalizes `normal` and scales `d` with it, a zero `normal` gives a NaN plane that contains nothing
+    pub fn new(normal: Vec3, d: f32) -> Self {
+        let len = normal.mag();
+        Plane { normal: normal / len, d: d / len }
+    }
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.norm();
+        Plane { normal, d: normal.dot(point) }
+    }
+    /// The normal follows the right hand rule, counter clockwise points face it
+    pub fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Self {
+        Plane::from_point_normal(a, (b - a).cross(c - a))
+    }
+    /// The plane's point closest to the origin
+    pub fn origin(&self) -> Vec3 {
+        self.normal * self.d
+    }
+    /// Positive on the side the normal points to
+    pub fn signed_distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) - self.d
+    }
+    pub fn distance(&self, point: Vec3) -> f32 {
+        self.signed_distance(point).abs()
+    }
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        point - self.normal * self.signed_distance(point)
+    }
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.distance(point) <= EPSILON
+    }
+    /// Moves three of its points, so non uniform scaling tilts the normal correctly
+    pub fn transform(&self, mat: &Matrix<4, 4>) -> Self {
+        let origin = self.origin();
+        let u = perpendicular(self.normal);
+        let v = self.normal.cross(u);
+        Plane::from_points(
+            transform_point(mat, origin),
+            transform_point(mat, origin + u),
+            transform_point(mat, origin + v),
+        )
+    }
+    pub fn rotate(&self, quat: &Quaternion) -> Self {
+        Plane { normal: rot_vec_by_quat(self.normal, quat), d: self.d }
+    }
+}
+
+// ------------------- Sphere -----------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+impl Sphere {
+    pub fn new(center: Vec3, radius: f32) -> Self {
+        Sphere { center, radius }
+    }
+    /// Negative inside
+    pub fn signed_distance(&self, point: Vec3) -> f32 {
+        (point - self.center).mag() - self.radius
+    }
+    /// 0 inside
+    pub fn distance(&self, point: Vec3) -> f32 {
+        self.signed_distance(point).max(0.)
+    }
+    /// `point` itself when it's inside
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        let offset = point - self.center;
+        let dist = offset.mag();
+        if dist <= self.radius {
+            point
+        } else {
+            self.center + offset * (self.radius / dist)
+        }
+    }
+    pub fn contains(&self, point: Vec3) -> bool {
+        let offset = point - self.center;
+        offset.dot(offset) <= self.radius * self.radius
+    }
+    pub fn aabb(&self) -> Aabb {
+        Aabb::from_center_half_extents(self.center, vec3!(self.radius))
+    }
+    /// The radius grows by the largest axis scale, so the result bounds the transformed sphere
+    pub fn transform(&self, mat: &Matrix<4, 4>) -> Self {
+        let scale = [Vec3::X, Vec3::Y, Vec3::Z]
+            .map(|axis| transform_vector(mat, axis).mag())
+            .into_iter()
+            .fold(0., f32::max);
+        Sphere::new(transform_point(mat, self.center), self.radius * scale)
+    }
+    pub fn rotate(&self, quat: &Quaternion) -> Self {
+        Sphere::new(rot_vec_by_quat(self.center, quat), self.radius)
+    }
+}
+
+// ------------------- Aabb -----------------------------
+
+/// Axis aligned box
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+impl Aabb {
+    /// Any two opposite corners
+    pub fn new(a: Vec3, b: Vec3) -> Self {
+        Aabb { min: a.min(b), max: a.max(b) }
+    }
+    pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+        Aabb::new(center - half_extents, center + half_extents)
+    }
+    /// The smallest box around `points`, `None` if there are none
+    pub fn from_points(points: &[Vec3]) -> Option<Self> {
+        let (first, rest) = points.split_first()?;
+        Some(rest.iter().fold(Aabb::new(*first, *first), |aabb, point| aabb.expand(*point)))
+    }
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+    pub fn size(&self) -> Vec3 {
+        self.max - self.min
+    }
+    /// Grown to also hold `point`
+    pub fn expand(&self, point: Vec3) -> Self {
+        Aabb { min: self.min.min(point), max: self.max.max(point) }
+    }
+    /// The smallest box around both
+    pub fn union(&self, other: &Aabb) -> Self {
+        Aabb { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (lo, hi) = (self.min, self.max);
+        [
+            vec3!(lo.x, lo.y, lo.z),
+            vec3!(hi.x, lo.y, lo.z),
+            vec3!(lo.x, hi.y, lo.z),
+            vec3!(hi.x, hi.y, lo.z),
+            vec3!(lo.x, lo.y, hi.z),
+            vec3!(hi.x, lo.y, hi.z),
+            vec3!(lo.x, hi.y, hi.z),
+            vec3!(hi.x, hi.y, hi.z),
+        ]
+    }
+    /// `point` itself when it's inside
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        point.max(self.min).min(self.max)
+    }
+    /// 0 inside
+    pub fn distance(&self, point: Vec3) -> f32 {
+        (point - self.closest_point(point)).mag()
+    }
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.min.x <= point.x && point.x <= self.max.x
+            && self.min.y <= point.y && point.y <= self.max.y
+            && self.min.z <= point.z && point.z <= self.max.z
+    }
+    /// The box around the transformed box, each output axis takes the extreme of every input axis' contribution
+    pub fn transform(&self, mat: &Matrix<4, 4>) -> Self {
+        let (min, max) = (self.min.to_array(), self.max.to_array());
+        let mut out_min = [mat[0][3], mat[1][3], mat[2][3]];
+        let mut out_max = out_min;
+        for row in 0..3 {
+            for col in 0..3 {
+                let a = mat[row][col] * min[col];
+                let b = mat[row][col] * max[col];
+                out_min[row] += a.min(b);
+                out_max[row] += a.max(b);
+            }
+        }
+        Aabb { min: Vec3::from_slice(out_min), max: Vec3::from_slice(out_max) }
+    }
+    /// The box around the rotated box
+    pub fn rotate(&self, quat: &Quaternion) -> Self {
+        Obb::from_aabb(self).rotate(quat).aabb()
+    }
+}
+
+// ------------------- Obb -----------------------------
+
+/// Oriented box, `axes` are orthonormal and `half_extents` measured along them
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Obb {
+    pub center: Vec3,
+    pub half_extents: Vec3,
+    pub axes: [Vec3; 3],
+}
+impl Obb {
+    /// A box with its local x, y and z axes rotated by `rotation`
+    pub fn new(center: Vec3, half_extents: Vec3, rotation: &Quaternion) -> Self {
+        Obb {
+            center,
+            half_extents,
+            axes: [Vec3::X, Vec3::Y, Vec3::Z].map(|axis| rot_vec_by_quat(axis, rotation)),
+        }
+    }
+    pub fn from_aabb(aabb: &Aabb) -> Self {
+        Obb { center: aabb.center(), half_extents: aabb.half_extents(), axes: [Vec3::X, Vec3::Y, Vec3::Z] }
+    }
+    /// `point` in the box's frame, relative to its center
+    pub fn local_point(&self, point: Vec3) -> Vec3 {
+        let offset = point - self.center;
+        Vec3::from_slice(self.axes.map(|axis| offset.dot(axis)))
+    }
+    pub fn world_point(&self, local: Vec3) -> Vec3 {
+        self.center + self.axes[0] * local.x + self.axes[1] * local.y + self.axes[2] * local.z
+    }
+    pub fn corners(&self) -> [Vec3; 8] {
+        let h = self.half_extents;
+        Aabb::new(h * -1., h).corners().map(|corner| self.world_point(corner))
+    }
+    /// `point` itself when it's inside
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        let h = self.half_extents;
+        self.world_point(self.local_point(point).max(h * -1.).min(h))
+    }
+    /// 0 inside
+    pub fn distance(&self, point: Vec3) -> f32 {
+        (point - self.closest_point(point)).mag()
+    }
+    pub fn contains(&self, point: Vec3) -> bool {
+        let local = self.local_point(point);
+        local.x.abs() <= self.half_extents.x
+            && local.y.abs() <= self.half_extents.y
+            && local.z.abs() <= self.half_extents.z
+    }
+    pub fn aabb(&self) -> Aabb {
+        let h = self.half_extents.to_array();
+        let reach = |component: fn(&Vec3) -> f32| (0..3).map(|i| component(&self.axes[i]).abs() * h[i]).sum::<f32>();
+        let extents = vec3!(reach(|v| v.x), reach(|v| v.y), reach(|v| v.z));
+        Aabb::from_center_half_extents(self.center, extents)
+    }
+    /// Rotation, translation and scaling along the box's own axes keep it a box. Any other
+    /// scaling shears it, then it's re-fitted: the first axis keeps its direction, the others
+    /// are made orthogonal to it and the half extents grow to hold the sheared corners.
+    pub fn transform(&self, mat: &Matrix<4, 4>) -> Self {
+        // the box's edges from its center, the sheared box is spanned by them
+        let edges: [Vec3; 3] = core::array::from_fn(|i| transform_vector(mat, self.axes[i]) * self.half_extents.to_array()[i]);
+        let x = edges[0].norm();
+        let y = (edges[1] - x * x.dot(edges[1])).norm();
+        let z = x.cross(y);
+        let z = if z.dot(edges[2]) < 0. { z * -1. } else { z };
+        let axes = [x, y, z];
+        let reach = |axis: Vec3| edges.iter().map(|edge| edge.dot(axis).abs()).sum::<f32>();
+        Obb {
+            center: transform_point(mat, self.center),
+            half_extents: vec3!(reach(x), reach(y), reach(z)),
+            axes,
+        }
+    }
+    pub fn rotate(&self, quat: &Quaternion) -> Self {
+        Obb {
+            center: rot_vec_by_quat(self.center, quat),
+            half_extents: self.half_extents,
+            axes: self.axes.map(|axis| rot_vec_by_quat(axis, quat)),
+        }
+    }
+}
+
+// ------------------- Triangle -----------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Triangle {
+    pub a: Vec3,
+    pub b: Vec3,
+    pub c: Vec3,
+}
+impl Triangle {
+    pub fn new(a: Vec3, b: Vec3, c: Vec3) -> Self {
+        Triangle { a, b, c }
+    }
+    /// Unit normal, facing the side `a`, `b`, `c` look counter clockwise from. NaN for a flat triangle.
+    pub fn normal(&self) -> Vec3 {
+        (self.b - self.a).cross(self.c - self.a).norm()
+    }
+    pub fn area(&self) -> f32 {
+        (self.b - self.a).cross(self.c - self.a).mag() * 0.5
+    }
+    pub fn centroid(&self) -> Vec3 {
+        (self.a + self.b + self.c) / 3.
+    }
+    pub fn plane(&self) -> Plane {
+        Plane::from_points(self.a, self.b, self.c)
+    }
+    /// `(u, v, w)` with `point == a*u + b*v + c*w` once `point` is projected onto the triangle's plane
+    pub fn barycentric(&self, point: Vec3) -> Vec3 {
+        let (ab, ac, ap) = (self.b - self.a, self.c - self.a, point - self.a);
+        let (d00, d01, d11) = (ab.dot(ab), ab.dot(ac), ac.dot(ac));
+        let (d20, d21) = (ap.dot(ab), ap.dot(ac));
+        let denom = d00 * d11 - d01 * d01;
+        let v = (d11 * d20 - d01 * d21) / denom;
+        let w = (d00 * d21 - d01 * d20) / denom;
+        vec3!(1. - v - w, v, w)
+    }
+    /// Checks which vertex, edge or the face `point` projects onto
+    pub fn closest_point(&self, point: Vec3) -> Vec3 {
+        let (a, b, c) = (self.a, self.b, self.c);
+        let (ab, ac, ap) = (b - a, c - a, point - a);
+        let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+        if d1 <= 0. && d2 <= 0. {
+            return a;
+        }
+        let bp = point - b;
+        let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+        if d3 >= 0. && d4 <= d3 {
+            return b;
+        }
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0. && d1 >= 0. && d3 <= 0. {
+            return a + ab * (d1 / (d1 - d3));
+        }
+        let cp = point - c;
+        let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+        if d6 >= 0. && d5 <= d6 {
+            return c;
+        }
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0. && d2 >= 0. && d6 <= 0. {
+            return a + ac * (d2 / (d2 - d6));
+        }
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0. && d4 - d3 >= 0. && d5 - d6 >= 0. {
+            return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+        }
+        let denom = 1. / (va + vb + vc);
+        a + ab * (vb * denom) + ac * (vc * denom)
+    }
+    pub fn distance(&self, point: Vec3) -> f32 {
+        (point - self.closest_point(point)).mag()
+    }
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.distance(point) <= EPSILON
+    }
+    pub fn aabb(&self) -> Aabb {
+        Aabb::new(self.a, self.b).expand(self.c)
+    }
+    pub fn transform(&self, mat: &Matrix<4, 4>) -> Self {
+        Triangle::new(transform_point(mat, self.a), transform_point(mat, self.b), transform_point(mat, self.c))
+    }
+    pub fn rotate(&self, quat: &Quaternion) -> Self {
+        Triangle::new(rot_vec_by_quat(self.a, quat), rot_vec_by_quat(self.b, quat), rot_vec_by_quat(self.c, quat))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+    use crate::{assert_approx_eq, new_mat};
+    use core::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_2, FRAC_PI_4};
+
+    #[test]
+    fn queries() {
+        let ray = Ray::new(Vec3::ZERO, vec3!(2., 0., 0.));
+        assert_eq!(ray.dir, Vec3::X);
+        assert_eq!(ray.closest_point(vec3!(3., 1., 0.)), vec3!(3., 0., 0.));
+        assert_eq!(ray.closest_point(vec3!(-3., 1., 0.)), Vec3::ZERO);
+        assert!(ray.contains(vec3!(5., 0., 0.)) && !ray.contains(vec3!(-5., 0., 0.)));
+
+        let seg = Segment::new(Vec3::ZERO, vec3!(0., 2., 0.));
+        assert_eq!(seg.distance(vec3!(1., 3., 0.)), 2f32.sqrt());
+        let other = Segment::new(vec3!(-1., 1., 1.), vec3!(1., 1., 1.));
+        assert_eq!(seg.closest_points(&other), (vec3!(0., 1., 0.), vec3!(0., 1., 1.)));
+
+        let plane = Plane::from_points(vec3!(0., 0., 1.), vec3!(1., 0., 1.), vec3!(0., 1., 1.));
+        assert_eq!((plane.normal, plane.d), (Vec3::Z, 1.));
+        assert_eq!(plane.signed_distance(vec3!(4., 4., -1.)), -2.);
+        assert_eq!(plane.closest_point(vec3!(4., 4., -1.)), vec3!(4., 4., 1.));
+        assert_eq!(Plane::new(vec3!(0., 0., 2.), 2.), plane);
+
+        let sphere = Sphere::new(vec3!(1., 0., 0.), 2.);
+        assert_eq!(sphere.closest_point(vec3!(5., 0., 0.)), vec3!(3., 0., 0.));
+        assert_eq!(sphere.distance(vec3!(1., 0., 0.5)), 0.);
+        assert!(sphere.contains(vec3!(-1., 0., 0.)) && !sphere.contains(vec3!(-1.1, 0., 0.)));
+
+        let aabb = Aabb::new(vec3!(1., 1., 1.), vec3!(-1., 0., -1.));
+        assert_eq!(aabb.min, vec3!(-1., 0., -1.));
+        assert_eq!(aabb.closest_point(vec3!(3., 0.5, -4.)), vec3!(1., 0.5, -1.));
+        assert_eq!(aabb.distance(vec3!(0., 4., 0.)), 3.);
+        assert_eq!(Aabb::from_points(&aabb.corners()), Some(aabb));
+        assert_eq!(Aabb::from_points(&[]), None);
+
+        let obb = Obb::new(Vec3::ZERO, vec3!(2., 1., 1.), &Quaternion::from_axis_angle(Vec3::Z, FRAC_PI_2));
+        assert!(obb.contains(vec3!(0.5, 1.5, 0.)) && !obb.contains(vec3!(1.5, 0.5, 0.)));
+        assert_approx_eq!(obb.closest_point(vec3!(0., 5., 0.)), vec3!(0., 2., 0.), epsilon = 1e-6);
+        assert_approx_eq!(obb.aabb().max, vec3!(1., 2., 1.), epsilon = 1e-6);
+
+        let tri = Triangle::new(Vec3::ZERO, Vec3::X, Vec3::Y);
+        assert_eq!(tri.normal(), Vec3::Z);
+        assert_eq!(tri.area(), 0.5);
+        assert_eq!(tri.barycentric(vec3!(0.25, 0.5, 3.)), vec3!(0.25, 0.25, 0.5));
+        assert_eq!(tri.closest_point(vec3!(0.25, 0.25, 3.)), vec3!(0.25, 0.25, 0.));
+        assert_eq!(tri.closest_point(vec3!(-1., -1., 0.)), Vec3::ZERO);
+        assert_eq!(tri.closest_point(vec3!(1., 1., 0.)), vec3!(0.5, 0.5, 0.));
+        assert_eq!(tri.closest_point(vec3!(0.5, -1., 0.)), vec3!(0.5, 0., 0.));
+        assert!(tri.contains(vec3!(0.2, 0.2, 0.)) && !tri.contains(vec3!(0.6, 0.6, 0.)));
+    }
+
+    #[test]
+    fn edge_cases() {
+        // rays start at their origin and a zero direction has none
+        let ray = Ray::from_points(vec3!(1., 1., 0.), vec3!(1., 3., 0.));
+        assert_eq!(ray.dir, Vec3::Y);
+        assert!(ray.contains(ray.origin));
+        assert_eq!(ray.closest_t(vec3!(5., -2., 0.)), 0.);
+        assert_eq!(ray.distance(vec3!(1., -2., 0.)), 3.);
+        assert!(Ray::new(Vec3::ZERO, Vec3::ZERO).dir.x.is_nan());
+
+        // ends clamp, a zero length segment is its start
+        let seg = Segment::new(Vec3::ZERO, vec3!(2., 0., 0.));
+        assert_eq!(seg.closest_point(vec3!(-3., 1., 0.)), Vec3::ZERO);
+        assert_eq!(seg.closest_point(vec3!(5., 0., 0.)), vec3!(2., 0., 0.));
+        assert!(seg.contains(seg.start) && seg.contains(seg.end) && !seg.contains(vec3!(2.1, 0., 0.)));
+        let point = Segment::new(Vec3::X, Vec3::X);
+        assert_eq!((point.closest_t(vec3!(4., 4., 4.)), point.length()), (0., 0.));
+        assert_eq!(point.closest_points(&seg), (Vec3::X, Vec3::X));
+        assert_eq!(seg.closest_points(&Segment::new(vec3!(1., 1., 0.), vec3!(1., 1., 0.))), (Vec3::X, vec3!(1., 1., 0.)));
+
+        // parallel segments give one of their closest pairs, collinear ones their nearest ends
+        let above = Segment::new(vec3!(1., 1., 0.), vec3!(3., 1., 0.));
+        assert_eq!(seg.closest_points(&above), (Vec3::X, vec3!(1., 1., 0.)));
+        let ahead = Segment::new(vec3!(5., 0., 0.), vec3!(3., 0., 0.));
+        assert_eq!(seg.closest_points(&ahead), (vec3!(2., 0., 0.), vec3!(3., 0., 0.)));
+        let touching = Segment::new(vec3!(2., 0., 0.), vec3!(2., 3., 0.));
+        assert_eq!(seg.closest_points(&touching), (vec3!(2., 0., 0.), vec3!(2., 0., 0.)));
+    }
+
+    #[test]
+    fn degenerate() {
+        // a flat triangle has no normal or area, but its closest points are still on its edges
+        let flat = Triangle::new(Vec3::ZERO, Vec3::X, vec3!(2., 0., 0.));
+        assert_eq!(flat.area(), 0.);
+        assert!(flat.normal().x.is_nan() && flat.plane().normal.x.is_nan());
+        assert_eq!(flat.closest_point(vec3!(1., 1., 0.)), Vec3::X);
+        assert_eq!(flat.closest_point(vec3!(1.5, 1., 0.)), vec3!(1.5, 0., 0.));
+        assert_eq!(flat.closest_point(vec3!(3., 0., 1.)), vec3!(2., 0., 0.));
+        assert!(flat.contains(vec3!(0.5, 0., 0.)) && !flat.contains(vec3!(0.5, 0.1, 0.)));
+        let dot = Triangle::new(Vec3::Y, Vec3::Y, Vec3::Y);
+        assert_eq!(dot.closest_point(vec3!(4., 4., 4.)), Vec3::Y);
+
+        // a plane without a normal is NaN and contains nothing
+        let plane = Plane::new(Vec3::ZERO, 1.);
+        assert!(plane.normal.x.is_nan() && !plane.contains(Vec3::ZERO));
+        assert!(Plane::from_point_normal(Vec3::X, Vec3::ZERO).signed_distance(Vec3::X).is_nan());
+        assert!(Plane::from_points(Vec3::X, Vec3::X, Vec3::Y).d.is_nan());
+    }
+
+    #[test]
+    fn transforms() {
+        let mat = new_mat!(4, 4, [
+            2., 0., 0., 1.,
+            0., 1., 0., 0.,
+            0., 0., 1., -3.,
+            0., 0., 0., 1.,
+        ]);
+        let ray = Ray::new(Vec3::ZERO, vec3!(1., 1., 0.)).transform(&mat);
+        assert_eq!(ray.origin, vec3!(1., 0., -3.));
+        assert_approx_eq!(ray.dir, vec3!(2., 1., 0.).norm());
+
+        let sphere = Sphere::new(Vec3::Y, 1.).transform(&mat);
+        assert_eq!(sphere, Sphere::new(vec3!(1., 1., -3.), 2.));
+
+        let aabb = Aabb::new(vec3!(-1., -1., -1.), vec3!(1., 1., 1.));
+        assert_eq!(aabb.transform(&mat), Aabb::new(vec3!(-1., -1., -4.), vec3!(3., 1., -2.)));
+
+        // a rotated cube's bounds grow to fit its corners
+        let q = Quaternion::from_axis_angle(Vec3::Z, FRAC_PI_4);
+        let refit = aabb.rotate(&q);
+        assert_approx_eq!(refit.max, vec3!(2. * FRAC_1_SQRT_2, 2. * FRAC_1_SQRT_2, 1.), epsilon = 1e-6);
+        let corners = aabb.corners().map(|corner| rot_vec_by_quat(corner, &q));
+        assert_approx_eq!(Aabb::from_points(&corners).unwrap().min, refit.min, epsilon = 1e-6);
+
+        // a tilted plane stays perpendicular to the tilted surface
+        let plane = Plane::from_point_normal(Vec3::ZERO, vec3!(1., 1., 0.)).transform(&mat);
+        assert_approx_eq!(plane.normal, vec3!(1., 2., 0.).norm(), epsilon = 1e-6);
+        assert!(plane.contains(vec3!(1., 0., 0.)));
+        let plane = Plane::new(Vec3::X, 2.).rotate(&Quaternion::from_axis_angle(Vec3::Z, FRAC_PI_2));
+        assert_approx_eq!(plane.origin(), vec3!(0., 2., 0.), epsilon = 1e-6);
+
+        let obb = Obb::from_aabb(&aabb).transform(&mat);
+        assert_eq!(obb.half_extents, vec3!(2., 1., 1.));
+        assert_eq!(obb.aabb(), aabb.transform(&mat));
+
+        let tri = Triangle::new(Vec3::ZERO, Vec3::X, Vec3::Y).rotate(&Quaternion::from_axis_angle(Vec3::Z, FRAC_PI_2));
+        assert_approx_eq!(tri.b, Vec3::Y, epsilon = 1e-6);
+        assert_approx_eq!(tri.normal(), Vec3::Z, epsilon = 1e-6);
+        assert_eq!(Segment::new(Vec3::ZERO, Vec3::X).transform(&mat).length(), 2.);
+    }
+
+    #[test]
+    fn non_uniform_scale() {
+        let scale = new_mat!(4, 4, [
+            3., 0., 0., 0.,
+            0., 1., 0., 0.,
+            0., 0., 0.5, 2.,
+            0., 0., 0., 1.,
+        ]);
+        // turned a quarter, the box's local x is scaled by the matrix' y and its local y by x
+        let obb = Obb::new(Vec3::X, vec3!(2., 1., 1.), &Quaternion::from_axis_angle(Vec3::Z, FRAC_PI_2)).transform(&scale);
+        assert_approx_eq!(obb.center, vec3!(3., 0., 2.), epsilon = 1e-6);
+        assert_approx_eq!(obb.half_extents, vec3!(2., 3., 0.5), epsilon = 1e-6);
+        assert_approx_eq!(obb.axes[1], Vec3::NEG_X, epsilon = 1e-6);
+        assert_approx_eq!(obb.aabb().min, vec3!(0., -2., 1.5), epsilon = 1e-6);
+        assert!(obb.contains(vec3!(5.9, 1.9, 2.4)) && !obb.contains(vec3!(6.1, 0., 2.)));
+        // the moved corners are the corners of the moved box
+        let before = Obb::new(Vec3::X, vec3!(2., 1., 1.), &Quaternion::from_axis_angle(Vec3::Z, FRAC_PI_2));
+        for corner in before.corners().map(|corner| transform_point(&scale, corner)) {
+            assert!(obb.corners().iter().any(|c| (*c - corner).mag() < 1e-5), "{corner:?}");
+        }
+
+
+        // across its axes the scale shears the box, the re-fitted one is still a box around it
+        let turned = Obb::new(Vec3::ZERO, vec3!(1.), &Quaternion::from_axis_angle(Vec3::Z, FRAC_PI_4));
+        let sheared = turned.transform(&scale);
+        for (i, j) in [(0, 1), (1, 2), (0, 2)] {
+            assert!(sheared.axes[i].dot(sheared.axes[j]).abs() < 1e-6);
+        }
+        assert_approx_eq!(sheared.axes[0], vec3!(3., 1., 0.).norm(), epsilon = 1e-6);
+        assert_approx_eq!(sheared.half_extents, vec3!(9. / 5f32.sqrt(), 1.8f32.sqrt(), 0.5), epsilon = 1e-5);
+        for corner in turned.corners().map(|corner| transform_point(&scale, corner)) {
+            assert!(sheared.distance(corner) < 1e-5, "{corner:?}");
+        }
+        // twice as wide along world x, where contains and distance used to disagree
+        let wide = turned.transform(&new_mat!(4, 4, [
+            2., 0., 0., 0.,
+            0., 1., 0., 0.,
+            0., 0., 1., 0.,
+            0., 0., 0., 1.,
+        ]));
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..1000 {
+            let p = rng.vec3(-3., 3.);
+            let distance = wide.distance(p);
+            assert!(if wide.contains(p) { distance < 1e-5 } else { distance > 0. }, "{p:?} {distance}");
+        }
+        for corner in turned.corners().map(|corner| vec3!(corner.x * 2., corner.y, corner.z)) {
+            assert!(wide.distance(corner) < 1e-5, "{corner:?}");
+        }
+    }
+}
@@ -0,0 +1,670 @@
+//! Polygons and point sets in the plane, on `Vec2` or `IVec2`.
+//!
+//! Everything is generic over `Point2`. `Vec2` works in `f32` and is as good as its rounding,
+//! `IVec2` widens to `i128` so its orientation tests, and everything decided by them, are exact.
+//! Polygons are slices of vertices with an implicit closing edge, in either winding, and points
+//! on the boundary count as inside.
+use core::cmp::Ordering;
+use core::error::Error;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::ops::{Add, Mul, Sub};
+
+use crate::fraction::Fraction;
+use crate::vec::{IVec2, Vec2};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A 2D point the predicates can work on
+pub trait Point2: Copy + PartialEq {
+    /// What coordinates are widened to before they are combined
+    type Scalar: Copy
+        + PartialOrd
+        + Add<Output = Self::Scalar>
+        + Sub<Output = Self::Scalar>
+        + Mul<Output = Self::Scalar>;
+    /// What `signed_area` returns, `f32` for `Vec2` and an exact `Fraction` for `IVec2`
+    type Area;
+    const ZERO: Self::Scalar;
+
+    fn coords(self) -> [Self::Scalar; 2];
+    /// The area from twice the area
+    fn halve(doubled: Self::Scalar) -> Self::Area;
+    fn to_f64(value: Self::Scalar) -> f64;
+}
+impl Point2 for Vec2 {
+    type Scalar = f32;
+    type Area = f32;
+    const ZERO: f32 = 0.;
+
+    fn coords(self) -> [f32; 2] {
+        [self.x, self.y]
+    }
+    fn halve(doubled: f32) -> f32 {
+        doubled / 2.
+    }
+    fn to_f64(value: f32) -> f64 {
+        value as f64
+    }
+}
+impl Point2 for IVec2 {
+    type Scalar = i128;
+    type Area = Fraction;
+    const ZERO: i128 = 0;
+
+    fn coords(self) -> [i128; 2] {
+        [self.x as i128, self.y as i128]
+    }
+    fn halve(doubled: i128) -> Fraction {
+        Fraction::new(doubled, 2)
+    }
+    fn to_f64(value: i128) -> f64 {
+        value as f64
+    }
+}
+
+fn sign<P: Point2>(value: P::Scalar) -> Ordering {
+    value.partial_cmp(&P::ZERO).unwrap_or(Ordering::Equal)
+}
+/// Orders by x, then y
+fn lexical<P: Point2>(a: &P, b: &P) -> Ordering {
+    let (a, b) = (a.coords(), b.coords());
+    a[0].partial_cmp(&b[0])
+        .unwrap_or(Ordering::Equal)
+        .then(a[1].partial_cmp(&b[1]).unwrap_or(Ordering::Equal))
+}
+fn edges<P: Point2>(polygon: &[P]) -> impl Iterator<Item = (P, P)> + '_ {
+    polygon.iter().zip(polygon.iter().cycle().skip(1)).map(|(a, b)| (*a, *b))
+}
+fn to_vec2<P: Point2>(point: P) -> Vec2 {
+    let [x, y] = point.coords();
+    Vec2::new(P::to_f64(x) as f32, P::to_f64(y) as f32)
+}
+
+/// Twice the signed area of the triangle `abc`, positive when it turns counter clockwise
+pub fn orient<P: Point2>(a: P, b: P, c: P) -> P::Scalar {
+    let [ax, ay] = a.coords();
+    let [bx, by] = b.coords();
+    let [cx, cy] = c.coords();
+    (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
+}
+
+// ------------------- Area and winding -----------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winding {
+    CounterClockwise,
+    Clockwise,
+    /// No area, e.g. fewer than three points or all of them on a line
+    Degenerate,
+}
+
+fn doubled_area<P: Point2>(polygon: &[P]) -> P::Scalar {
+    let Some(&origin) = polygon.first() else {
+        return P::ZERO;
+    };
+    edges(polygon).fold(P::ZERO, |sum, (a, b)| sum + orient(origin, a, b))
+}
+/// Shoelace area, positive for counter clockwise polygons
+pub fn signed_area<P: Point2>(polygon: &[P]) -> P::Area {
+    P::halve(doubled_area(polygon))
+}
+pub fn winding<P: Point2>(polygon: &[P]) -> Winding {
+    match sign::<P>(doubled_area(polygon)) {
+        Ordering::Greater => Winding::CounterClockwise,
+        Ordering::Less => Winding::Clockwise,
+        Ordering::Equal => Winding::Degenerate,
+    }
+}
+
+// ------------------- Point in polygon -----------------------------
+
+/// Whether `p` lies on the segment from `a` to `b`, ends included
+pub fn on_segment<P: Point2>(p: P, a: P, b: P) -> bool {
+    if sign::<P>(orient(a, b, p)) != Ordering::Equal {
+        return false;
+    }
+    let (p, a, b) = (p.coords(), a.coords(), b.coords());
+    (0..2).all(|i| {
+        let (lo, hi) = if a[i] <= b[i] { (a[i], b[i]) } else { (b[i], a[i]) };
+        lo <= p[i] && p[i] <= hi
+    })
+}
+/// How often the edges cross a ray from `p` towards +x, upwards and downwards.
+/// `None` when `p` is on the boundary.
+fn crossings<P: Point2>(polygon: &[P], p: P) -> Option<(i32, i32)> {
+    let py = p.coords()[1];
+    let (mut up, mut down) = (0, 0);
+    for (a, b) in edges(polygon) {
+        if on_segment(p, a, b) {
+            return None;
+        }
+        let (ay, by) = (a.coords()[1], b.coords()[1]);
+        if ay <= py {
+            if by > py && sign::<P>(orient(a, b, p)) == Ordering::Greater {
+                up += 1;
+            }
+        } else if by <= py && sign::<P>(orient(a, b, p)) == Ordering::Less {
+            down += 1;
+        }
+    }
+    Some((up, down))
+}
+/// How many times the polygon winds counter clockwise around `p`, `0` on the boundary
+pub fn winding_number<P: Point2>(polygon: &[P], p: P) -> i32 {
+    crossings(polygon, p).map_or(0, |(up, down)| up - down)
+}
+/// Even-odd rule: inside when a ray from `p` crosses the boundary an odd number of times
+pub fn contains_even_odd<P: Point2>(polygon: &[P], p: P) -> bool {
+    crossings(polygon, p).is_none_or(|(up, down)| (up + down) % 2 == 1)
+}
+/// Nonzero rule: inside when the polygon winds around `p` at all
+pub fn contains_nonzero<P: Point2>(polygon: &[P], p: P) -> bool {
+    crossings(polygon, p).is_none_or(|(up, down)| up != down)
+}
+
+// ------------------- Segments -----------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SegmentIntersection {
+    Point(Vec2),
+    /// The segments are collinear and share this stretch
+    Overlap(Vec2, Vec2),
+}
+
+/// Whether the segments `a0 a1` and `b0 b1` share a point, touching included
+pub fn segments_intersect<P: Point2>(a0: P, a1: P, b0: P, b1: P) -> bool {
+    let d0 = sign::<P>(orient(b0, b1, a0));
+    let d1 = sign::<P>(orient(b0, b1, a1));
+    let d2 = sign::<P>(orient(a0, a1, b0));
+    let d3 = sign::<P>(orient(a0, a1, b1));
+    if d0 != d1 && d2 != d3 && d0 != Ordering::Equal && d1 != Ordering::Equal {
+        return true;
+    }
+    on_segment(a0, b0, b1) || on_segment(a1, b0, b1) || on_segment(b0, a0, a1) || on_segment(b1, a0, a1)
+}
+/// Where the segments `a0 a1` and `b0 b1` meet. Which of them meet is decided exactly for
+/// `IVec2`, the crossing point itself is rounded to a `Vec2`.
+pub fn segment_intersection<P: Point2>(a0: P, a1: P, b0: P, b1: P) -> Option<SegmentIntersection> {
+    if !segments_intersect(a0, a1, b0, b1) {
+        return None;
+    }
+    let d0 = orient(b0, b1, a0);
+    let d1 = orient(b0, b1, a1);
+    let collinear = sign::<P>(d0) == Ordering::Equal && sign::<P>(d1) == Ordering::Equal;
+    if collinear && a0 != a1 && b0 != b1 {
+        let (a_lo, a_hi) = if lexical(&a0, &a1).is_le() { (a0, a1) } else { (a1, a0) };
+        let (b_lo, b_hi) = if lexical(&b0, &b1).is_le() { (b0, b1) } else { (b1, b0) };
+        let lo = if lexical(&a_lo, &b_lo).is_ge() { a_lo } else { b_lo };
+        let hi = if lexical(&a_hi, &b_hi).is_le() { a_hi } else { b_hi };
+        return Some(if lo == hi {
+            SegmentIntersection::Point(to_vec2(lo))
+        } else {
+            SegmentIntersection::Overlap(to_vec2(lo), to_vec2(hi))
+        });
+    }
+    // an end on the other segment is the exact answer
+    for (p, q0, q1) in [(a0, b0, b1), (a1, b0, b1), (b0, a0, a1), (b1, a0, a1)] {
+        if on_segment(p, q0, q1) {
+            return Some(SegmentIntersection::Point(to_vec2(p)));
+        }
+    }
+    let t = P::to_f64(d0) / (P::to_f64(d0) - P::to_f64(d1));
+    let ([x0, y0], [x1, y1]) = (a0.coords(), a1.coords());
+    let (x0, y0, x1, y1) = (P::to_f64(x0), P::to_f64(y0), P::to_f64(x1), P::to_f64(y1));
+    Some(SegmentIntersection::Point(Vec2::new(
+        (x0 + (x1 - x0) * t) as f32,
+        (y0 + (y1 - y0) * t) as f32,
+    )))
+}
+
+// ------------------- Convex hull -----------------------------
+
+/// Andrew's monotone chain. Counter clockwise from the lowest-x point, without collinear points.
+#[cfg(feature = "alloc")]
+pub fn convex_hull<P: Point2>(points: &[P]) -> Vec<P> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(lexical);
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+    let mut hull: Vec<P> = Vec::with_capacity(sorted.len() + 1);
+    // keeps only left turns, never popping below `floor` points
+    let push = |hull: &mut Vec<P>, floor: usize, p: P| {
+        while hull.len() > floor
+            && sign::<P>(orient(hull[hull.len() - 2], hull[hull.len() - 1], p)) != Ordering::Greater
+        {
+            hull.pop();
+        }
+        hull.push(p);
+    };
+    for &p in &sorted {
+        push(&mut hull, 1, p);
+    }
+    let lower = hull.len();
+    for &p in sorted.iter().rev().skip(1) {
+        push(&mut hull, lower, p);
+    }
+    // the upper chain ends back on the first point
+    hull.pop();
+    hull
+}
+
+// ------------------- Triangulation -----------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriangulateError {
+    /// No vertex of the outer ring can see this hole, it's outside the polygon or overlaps it
+    /// or another hole
+    HoleNotBridged(usize),
+}
+impl Display for TriangulateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            TriangulateError::HoleNotBridged(h) => write!(f, "hole {h} can't be joined to the outer polygon"),
+        }
+    }
+}
+impl Error for TriangulateError {}
+
+/// Whether `p` is inside or on the counter clockwise triangle `abc`
+#[cfg(feature = "alloc")]
+fn in_triangle<P: Point2>(a: P, b: P, c: P, p: P) -> bool {
+    [orient(a, b, p), orient(b, c, p), orient(c, a, p)]
+        .into_iter()
+        .all(|o| sign::<P>(o) != Ordering::Less)
+}
+/// Whether a diagonal from ring vertex `at` towards `towards` starts into the polygon,
+/// which lies left of the ring
+#[cfg(feature = "alloc")]
+fn locally_inside<P: Point2>(points: &[P], ring: &[usize], at: usize, towards: P) -> bool {
+    let n = ring.len();
+    let prev = points[ring[(at + n - 1) % n]];
+    let here = points[ring[at]];
+    let next = points[ring[(at + 1) % n]];
+    let left = |o: P::Scalar| sign::<P>(o) == Ordering::Greater;
+    if left(orient(prev, here, next)) {
+        !left(orient(here, towards, next)) && !left(orient(here, prev, towards))
+    } else {
+        left(orient(here, towards, prev)) || left(orient(here, next, towards))
+    }
+}
+/// Whether the bridge `m v` crosses any edge of the rings, other than at its own ends
+#[cfg(feature = "alloc")]
+fn bridge_blocked<P: Point2>(points: &[P], rings: &[&[usize]], m: P, v: P) -> bool {
+    rings.iter().any(|ring| {
+        ring.iter().zip(ring.iter().cycle().skip(1)).any(|(&i, &j)| {
+            let (e0, e1) = (points[i], points[j]);
+            if [e0, e1].iter().any(|&e| e == m || e == v) {
+                // sharing an end is fine unless the edge runs along the bridge
+                [e0, e1].iter().any(|&e| e != m && e != v && on_segment(e, m, v))
+            } else {
+                segments_intersect(m, v, e0, e1)
+            }
+        })
+    })
+}
+/// Splices each hole into the outer ring through a bridge to a vertex it can see. The holes
+/// come with their index in the input, for the error.
+#[cfg(feature = "alloc")]
+fn bridge_holes<P: Point2>(
+    points: &[P],
+    mut ring: Vec<usize>,
+    mut holes: Vec<(usize, Vec<usize>)>,
+) -> Result<Vec<usize>, TriangulateError> {
+    let x = |i: &usize| points[*i].coords()[0];
+    let rightmost = |hole: &Vec<usize>| {
+        (0..hole.len())
+            .max_by(|&a, &b| x(&hole[a]).partial_cmp(&x(&hole[b])).unwrap_or(Ordering::Equal))
+            .unwrap_or(0)
+    };
+    // rightmost holes first, so the ones left of them can bridge over their new edges
+    holes.sort_by(|(_, a), (_, b)| x(&b[rightmost(b)]).partial_cmp(&x(&a[rightmost(a)])).unwrap_or(Ordering::Equal));
+    for h in 0..holes.len() {
+        let (input, hole) = &holes[h];
+        let start = rightmost(hole);
+        let m = points[hole[start]];
+        let distance = |k: &usize| {
+            let ([mx, my], [vx, vy]) = (m.coords(), points[ring[*k]].coords());
+            (vx - mx) * (vx - mx) + (vy - my) * (vy - my)
+        };
+        let mut candidates: Vec<usize> = (0..ring.len()).collect();
+        candidates.sort_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap_or(Ordering::Equal));
+        let mut obstacles: Vec<&[usize]> = holes[h..].iter().map(|(_, hole)| &hole[..]).collect();
+        obstacles.push(&ring);
+        let bridge = candidates.into_iter().find(|&k| {
+            let v = points[ring[k]];
+            locally_inside(points, &ring, k, m)
+                && (v == m || (locally_inside(points, hole, start, v) && !bridge_blocked(points, &obstacles, m, v)))
+        });
+        let k = bridge.ok_or(TriangulateError::HoleNotBridged(*input))?;
+        let mut spliced = Vec::with_capacity(ring.len() + hole.len() + 2);
+        spliced.extend_from_slice(&ring[..=k]);
+        spliced.extend(hole[start..].iter().chain(&hole[..=start]));
+        spliced.push(ring[k]);
+        spliced.extend_from_slice(&ring[k + 1..]);
+        ring = spliced;
+    }
+    Ok(ring)
+}
+/// Ear clipping of a counter clockwise ring of indices into `points`
+#[cfg(feature = "alloc")]
+fn clip_ears<P: Point2>(points: &[P], mut ring: Vec<usize>) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::with_capacity(ring.len().saturating_sub(2));
+    let mut at = 0;
+    let mut misses = 0;
+    while ring.len() > 3 {
+        let n = ring.len();
+        at %= n;
+        let corner = [ring[(at + n - 1) % n], ring[at], ring[(at + 1) % n]];
+        let [a, b, c] = corner.map(|i| points[i]);
+        let convex = sign::<P>(orient(a, b, c)) == Ordering::Greater;
+        let ear = convex
+            && !ring.iter().any(|&i| {
+                let p = points[i];
+                p != a && p != b && p != c && in_triangle(a, b, c, p)
+            });
+        if ear {
+            triangles.push(corner);
+            ring.remove(at);
+            misses = 0;
+            continue;
+        }
+        at += 1;
+        misses += 1;
+        if misses >= n {
+            // no ear left: drop a vertex that adds no area, or give up on a non-simple ring
+            let flat = (0..n).find(|&i| {
+                let [a, b, c] = [ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]].map(|i| points[i]);
+                sign::<P>(orient(a, b, c)) == Ordering::Equal
+            });
+            match flat {
+                Some(i) => {
+                    ring.remove(i);
+                    misses = 0;
+                }
+                None => return triangles,
+            }
+        }
+    }
+    if let [i, j, k] = ring[..] {
+        if sign::<P>(orient(points[i], points[j], points[k])) == Ordering::Greater {
+            triangles.push([i, j, k]);
+        }
+    }
+    triangles
+}
+/// Ear clipping of a simple polygon, counter clockwise triangles of indices into `polygon`
+#[cfg(feature = "alloc")]
+pub fn triangulate<P: Point2>(polygon: &[P]) -> Vec<[usize; 3]> {
+    // without holes there's nothing that could fail to bridge
+    triangulate_with_holes::<P, &[P]>(polygon, &[]).unwrap_or_default()
+}
+/// Ear clipping of a simple polygon with simple holes inside it. The indices count through
+/// `outer` first and then through each hole in turn.
+///
+/// A hole that no outer vertex can see, because it lies outside `outer` or overlaps it or
+/// another hole, is a [`TriangulateError::HoleNotBridged`] with its index in `holes`.
+#[cfg(feature = "alloc")]
+pub fn triangulate_with_holes<P: Point2, H: AsRef<[P]>>(
+    outer: &[P],
+    holes: &[H],
+) -> Result<Vec<[usize; 3]>, TriangulateError> {
+    let mut points = outer.to_vec();
+    let mut outer_ring: Vec<usize> = (0..outer.len()).collect();
+    match winding(outer) {
+        Winding::Degenerate => return Ok(Vec::new()),
+        Winding::Clockwise => outer_ring.reverse(),
+        Winding::CounterClockwise => {}
+    }
+    let mut hole_rings = Vec::with_capacity(holes.len());
+    for (h, hole) in holes.iter().enumerate() {
+        let hole = hole.as_ref();
+        let mut ring: Vec<usize> = (points.len()..points.len() + hole.len()).collect();
+        points.extend_from_slice(hole);
+        match winding(hole) {
+            Winding::Degenerate => continue,
+            Winding::CounterClockwise => ring.reverse(),
+            Winding::Clockwise => {}
+        }
+        hole_rings.push((h, ring));
+    }
+    let merged = bridge_holes(&points, outer_ring, hole_rings)?;
+    Ok(clip_ears(&points, merged))
+}
+
+// ------------------- Offsetting -----------------------------
+
+/// Moves every edge `distance` outwards (inwards when negative) and joins neighbouring
+/// edges where their lines meet. Corners stay sharp and nothing removes the loops that
+/// shrinking past a narrow part makes.
+#[cfg(feature = "alloc")]
+pub fn offset_polygon(polygon: &[Vec2], distance: f32) -> Vec<Vec2> {
+    let mut points = polygon.to_vec();
+    points.dedup();
+    while points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let outwards = match winding(&points) {
+        Winding::CounterClockwise => distance,
+        Winding::Clockwise => -distance,
+        Winding::Degenerate => return points,
+    };
+    let n = points.len();
+    // right hand normal of the edge leaving each vertex
+    let normals: Vec<Vec2> = (0..n)
+        .map(|i| {
+            let d = (points[(i + 1) % n] - points[i]).norm();
+            Vec2::new(d.y, -d.x)
+        })
+        .collect();
+    (0..n)
+        .map(|i| {
+            let (before, after) = (normals[(i + n - 1) % n], normals[i]);
+            let cos = before.dot(after);
+            if cos <= -1. + 1e-6 {
+                // the edges fold back on each other, their lines never meet
+                return points[i] + after * outwards;
+            }
+            points[i] + (before + after) * (outwards / (1. + cos))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ivec2, vec2};
+    #[cfg(feature = "alloc")]
+    use crate::assert_approx_eq;
+    #[cfg(feature = "alloc")]
+    use crate::test_rng::Rng;
+    #[cfg(feature = "alloc")]
+    use alloc::vec;
+
+    #[cfg(feature = "alloc")]
+    fn area_of<P: Point2>(points: &[P], triangles: &[[usize; 3]]) -> f64 {
+        triangles
+            .iter()
+            .map(|&[a, b, c]| P::to_f64(orient(points[a], points[b], points[c])) / 2.)
+            .sum()
+    }
+
+    #[test]
+    fn polygons() {
+        let square = [vec2!(0., 0.), vec2!(2., 0.), vec2!(2., 2.), vec2!(0., 2.)];
+        assert_eq!(signed_area(&square), 4.);
+        assert_eq!(winding(&square), Winding::CounterClockwise);
+        let mut reversed = square;
+        reversed.reverse();
+        assert_eq!(signed_area(&reversed), -4.);
+        assert_eq!(winding(&reversed), Winding::Clockwise);
+        assert_eq!(winding(&[vec2!(0., 0.), vec2!(1., 1.), vec2!(2., 2.)]), Winding::Degenerate);
+
+        let triangle = [ivec2!(0, 0), ivec2!(3, 0), ivec2!(0, 1)];
+        assert_eq!(signed_area(&triangle), Fraction::new(3, 2));
+        // far beyond what an i32 cross product holds
+        let big = [ivec2!(i32::MIN, i32::MIN), ivec2!(i32::MAX, i32::MIN), ivec2!(i32::MAX, i32::MAX)];
+        assert_eq!(winding(&big), Winding::CounterClockwise);
+        assert_eq!(signed_area(&big), Fraction::new(((1i128 << 32) - 1).pow(2), 2));
+
+        // a pentagram, its middle wound twice
+        let star = [ivec2!(0, 10), ivec2!(6, -8), ivec2!(-9, 3), ivec2!(9, 3), ivec2!(-6, -8)];
+        assert!(!contains_even_odd(&star, ivec2!(0, 0)));
+        assert!(contains_nonzero(&star, ivec2!(0, 0)));
+        assert_eq!(winding_number(&star, ivec2!(0, 0)).abs(), 2);
+        assert!(contains_even_odd(&star, ivec2!(0, 7)));
+        assert!(!contains_nonzero(&star, ivec2!(20, 0)));
+
+        // the boundary is inside, for either rule
+        let square = [ivec2!(0, 0), ivec2!(4, 0), ivec2!(4, 4), ivec2!(0, 4)];
+        for p in [ivec2!(0, 0), ivec2!(2, 0), ivec2!(4, 3), ivec2!(2, 2)] {
+            assert!(contains_even_odd(&square, p) && contains_nonzero(&square, p));
+        }
+        for p in [ivec2!(5, 0), ivec2!(-1, 4), ivec2!(2, 5)] {
+            assert!(!contains_even_odd(&square, p) && !contains_nonzero(&square, p));
+        }
+    }
+
+    #[test]
+    fn segments() {
+        let hit = segment_intersection(vec2!(0., 0.), vec2!(2., 2.), vec2!(0., 2.), vec2!(2., 0.));
+        assert_eq!(hit, Some(SegmentIntersection::Point(vec2!(1., 1.))));
+        assert_eq!(segment_intersection(vec2!(0., 0.), vec2!(1., 0.), vec2!(0., 1.), vec2!(1., 1.)), None);
+        // touching at an end
+        let touch = segment_intersection(ivec2!(0, 0), ivec2!(4, 0), ivec2!(4, 0), ivec2!(4, 7));
+        assert_eq!(touch, Some(SegmentIntersection::Point(vec2!(4., 0.))));
+        let overlap = segment_intersection(ivec2!(0, 0), ivec2!(4, 4), ivec2!(6, 6), ivec2!(2, 2));
+        assert_eq!(overlap, Some(SegmentIntersection::Overlap(vec2!(2., 2.), vec2!(4., 4.))));
+        assert!(!segments_intersect(ivec2!(0, 0), ivec2!(1, 1), ivec2!(2, 2), ivec2!(3, 3)));
+
+        // the long segment passes half a unit below `b`, far below what f32 resolves out there
+        let (a0, a1) = (ivec2!(-1_000_000_000, -1_000_000_000), ivec2!(1_000_000_000, 1_000_000_001));
+        let b = ivec2!(0, 1);
+        assert!(!segments_intersect(a0, a1, b, b + ivec2!(0, 5)));
+        assert!(segments_intersect(a0, a1, b, b - ivec2!(0, 5)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn hulls() {
+        let mut points = Vec::new();
+        for x in -3..=3 {
+            for y in -3..=3 {
+                if x * x + y * y <= 9 {
+                    points.push(ivec2!(x, y));
+                }
+            }
+        }
+        let hull = convex_hull(&points);
+        assert_eq!(hull[0], ivec2!(-3, 0));
+        assert_eq!(winding(&hull), Winding::CounterClockwise);
+        for i in 0..hull.len() {
+            let (a, b, c) = (hull[i], hull[(i + 1) % hull.len()], hull[(i + 2) % hull.len()]);
+            assert!(orient(a, b, c) > 0, "{b:?} is not a strict corner");
+        }
+        for &p in &points {
+            assert!(contains_nonzero(&hull, p));
+        }
+        assert_eq!(convex_hull(&[vec2!(0., 0.), vec2!(1., 1.), vec2!(2., 2.), vec2!(1., 1.)]).len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn triangulation() {
+        // an L, clockwise
+        let l = [ivec2!(0, 0), ivec2!(0, 4), ivec2!(2, 4), ivec2!(2, 2), ivec2!(4, 2), ivec2!(4, 0)];
+        let triangles = triangulate(&l);
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(area_of(&l, &triangles), 12.);
+
+        // a square with two square holes, one of them the wrong way round
+        let outer = [vec2!(0., 0.), vec2!(10., 0.), vec2!(10., 10.), vec2!(0., 10.)];
+        let holes = [
+            vec![vec2!(2., 2.), vec2!(2., 4.), vec2!(4., 4.), vec2!(4., 2.)],
+            vec![vec2!(6., 5.), vec2!(8., 5.), vec2!(8., 8.), vec2!(6., 8.)],
+        ];
+        let triangles = triangulate_with_holes(&outer, &holes).unwrap();
+        let points: Vec<Vec2> = outer.iter().chain(holes.iter().flatten()).copied().collect();
+        // n + 2h - 2 triangles for n vertices and h holes
+        assert_eq!(triangles.len(), 12 + 4 - 2);
+        assert_approx_eq!(area_of(&points, &triangles), 100. - 4. - 6.);
+        for &[a, b, c] in &triangles {
+            let centroid = (points[a] + points[b] + points[c]) / 3.;
+            for hole in &holes {
+                assert!(!contains_nonzero(hole, centroid));
+            }
+        }
+
+        // a comb, lots of reflex corners
+        let mut comb = vec![ivec2!(0, 0), ivec2!(20, 0)];
+        for tooth in (0..10).rev() {
+            comb.extend([ivec2!(2 * tooth + 2, 5), ivec2!(2 * tooth + 1, 5), ivec2!(2 * tooth + 1, 1)]);
+        }
+        comb.push(ivec2!(0, 5));
+        let triangles = triangulate(&comb);
+        assert_eq!(triangles.len(), comb.len() - 2);
+        assert_eq!(area_of(&comb, &triangles), signed_area(&comb).as_f64());
+
+        // holes outside the polygon or through its edge can't be joined to it
+        let square = [ivec2!(0, 0), ivec2!(4, 0), ivec2!(4, 4), ivec2!(0, 4)];
+        let inside = [ivec2!(1, 1), ivec2!(1, 2), ivec2!(2, 2)];
+        let outside = [ivec2!(10, 1), ivec2!(10, 2), ivec2!(11, 2)];
+        let across = [ivec2!(3, 1), ivec2!(3, 3), ivec2!(6, 3), ivec2!(6, 1)];
+        assert_eq!(triangulate_with_holes(&square, &[inside, outside]), Err(TriangulateError::HoleNotBridged(1)));
+        assert_eq!(triangulate_with_holes(&square, &[&across[..]]), Err(TriangulateError::HoleNotBridged(0)));
+        assert_eq!(triangulate_with_holes(&square, &[inside]).map(|t| t.len()), Ok(4 + 3 + 2 - 2));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn randomized_triangulation() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let mut unit = || rng.unit() as f64;
+        for _ in 0..200 {
+            // star shaped around the origin, snapped to the grid, with up to three holes near the middle
+            let n = 8 + (unit() * 30.) as usize;
+            let outer: Vec<IVec2> = (0..n)
+                .map(|i| {
+                    let angle = (i as f64 + unit() * 0.8) / n as f64 * core::f64::consts::TAU;
+                    let radius = 60. + unit() * 40.;
+                    ivec2!((angle.cos() * radius) as i32, (angle.sin() * radius) as i32)
+                })
+                .collect();
+            let holes: Vec<Vec<IVec2>> = (0..(unit() * 4.) as i32)
+                .map(|h| {
+                    let x = -30 + 20 * h;
+                    vec![ivec2!(x, -5), ivec2!(x, 5), ivec2!(x + 8, 3 - 6 * (h % 2))]
+                })
+                .collect();
+            let triangles = triangulate_with_holes(&outer, &holes).unwrap();
+            let points: Vec<IVec2> = outer.iter().chain(holes.iter().flatten()).copied().collect();
+            let holes_area: f64 = holes.iter().map(|hole| signed_area(hole).as_f64().abs()).sum();
+            assert_eq!(area_of(&points, &triangles), signed_area(&outer).as_f64().abs() - holes_area, "{outer:?}");
+            for &[a, b, c] in &triangles {
+                assert!(orient(points[a], points[b], points[c]) >= 0);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn offsetting() {
+        let square = [vec2!(0., 0.), vec2!(2., 0.), vec2!(2., 2.), vec2!(0., 2.)];
+        let grown = offset_polygon(&square, 1.);
+        assert_approx_eq!(grown[0], vec2!(-1., -1.));
+        assert_approx_eq!(grown[2], vec2!(3., 3.));
+        assert_approx_eq!(signed_area(&grown), 16.);
+
+        // clockwise input still grows outwards, and shrinks with a negative distance
+        let mut reversed = square;
+        reversed.reverse();
+        assert_approx_eq!(signed_area(&offset_polygon(&reversed, 1.)), -16.);
+        let shrunk = offset_polygon(&square, -0.5);
+        assert_approx_eq!(shrunk[0], vec2!(0.5, 0.5));
+
+        // a collinear vertex just moves with its edge
+        let with_mid = [vec2!(0., 0.), vec2!(1., 0.), vec2!(2., 0.), vec2!(2., 2.), vec2!(0., 2.)];
+        assert_approx_eq!(offset_polygon(&with_mid, 1.)[1], vec2!(1., -1.));
+    }
+}
@@ -0,0 +1,506 @@
+//! Collision between any two convex shapes, described only by their `Support` functions.
+//!
+//! GJK walks the Minkowski difference `A - B` towards the origin: if it contains the origin the
+//! shapes overlap, otherwise its point closest to the origin gives their distance. EPA then
+//! expands the final simplex to find how deep overlapping shapes are (needs `alloc`).
+//! ```
+//! use my_math::prelude::*;
+//! use my_math::gjk::{self, Capsule};
+//! use my_math::vec3;
+//!
+//! let capsule = Capsule::new(vec3!(0., -1., 0.), vec3!(0., 1., 0.), 0.5);
+//! let ball = Sphere::new(vec3!(2., 0., 0.), 1.);
+//! assert!((gjk::distance(&capsule, &ball).unwrap().distance - 0.5).abs() < 1e-4);
+//! ```
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::geometry::perpendicular;
+use crate::geometry::{Aabb, Obb, Segment, Sphere, Triangle};
+#[cfg(feature = "alloc")]
+use crate::intersection::Contact;
+use crate::quat::{rot_vec_by_quat, Quaternion};
+use crate::vec::Vec3;
+
+const MAX_ITERATIONS: usize = 64;
+/// GJK stops once a step gets the distance this much closer, relative to the distance
+const RELATIVE_TOLERANCE: f32 = 1e-5;
+/// Squared distances below this count as touching
+const TOUCHING: f32 = 1e-12;
+#[cfg(feature = "alloc")]
+const EPA_TOLERANCE: f32 = 1e-4;
+
+/// The shape's farthest point in direction `dir`, `dir` doesn't have to be normalized
+pub trait Support {
+    fn support(&self, dir: Vec3) -> Vec3;
+}
+
+impl<S: Support + ?Sized> Support for &S {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        (**self).support(dir)
+    }
+}
+impl Support for Vec3 {
+    fn support(&self, _: Vec3) -> Vec3 {
+        *self
+    }
+}
+impl Support for Sphere {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        let len = dir.mag();
+        if len == 0. {
+            return self.center;
+        }
+        self.center + dir * (self.radius / len)
+    }
+}
+impl Support for Aabb {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        let pick = |d: f32, min: f32, max: f32| if d >= 0. { max } else { min };
+        Vec3::new(
+            pick(dir.x, self.min.x, self.max.x),
+            pick(dir.y, self.min.y, self.max.y),
+            pick(dir.z, self.min.z, self.max.z),
+        )
+    }
+}
+impl Support for Obb {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        let h = self.half_extents.to_array();
+        (0..3).fold(self.center, |point, i| {
+            let axis = self.axes[i];
+            point + axis * if axis.dot(dir) >= 0. { h[i] } else { -h[i] }
+        })
+    }
+}
+impl Support for Segment {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        if self.start.dot(dir) >= self.end.dot(dir) { self.start } else { self.end }
+    }
+}
+impl Support for Triangle {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        [self.a, self.b, self.c].support(dir)
+    }
+}
+/// The convex hull of the points, there has to be at least one
+impl Support for [Vec3] {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        let first = *self.first().expect("a point cloud needs at least one point");
+        self.iter().fold(first, |best, &p| if p.dot(dir) > best.dot(dir) { p } else { best })
+    }
+}
+impl<const N: usize> Support for [Vec3; N] {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        self[..].support(dir)
+    }
+}
+#[cfg(feature = "alloc")]
+impl Support for Vec<Vec3> {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        self[..].support(dir)
+    }
+}
+
+/// A segment swept by a sphere
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capsule {
+    pub segment: Segment,
+    pub radius: f32,
+}
+impl Capsule {
+    pub fn new(start: Vec3, end: Vec3, radius: f32) -> Self {
+        Capsule { segment: Segment::new(start, end), radius }
+    }
+}
+impl Support for Capsule {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        Sphere::new(self.segment.support(dir), self.radius).support(dir)
+    }
+}
+
+/// `shape` rotated by `rotation` around its local origin, then moved by `offset`
+#[derive(Clone, Copy)]
+pub struct Transformed<S> {
+    pub shape: S,
+    /// Has to be normalized
+    pub rotation: Quaternion,
+    pub offset: Vec3,
+}
+impl<S> Transformed<S> {
+    pub fn new(shape: S, rotation: Quaternion, offset: Vec3) -> Self {
+        Transformed { shape, rotation, offset }
+    }
+}
+impl<S: Support> Support for Transformed<S> {
+    fn support(&self, dir: Vec3) -> Vec3 {
+        let local = self.shape.support(rot_vec_by_quat(dir, &self.rotation.conjugate()));
+        rot_vec_by_quat(local, &self.rotation) + self.offset
+    }
+}
+
+/// A point of `A - B` and the two points it came from
+#[derive(Clone, Copy)]
+struct Vertex {
+    p: Vec3,
+    a: Vec3,
+    b: Vec3,
+}
+fn minkowski<A: Support + ?Sized, B: Support + ?Sized>(a: &A, b: &B, dir: Vec3) -> Vertex {
+    let (a, b) = (a.support(dir), b.support(dir * -1.));
+    Vertex { p: a - b, a, b }
+}
+
+/// Up to 4 vertices with the barycentric weights of the point closest to the origin
+#[derive(Clone, Copy)]
+struct Simplex {
+    verts: [Vertex; 4],
+    weights: [f32; 4],
+    len: usize,
+}
+impl Simplex {
+    fn keep(&mut self, picked: &[(usize, f32)]) -> Vec3 {
+        let verts = self.verts;
+        self.len = picked.len();
+        let mut closest = Vec3::ZERO;
+        for (slot, &(index, weight)) in picked.iter().enumerate() {
+            self.verts[slot] = verts[index];
+            self.weights[slot] = weight;
+            closest = closest + verts[index].p * weight;
+        }
+        closest
+    }
+    /// Drops the vertices the closest point doesn't need, `None` when the origin is inside the tetrahedron
+    fn reduce(&mut self) -> Option<Vec3> {
+        match self.len {
+            1 => Some(self.keep(&[(0, 1.)])),
+            2 => Some(self.reduce_segment(0, 1)),
+            3 => Some(self.reduce_triangle(0, 1, 2)),
+            _ => self.reduce_tetrahedron(),
+        }
+    }
+    fn reduce_segment(&mut self, i: usize, j: usize) -> Vec3 {
+        let (a, b) = (self.verts[i].p, self.verts[j].p);
+        let ab = b - a;
+        let t = -a.dot(ab) / ab.dot(ab);
+        if t.is_nan() || t <= 0. {
+            self.keep(&[(i, 1.)])
+        } else if t >= 1. {
+            self.keep(&[(j, 1.)])
+        } else {
+            self.keep(&[(i, 1. - t), (j, t)])
+        }
+    }
+    /// The same vertex, edge and face regions as `Triangle::closest_point`, for the origin
+    fn reduce_triangle(&mut self, i: usize, j: usize, k: usize) -> Vec3 {
+        let (a, b, c) = (self.verts[i].p, self.verts[j].p, self.verts[k].p);
+        let (ab, ac) = (b - a, c - a);
+        let (d1, d2) = (-ab.dot(a), -ac.dot(a));
+        if d1 <= 0. && d2 <= 0. {
+            return self.keep(&[(i, 1.)]);
+        }
+        let (d3, d4) = (-ab.dot(b), -ac.dot(b));
+        if d3 >= 0. && d4 <= d3 {
+            return self.keep(&[(j, 1.)]);
+        }
+        let vc = d1 * d4 - d3 * d2;
+        if vc <= 0. && d1 >= 0. && d3 <= 0. {
+            let v = d1 / (d1 - d3);
+            return self.keep(&[(i, 1. - v), (j, v)]);
+        }
+        let (d5, d6) = (-ab.dot(c), -ac.dot(c));
+        if d6 >= 0. && d5 <= d6 {
+            return self.keep(&[(k, 1.)]);
+        }
+        let vb = d5 * d2 - d1 * d6;
+        if vb <= 0. && d2 >= 0. && d6 <= 0. {
+            let w = d2 / (d2 - d6);
+            return self.keep(&[(i, 1. - w), (k, w)]);
+        }
+        let va = d3 * d6 - d5 * d4;
+        if va <= 0. && d4 - d3 >= 0. && d5 - d6 >= 0. {
+            let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+            return self.keep(&[(j, 1. - w), (k, w)]);
+        }
+        let denom = 1. / (va + vb + vc);
+        let (v, w) = (vb * denom, vc * denom);
+        self.keep(&[(i, 1. - v - w), (j, v), (k, w)])
+    }
+    /// The closest of the faces the origin is in front of
+    fn reduce_tetrahedron(&mut self) -> Option<Vec3> {
+        const FACES: [[usize; 4]; 4] = [[0, 1, 2, 3], [0, 2, 3, 1], [0, 3, 1, 2], [1, 3, 2, 0]];
+        let mut best: Option<(f32, Simplex, Vec3)> = None;
+        for [i, j, k, opposite] in FACES {
+            let a = self.verts[i].p;
+            let normal = (self.verts[j].p - a).cross(self.verts[k].p - a);
+            let towards_opposite = normal.dot(self.verts[opposite].p - a);
+            if towards_opposite != 0. && normal.dot(a * -1.) * towards_opposite >= 0. {
+                continue;
+            }
+            let mut face = *self;
+            let closest = face.reduce_triangle(i, j, k);
+            let dist = closest.dot(closest);
+            if best.as_ref().is_none_or(|(best_dist, ..)| dist < *best_dist) {
+                best = Some((dist, face, closest));
+            }
+        }
+        let (_, face, closest) = best?;
+        *self = face;
+        Some(closest)
+    }
+    fn point_a(&self) -> Vec3 {
+        (0..self.len).fold(Vec3::ZERO, |sum, i| sum + self.verts[i].a * self.weights[i])
+    }
+    fn point_b(&self) -> Vec3 {
+        (0..self.len).fold(Vec3::ZERO, |sum, i| sum + self.verts[i].b * self.weights[i])
+    }
+}
+
+/// How far apart two shapes are and their closest points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Separation {
+    pub distance: f32,
+    pub point_a: Vec3,
+    pub point_b: Vec3,
+}
+
+enum Gjk {
+    Separated(Separation),
+    /// EPA starts from the simplex around the origin
+    #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+    Intersecting(Simplex),
+}
+
+fn gjk<A: Support + ?Sized, B: Support + ?Sized>(a: &A, b: &B) -> Gjk {
+    let first = minkowski(a, b, Vec3::X);
+    let mut simplex = Simplex { verts: [first; 4], weights: [1., 0., 0., 0.], len: 1 };
+    let mut v = first.p;
+    for _ in 0..MAX_ITERATIONS {
+        let vv = v.dot(v);
+        if vv <= TOUCHING {
+            return Gjk::Intersecting(simplex);
+        }
+        let w = minkowski(a, b, v * -1.);
+        let repeated = simplex.verts[..simplex.len].iter().any(|vert| vert.p == w.p);
+        if repeated || vv - v.dot(w.p) <= RELATIVE_TOLERANCE * vv {
+            break;
+        }
+        let previous = simplex;
+        simplex.verts[simplex.len] = w;
+        simplex.len += 1;
+        match simplex.reduce() {
+            // rounding can make a step go backwards on curved shapes, the last simplex was as close as it gets
+            Some(closest) if closest.dot(closest) >= vv => {
+                simplex = previous;
+                break;
+            }
+            Some(closest) => v = closest,
+            None => return Gjk::Intersecting(simplex),
+        }
+    }
+    Gjk::Separated(Separation { distance: v.mag(), point_a: simplex.point_a(), point_b: simplex.point_b() })
+}
+
+/// Touching counts as intersecting
+pub fn intersects<A: Support + ?Sized, B: Support + ?Sized>(a: &A, b: &B) -> bool {
+    matches!(gjk(a, b), Gjk::Intersecting(_))
+}
+
+/// `None` when the shapes intersect
+pub fn distance<A: Support + ?Sized, B: Support + ?Sized>(a: &A, b: &B) -> Option<Separation> {
+    match gjk(a, b) {
+        Gjk::Separated(separation) => Some(separation),
+        Gjk::Intersecting(_) => None,
+    }
+}
+
+/// Penetration depth and normal through EPA, `None` when the shapes don't intersect or are both flat
+/// enough that their Minkowski difference has no volume
+#[cfg(feature = "alloc")]
+pub fn penetration<A: Support + ?Sized, B: Support + ?Sized>(a: &A, b: &B) -> Option<Contact> {
+    let Gjk::Intersecting(simplex) = gjk(a, b) else {
+        return None;
+    };
+    let mut verts: Vec<Vertex> = simplex.verts[..simplex.len].to_vec();
+    grow_to_tetrahedron(a, b, &mut verts)?;
+
+    // faces wind counter clockwise seen from outside
+    let mut faces: Vec<[usize; 3]> = Vec::new();
+    for [i, j, k, opposite] in [[0, 1, 2, 3], [0, 2, 3, 1], [0, 3, 1, 2], [1, 3, 2, 0]] {
+        let normal = (verts[j].p - verts[i].p).cross(verts[k].p - verts[i].p);
+        faces.push(if normal.dot(verts[opposite].p - verts[i].p) > 0. { [i, k, j] } else { [i, j, k] });
+    }
+
+    for _ in 0..MAX_ITERATIONS {
+        let (face, normal, dist) = faces
+            .iter()
+            .filter_map(|&face| {
+                let normal = face_normal(&verts, face)?;
+                Some((face, normal, normal.dot(verts[face[0]].p)))
+            })
+            .min_by(|x, y| x.2.total_cmp(&y.2))?;
+        let w = minkowski(a, b, normal);
+        if w.p.dot(normal) - dist <= EPA_TOLERANCE {
+            let [i, j, k] = face.map(|index| verts[index]);
+            let bary = Triangle::new(i.p, j.p, k.p).barycentric(normal * dist);
+            let point_a = i.a * bary.x + j.a * bary.y + k.a * bary.z;
+            let point_b = i.b * bary.x + j.b * bary.y + k.b * bary.z;
+            return Some(Contact { point: (point_a + point_b) * 0.5, normal, depth: dist });
+        }
+
+        // every face `w` can see goes, the edges only one of them had are the hole's rim
+        let index = verts.len();
+        verts.push(w);
+        let mut rim: Vec<[usize; 2]> = Vec::new();
+        faces.retain(|&face| {
+            let visible = face_normal(&verts, face).is_none_or(|n| n.dot(w.p - verts[face[0]].p) > 0.);
+            if visible {
+                for edge in [[face[0], face[1]], [face[1], face[2]], [face[2], face[0]]] {
+                    match rim.iter().position(|&[x, y]| x == edge[1] && y == edge[0]) {
+                        Some(shared) => {
+                            rim.swap_remove(shared);
+                        }
+                        None => rim.push(edge),
+                    }
+                }
+            }
+            !visible
+        });
+        faces.extend(rim.into_iter().map(|[x, y]| [x, y, index]));
+    }
+    None
+}
+
+#[cfg(feature = "alloc")]
+fn face_normal(verts: &[Vertex], [i, j, k]: [usize; 3]) -> Option<Vec3> {
+    let normal = (verts[j].p - verts[i].p).cross(verts[k].p - verts[i].p);
+    let len = normal.mag();
+    (len > 0.).then(|| normal / len)
+}
+
+/// GJK can stop on a point, edge or triangle touching the origin, EPA needs a volume around it
+#[cfg(feature = "alloc")]
+fn grow_to_tetrahedron<A: Support + ?Sized, B: Support + ?Sized>(a: &A, b: &B, verts: &mut Vec<Vertex>) -> Option<()> {
+    const MIN_SPREAD: f32 = 1e-6;
+    let axes = [Vec3::X, Vec3::Y, Vec3::Z, Vec3::NEG_X, Vec3::NEG_Y, Vec3::NEG_Z];
+    if verts.len() == 1 {
+        let w = axes.into_iter().map(|dir| minkowski(a, b, dir)).find(|w| (w.p - verts[0].p).mag() > MIN_SPREAD)?;
+        verts.push(w);
+    }
+    if verts.len() == 2 {
+        let line = (verts[1].p - verts[0].p).norm();
+        let (u, v) = (perpendicular(line), line.cross(perpendicular(line)));
+        let w = [u, v, u * -1., v * -1., u + v, (u + v) * -1.]
+            .into_iter()
+            .map(|dir| minkowski(a, b, dir))
+            .find(|w| (w.p - verts[0].p).cross(line).mag() > MIN_SPREAD)?;
+        verts.push(w);
+    }
+    if verts.len() == 3 {
+        let normal = (verts[1].p - verts[0].p).cross(verts[2].p - verts[0].p).norm();
+        let w = [normal, normal * -1.]
+            .into_iter()
+            .map(|dir| minkowski(a, b, dir))
+            .find(|w| normal.dot(w.p - verts[0].p).abs() > MIN_SPREAD)?;
+        verts.push(w);
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intersection::Intersects;
+    use crate::test_rng::Rng;
+    use crate::{assert_approx_eq, vec3};
+    use core::f32::consts::{FRAC_PI_4, FRAC_PI_6};
+
+    #[test]
+    fn distances() {
+        let a = Sphere::new(Vec3::ZERO, 1.);
+        let b = Sphere::new(vec3!(3., 4., 0.), 2.);
+        let sep = distance(&a, &b).unwrap();
+        assert!((sep.distance - 2.).abs() < 1e-4);
+        assert_approx_eq!(sep.point_a, vec3!(0.6, 0.8, 0.), epsilon = 1e-3);
+        assert_approx_eq!(sep.point_b, vec3!(1.8, 2.4, 0.), epsilon = 1e-3);
+
+        let unit = Aabb::new(vec3!(-1., -1., -1.), vec3!(1., 1., 1.));
+        let sep = distance(&unit, &vec3!(3., 0.5, 0.)).unwrap();
+        assert!((sep.distance - 2.).abs() < 1e-5);
+        assert_approx_eq!(sep.point_a, vec3!(1., 0.5, 0.), epsilon = 1e-5);
+
+        // a cube turned 45 degrees reaches sqrt(2) along x
+        let turned = Transformed::new(unit, Quaternion::from_axis_angle(Vec3::Z, FRAC_PI_4), vec3!(5., 0., 0.));
+        let sep = distance(&turned, &Vec3::ZERO).unwrap();
+        assert!((sep.distance - (5. - 2f32.sqrt())).abs() < 1e-4);
+
+        let capsule = Capsule::new(vec3!(-2., 0., 0.), vec3!(2., 0., 0.), 0.5);
+        let hull = unit.corners().map(|corner| corner + vec3!(0., 3., 0.));
+        assert!((distance(&capsule, &hull).unwrap().distance - 1.5).abs() < 1e-4);
+        assert!(intersects(&capsule, &Obb::new(vec3!(0., 1., 0.), vec3!(0.6, 0.6, 0.6), &Quaternion::from_axis_angle(Vec3::X, FRAC_PI_6))));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn penetrations() {
+        let a = Sphere::new(Vec3::ZERO, 1.);
+        let b = Sphere::new(vec3!(1.5, 0., 0.), 1.);
+        let contact = penetration(&a, &b).unwrap();
+        assert!((contact.depth - 0.5).abs() < 1e-3);
+        assert_approx_eq!(contact.normal, Vec3::X, epsilon = 1e-2);
+        assert!(penetration(&a, &Sphere::new(vec3!(3., 0., 0.), 1.)).is_none());
+
+        let unit = Aabb::new(vec3!(-1., -1., -1.), vec3!(1., 1., 1.));
+        let other = Aabb::new(vec3!(0.5, -3., -3.), vec3!(4., 3., 3.));
+        let contact = penetration(&unit, &other).unwrap();
+        assert!((contact.depth - 0.5).abs() < 1e-4);
+        assert_approx_eq!(contact.normal, Vec3::X, epsilon = 1e-4);
+
+        // boxes touching face to face make GJK stop on a flat simplex
+        let touching = Aabb::new(vec3!(1., -1., -1.), vec3!(3., 1., 1.));
+        let contact = penetration(&unit, &touching).unwrap();
+        assert!(contact.depth.abs() < 1e-4);
+    }
+
+    #[test]
+    fn randomized() {
+        let mut rng = Rng(0x853c_49e6_748f_ea9b);
+        let mut rand = move |lo: f32, hi: f32| rng.range(lo, hi);
+        for _ in 0..300 {
+            let mut point = || vec3!(rand(-3., 3.), rand(-3., 3.), rand(-3., 3.));
+            let (c1, c2, p) = (point(), point(), point());
+            let sphere = Sphere::new(c1, rand(0.2, 1.5));
+            let other = Sphere::new(c2, rand(0.2, 1.5));
+            let aabb = Aabb::new(p, p + vec3!(rand(0.1, 2.), rand(0.1, 2.), rand(0.1, 2.)));
+
+            let gap = (c2 - c1).mag() - sphere.radius - other.radius;
+            if gap.abs() > 1e-3 {
+                assert_eq!(intersects(&sphere, &other), gap < 0.);
+            }
+            match distance(&sphere, &other) {
+                Some(sep) => assert!((sep.distance - gap).abs() < 1e-3),
+                None => assert!(gap < 1e-3),
+            }
+            #[cfg(feature = "alloc")]
+            if let Some(contact) = penetration(&sphere, &other) {
+                assert!((contact.depth + gap).abs() < 2e-2, "{} vs {}", contact.depth, -gap);
+            }
+
+            let gap = aabb.distance(c1) - sphere.radius;
+            if gap.abs() > 1e-3 {
+                assert_eq!(intersects(&sphere, &aabb), sphere.intersects(&aabb));
+            }
+            if let (Some(sep), true) = (distance(&sphere, &aabb), gap > 0.) {
+                assert!((sep.distance - gap).abs() < 1e-3, "{sphere:?} {aabb:?} {sep:?} {gap}");
+            }
+            // the separating move from EPA really separates
+            #[cfg(feature = "alloc")]
+            if let Some(contact) = penetration(&sphere, &aabb) {
+                let moved = Aabb::new(aabb.min + contact.normal * (contact.depth + 1e-2), aabb.max + contact.normal * (contact.depth + 1e-2));
+                assert!(!intersects(&sphere, &moved));
+            }
+        }
+    }
+}
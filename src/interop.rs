@@ -0,0 +1,270 @@
+//! `From` conversions to the types of other math crates, each behind the feature of the same name.
+//!
+//! `Matrix` stores rows while glam, cgmath and mint's `ColumnMatrix` store columns, so those
+//! conversions transpose the storage, the matrix they describe stays the same.
+use crate::matrix::Matrix;
+use crate::quat::Quaternion;
+use crate::vec::{IVec2, IVec3, Vec2, Vec3, Vec4};
+
+/// Vectors with the same fields on both sides, `implVecFrom!(Vec3 => other::Vec3; x, y, z)`
+macro_rules! implVecFrom {
+    ($($ours:ty => $theirs:ty; $($field:ident),+);+ $(;)?) => {
+        $(
+            impl From<$theirs> for $ours {
+                fn from(v: $theirs) -> Self {
+                    Self { $($field: v.$field),+ }
+                }
+            }
+            impl From<$ours> for $theirs {
+                fn from(v: $ours) -> Self {
+                    <$theirs>::from([$(v.$field),+])
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "mint")]
+mod mint_conv {
+    use super::*;
+
+    implVecFrom!(
+        Vec2 => mint::Vector2<f32>; x, y;
+        Vec3 => mint::Vector3<f32>; x, y, z;
+        Vec4 => mint::Vector4<f32>; x, y, z, w;
+        IVec2 => mint::Vector2<i32>; x, y;
+        IVec3 => mint::Vector3<i32>; x, y, z;
+    );
+
+    impl From<mint::Quaternion<f32>> for Quaternion {
+        fn from(q: mint::Quaternion<f32>) -> Self {
+            Quaternion::new(q.s, q.v.into())
+        }
+    }
+    impl From<Quaternion> for mint::Quaternion<f32> {
+        fn from(q: Quaternion) -> Self {
+            mint::Quaternion { v: q.v.into(), s: q.w }
+        }
+    }
+
+    macro_rules! implMintMat {
+        ($($n:literal => $rows:ident, $cols:ident);+) => {
+            $(
+                impl From<mint::$rows<f32>> for Matrix<$n, $n> {
+                    fn from(m: mint::$rows<f32>) -> Self {
+                        Matrix { data: m.into() }
+                    }
+                }
+                impl From<Matrix<$n, $n>> for mint::$rows<f32> {
+                    fn from(m: Matrix<$n, $n>) -> Self {
+                        m.data.into()
+                    }
+                }
+                impl From<mint::$cols<f32>> for Matrix<$n, $n> {
+                    fn from(m: mint::$cols<f32>) -> Self {
+                        Matrix::from_cols_array(m.into())
+                    }
+                }
+                impl From<Matrix<$n, $n>> for mint::$cols<f32> {
+                    fn from(m: Matrix<$n, $n>) -> Self {
+                        m.to_cols_array().into()
+                    }
+                }
+            )+
+        };
+    }
+    implMintMat!(2 => RowMatrix2, ColumnMatrix2; 3 => RowMatrix3, ColumnMatrix3; 4 => RowMatrix4, ColumnMatrix4);
+}
+
+#[cfg(feature = "glam")]
+mod glam_conv {
+    use super::*;
+
+    implVecFrom!(
+        Vec2 => glam::Vec2; x, y;
+        Vec3 => glam::Vec3; x, y, z;
+        Vec4 => glam::Vec4; x, y, z, w;
+        IVec2 => glam::IVec2; x, y;
+        IVec3 => glam::IVec3; x, y, z;
+    );
+
+    impl From<glam::Quat> for Quaternion {
+        fn from(q: glam::Quat) -> Self {
+            Quaternion::new(q.w, Vec3::new(q.x, q.y, q.z))
+        }
+    }
+    impl From<Quaternion> for glam::Quat {
+        fn from(q: Quaternion) -> Self {
+            glam::Quat::from_xyzw(q.v.x, q.v.y, q.v.z, q.w)
+        }
+    }
+
+    macro_rules! implGlamMat {
+        ($($n:literal => $mat:ident),+) => {
+            $(
+                impl From<glam::$mat> for Matrix<$n, $n> {
+                    fn from(m: glam::$mat) -> Self {
+                        Matrix::from_cols_array(m.to_cols_array_2d())
+                    }
+                }
+                impl From<Matrix<$n, $n>> for glam::$mat {
+                    fn from(m: Matrix<$n, $n>) -> Self {
+                        glam::$mat::from_cols_array_2d(&m.to_cols_array())
+                    }
+                }
+            )+
+        };
+    }
+    implGlamMat!(2 => Mat2, 3 => Mat3, 4 => Mat4);
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_conv {
+    use super::*;
+
+    implVecFrom!(
+        Vec2 => nalgebra::Vector2<f32>; x, y;
+        Vec3 => nalgebra::Vector3<f32>; x, y, z;
+        Vec4 => nalgebra::Vector4<f32>; x, y, z, w;
+        IVec2 => nalgebra::Vector2<i32>; x, y;
+        IVec3 => nalgebra::Vector3<i32>; x, y, z;
+    );
+
+    impl From<nalgebra::Quaternion<f32>> for Quaternion {
+        fn from(q: nalgebra::Quaternion<f32>) -> Self {
+            Quaternion::new(q.w, Vec3::new(q.i, q.j, q.k))
+        }
+    }
+    impl From<Quaternion> for nalgebra::Quaternion<f32> {
+        fn from(q: Quaternion) -> Self {
+            nalgebra::Quaternion::new(q.w, q.v.x, q.v.y, q.v.z)
+        }
+    }
+    impl From<nalgebra::UnitQuaternion<f32>> for Quaternion {
+        fn from(q: nalgebra::UnitQuaternion<f32>) -> Self {
+            q.into_inner().into()
+        }
+    }
+
+    /// nalgebra indexes by (row, column) whatever its storage, so any size converts
+    impl<const ROW: usize, const COL: usize> From<nalgebra::SMatrix<f32, ROW, COL>> for Matrix<ROW, COL> {
+        fn from(m: nalgebra::SMatrix<f32, ROW, COL>) -> Self {
+            Matrix { data: core::array::from_fn(|row| core::array::from_fn(|col| m[(row, col)])) }
+        }
+    }
+    impl<const ROW: usize, const COL: usize> From<Matrix<ROW, COL>> for nalgebra::SMatrix<f32, ROW, COL> {
+        fn from(m: Matrix<ROW, COL>) -> Self {
+            nalgebra::SMatrix::from_fn(|row, col| m.data[row][col])
+        }
+    }
+}
+
+#[cfg(feature = "cgmath")]
+mod cgmath_conv {
+    use super::*;
+
+    implVecFrom!(
+        Vec2 => cgmath::Vector2<f32>; x, y;
+        Vec3 => cgmath::Vector3<f32>; x, y, z;
+        Vec4 => cgmath::Vector4<f32>; x, y, z, w;
+        IVec2 => cgmath::Vector2<i32>; x, y;
+        IVec3 => cgmath::Vector3<i32>; x, y, z;
+    );
+
+    impl From<cgmath::Quaternion<f32>> for Quaternion {
+        fn from(q: cgmath::Quaternion<f32>) -> Self {
+            Quaternion::new(q.s, q.v.into())
+        }
+    }
+    impl From<Quaternion> for cgmath::Quaternion<f32> {
+        fn from(q: Quaternion) -> Self {
+            cgmath::Quaternion::from_sv(q.w, q.v.into())
+        }
+    }
+
+    macro_rules! implCgmathMat {
+        ($($n:literal => $mat:ident),+) => {
+            $(
+                impl From<cgmath::$mat<f32>> for Matrix<$n, $n> {
+                    fn from(m: cgmath::$mat<f32>) -> Self {
+                        Matrix::from_cols_array(m.into())
+                    }
+                }
+                impl From<Matrix<$n, $n>> for cgmath::$mat<f32> {
+                    fn from(m: Matrix<$n, $n>) -> Self {
+                        m.to_cols_array().into()
+                    }
+                }
+            )+
+        };
+    }
+    implCgmathMat!(2 => Matrix2, 3 => Matrix3, 4 => Matrix4);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_mat, vec3};
+
+    #[cfg(feature = "mint")]
+    #[test]
+    fn mint() {
+        let v: mint::Vector3<f32> = vec3!(1., 2., 3.).into();
+        assert_eq!(Vec3::from(v), vec3!(1., 2., 3.));
+
+        let mat = new_mat!(2, 2, [1., 2., 3., 4.]);
+        let rows: mint::RowMatrix2<f32> = mat.into();
+        let cols: mint::ColumnMatrix2<f32> = mat.into();
+        assert_eq!(rows.x, mint::Vector2 { x: 1., y: 2. });
+        assert_eq!(cols.x, mint::Vector2 { x: 1., y: 3. });
+        assert_eq!(Matrix::from(cols).data, mat.data);
+
+        let q: mint::Quaternion<f32> = Quaternion::new(1., vec3!(2., 3., 4.)).into();
+        assert_eq!(q.s, 1.);
+        assert_eq!(Quaternion::from(q).v, vec3!(2., 3., 4.));
+    }
+
+    #[cfg(feature = "glam")]
+    #[test]
+    fn glam() {
+        let mat = new_mat!(3, 3, [1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+        let g: glam::Mat3 = mat.into();
+        assert_eq!(g.x_axis, glam::Vec3::new(1., 4., 7.));
+        assert_eq!(g * glam::Vec3::new(1., 0., 0.), glam::Vec3::from(mat * Vec3::new(1., 0., 0.)));
+        assert_eq!(Matrix::from(g).data, mat.data);
+
+        let q: glam::Quat = Quaternion::new(1., vec3!(0., 0., 0.)).into();
+        assert_eq!(q, glam::Quat::IDENTITY);
+        assert_eq!(IVec3::from(glam::IVec3::new(1, -2, 3)), IVec3::new(1, -2, 3));
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn nalgebra() {
+        let mat = new_mat!(2, 3, [1., 2., 3., 4., 5., 6.]);
+        let n: nalgebra::Matrix2x3<f32> = mat.into();
+        assert_eq!(n[(0, 2)], 3.);
+        assert_eq!(n[(1, 0)], 4.);
+        assert_eq!(Matrix::from(n).data, mat.data);
+        let v: nalgebra::Vector3<f32> = vec3!(1., 2., 3.).into();
+        assert_eq!(v.z, 3.);
+
+        let q = nalgebra::UnitQuaternion::from_axis_angle(&nalgebra::Vector3::z_axis(), 1.);
+        let ours = Quaternion::from(q);
+        assert_eq!(ours.w, q.w);
+        assert_eq!(nalgebra::Quaternion::from(ours), *q.quaternion());
+    }
+
+    #[cfg(feature = "cgmath")]
+    #[test]
+    fn cgmath() {
+        let mat = new_mat!(4, 4, [1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13., 14., 15., 16.]);
+        let c: cgmath::Matrix4<f32> = mat.into();
+        assert_eq!(c.x, cgmath::Vector4::new(1., 5., 9., 13.));
+        assert_eq!(Matrix::from(c).data, mat.data);
+
+        let q: cgmath::Quaternion<f32> = Quaternion::new(0.5, vec3!(1., 2., 3.)).into();
+        assert_eq!(q.s, 0.5);
+        assert_eq!(Vec2::from(cgmath::Vector2::new(1., 2.)).y, 2.);
+    }
+}
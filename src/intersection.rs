@@ -0,0 +1,425 @@
+//! Raycasts and overlap tests between the `geometry` primitives.
+//!
+//! `Ray::cast_*` return the first `RayHit` along the ray. `Intersects` answers yes or no for a
+//! pair of shapes, and the `contact_*` methods also say how deep the overlap is and which way to separate.
+use crate::geometry::{Aabb, Plane, Ray, Sphere, Triangle};
+use crate::math;
+use crate::vec::Vec3;
+
+/// Below this a ray counts as parallel to a plane or triangle
+const PARALLEL_EPSILON: f32 = 1e-8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    /// Along the ray, the direction is normalized so it's also the distance from the origin
+    pub distance: f32,
+    pub point: Vec3,
+    /// Unit normal of the surface that was hit, facing the side the ray came from
+    pub normal: Vec3,
+    /// `(u, v, w)` with `point == a*u + b*v + c*w`, only for triangles
+    pub barycentric: Option<Vec3>,
+}
+
+/// How two overlapping shapes touch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// A point in the overlap
+    pub point: Vec3,
+    /// Unit vector from the first shape towards the second, moving the second by `normal * depth` separates them
+    pub normal: Vec3,
+    pub depth: f32,
+}
+
+impl Ray {
+    /// Both sides of the plane can be hit, a parallel ray never does
+    pub fn cast_plane(&self, plane: &Plane) -> Option<RayHit> {
+        let denom = plane.normal.dot(self.dir);
+        if denom.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+        let distance = -plane.signed_distance(self.origin) / denom;
+        if distance < 0. {
+            return None;
+        }
+        let normal = if denom > 0. { plane.normal * -1. } else { plane.normal };
+        Some(RayHit { distance, point: self.at(distance), normal, barycentric: None })
+    }
+    /// A ray starting inside hits the surface where it leaves
+    pub fn cast_sphere(&self, sphere: &Sphere) -> Option<RayHit> {
+        let offset = self.origin - sphere.center;
+        let b = offset.dot(self.dir);
+        let c = offset.dot(offset) - sphere.radius * sphere.radius;
+        // outside and pointing away
+        if c > 0. && b > 0. {
+            return None;
+        }
+        let discriminant = b * b - c;
+        if discriminant < 0. {
+            return None;
+        }
+        let root = math::sqrtf(discriminant);
+        let distance = if -b - root >= 0. { -b - root } else { -b + root };
+        let point = self.at(distance);
+        let outward = (point - sphere.center).norm();
+        let normal = if c > 0. { outward } else { outward * -1. };
+        Some(RayHit { distance, point, normal, barycentric: None })
+    }
+    /// Slab test, a ray starting inside hits the face it leaves through
+    pub fn cast_aabb(&self, aabb: &Aabb) -> Option<RayHit> {
+        let (origin, dir) = (self.origin.to_array(), self.dir.to_array());
+        let (min, max) = (aabb.min.to_array(), aabb.max.to_array());
+        let (mut t_near, mut t_far) = (f32::NEG_INFINITY, f32::INFINITY);
+        let (mut near_axis, mut far_axis) = (0, 0);
+        for axis in 0..3 {
+            if dir[axis] == 0. {
+                if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let inv = 1. / dir[axis];
+            let (mut t0, mut t1) = ((min[axis] - origin[axis]) * inv, (max[axis] - origin[axis]) * inv);
+            if t0 > t1 {
+                core::mem::swap(&mut t0, &mut t1);
+            }
+            if t0 > t_near {
+                t_near = t0;
+                near_axis = axis;
+            }
+            if t1 < t_far {
+                t_far = t1;
+                far_axis = axis;
+            }
+            if t_near > t_far || t_far < 0. {
+                return None;
+            }
+        }
+        let (distance, axis, sign) = if t_near >= 0. {
+            (t_near, near_axis, -dir[near_axis].signum())
+        } else {
+            (t_far, far_axis, dir[far_axis].signum())
+        };
+        let mut normal = [0.; 3];
+        normal[axis] = sign;
+        Some(RayHit { distance, point: self.at(distance), normal: Vec3::from_slice(normal), barycentric: None })
+    }
+    /// Möller–Trumbore, both faces count
+    pub fn cast_triangle(&self, tri: &Triangle) -> Option<RayHit> {
+        let (ab, ac) = (tri.b - tri.a, tri.c - tri.a);
+        let p = self.dir.cross(ac);
+        let det = ab.dot(p);
+        if det.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+        let inv_det = 1. / det;
+        let s = self.origin - tri.a;
+        let v = s.dot(p) * inv_det;
+        if !(0. ..=1.).contains(&v) {
+            return None;
+        }
+        let q = s.cross(ab);
+        let w = self.dir.dot(q) * inv_det;
+        if w < 0. || v + w > 1. {
+            return None;
+        }
+        let distance = ac.dot(q) * inv_det;
+        if distance < 0. {
+            return None;
+        }
+        let normal = ab.cross(ac).norm();
+        Some(RayHit {
+            distance,
+            point: self.at(distance),
+            normal: if det > 0. { normal } else { normal * -1. },
+            barycentric: Some(Vec3::new(1. - v - w, v, w)),
+        })
+    }
+}
+
+impl Sphere {
+    pub fn contact_sphere(&self, other: &Sphere) -> Option<Contact> {
+        let offset = other.center - self.center;
+        let dist = offset.mag();
+        let depth = self.radius + other.radius - dist;
+        if depth < 0. {
+            return None;
+        }
+        // concentric spheres have no preferred direction
+        let normal = if dist > 0. { offset / dist } else { Vec3::Y };
+        Some(Contact { point: self.center + normal * (self.radius - depth * 0.5), normal, depth })
+    }
+    pub fn contact_aabb(&self, aabb: &Aabb) -> Option<Contact> {
+        let closest = aabb.closest_point(self.center);
+        let offset = closest - self.center;
+        let dist_sq = offset.dot(offset);
+        if dist_sq > self.radius * self.radius {
+            return None;
+        }
+        if dist_sq > 0. {
+            let dist = math::sqrtf(dist_sq);
+            return Some(Contact { point: closest, normal: offset / dist, depth: self.radius - dist });
+        }
+        // the center is inside, leave through the nearest face
+        let (center, min, max) = (self.center.to_array(), aabb.min.to_array(), aabb.max.to_array());
+        let (mut best, mut axis, mut sign) = (f32::INFINITY, 0, 1.);
+        for i in 0..3 {
+            for (gap, face_sign) in [(center[i] - min[i], -1.), (max[i] - center[i], 1.)] {
+                if gap < best {
+                    (best, axis, sign) = (gap, i, face_sign);
+                }
+            }
+        }
+        let mut normal = [0.; 3];
+        // the box sits on the far side of the face the sphere leaves through
+        normal[axis] = -sign;
+        Some(Contact { point: self.center, normal: Vec3::from_slice(normal), depth: self.radius + best })
+    }
+}
+
+impl Aabb {
+    /// Pushes along the axis with the least overlap
+    pub fn contact_aabb(&self, other: &Aabb) -> Option<Contact> {
+        let overlap_min = self.min.max(other.min);
+        let overlap_max = self.max.min(other.max);
+        let overlap = (overlap_max - overlap_min).to_array();
+        if overlap.iter().any(|&len| len < 0.) {
+            return None;
+        }
+        let axis = (0..3).fold(0, |best, i| if overlap[i] < overlap[best] { i } else { best });
+        let mut normal = [0.; 3];
+        normal[axis] = if other.center().to_array()[axis] >= self.center().to_array()[axis] { 1. } else { -1. };
+        Some(Contact {
+            point: (overlap_min + overlap_max) * 0.5,
+            normal: Vec3::from_slice(normal),
+            depth: overlap[axis],
+        })
+    }
+}
+
+impl Triangle {
+    /// Separating axis test on the box's 3 axes, the triangle's normal and the 9 edge cross products
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let center = aabb.center();
+        let extents = aabb.half_extents();
+        let verts = [self.a - center, self.b - center, self.c - center];
+        let edges = [verts[1] - verts[0], verts[2] - verts[1], verts[0] - verts[2]];
+
+        let separated = |axis: Vec3| {
+            let proj = verts.map(|v| v.dot(axis));
+            let radius = extents.x * axis.x.abs() + extents.y * axis.y.abs() + extents.z * axis.z.abs();
+            proj[0].min(proj[1]).min(proj[2]) > radius || proj[0].max(proj[1]).max(proj[2]) < -radius
+        };
+        let box_axes = [Vec3::X, Vec3::Y, Vec3::Z];
+        if box_axes.into_iter().any(separated) || separated(edges[0].cross(edges[1])) {
+            return false;
+        }
+        !box_axes.iter().any(|axis| edges.iter().any(|edge| separated(axis.cross(*edge))))
+    }
+}
+
+/// Whether two shapes share at least one point, touching counts
+pub trait Intersects<Rhs = Self> {
+    fn intersects(&self, other: &Rhs) -> bool;
+}
+
+macro_rules! implIntersects {
+    ($($lhs:ty, $rhs:ty => |$a:ident, $b:ident| $body:expr);+ $(;)?) => {
+        $(
+            impl Intersects<$rhs> for $lhs {
+                fn intersects(&self, other: &$rhs) -> bool {
+                    let ($a, $b) = (self, other);
+                    $body
+                }
+            }
+        )+
+    };
+}
+implIntersects!(
+    Sphere, Sphere => |a, b| a.contact_sphere(b).is_some();
+    Aabb, Aabb => |a, b| a.contact_aabb(b).is_some();
+    Sphere, Aabb => |a, b| a.contact_aabb(b).is_some();
+    Aabb, Sphere => |a, b| b.contact_aabb(a).is_some();
+    Triangle, Aabb => |a, b| a.intersects_aabb(b);
+    Aabb, Triangle => |a, b| b.intersects_aabb(a);
+    Ray, Plane => |a, b| a.cast_plane(b).is_some();
+    Ray, Sphere => |a, b| a.cast_sphere(b).is_some();
+    Ray, Aabb => |a, b| a.cast_aabb(b).is_some();
+    Ray, Triangle => |a, b| a.cast_triangle(b).is_some();
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+    use crate::{assert_approx_eq, vec3};
+
+    fn int_vec(rng: &mut Rng, lo: f32, hi: f32) -> Vec3 {
+        let v = rng.vec3(lo, hi);
+        vec3!(v.x.round(), v.y.round(), v.z.round())
+    }
+    fn random_ray(rng: &mut Rng) -> Ray {
+        Ray::new(rng.vec3(-4., 4.), rng.vec3(-1., 1.))
+    }
+
+    type Distance<'a> = &'a dyn Fn(Vec3) -> f32;
+
+    /// The smallest distance from points on the ray to a convex shape, by ternary search
+    fn ray_distance(ray: &Ray, distance: impl Fn(Vec3) -> f32) -> f32 {
+        let (mut lo, mut hi) = (0f32, 20f32);
+        for _ in 0..200 {
+            let (m1, m2) = (lo + (hi - lo) / 3., hi - (hi - lo) / 3.);
+            if distance(ray.at(m1)) > distance(ray.at(m2)) {
+                lo = m1;
+            } else {
+                hi = m2;
+            }
+        }
+        distance(ray.at(lo))
+    }
+
+    #[test]
+    fn raycasts() {
+        let ray = Ray::new(vec3!(0., 0., -5.), Vec3::Z);
+        let hit = ray.cast_sphere(&Sphere::new(Vec3::ZERO, 1.)).unwrap();
+        assert_eq!((hit.distance, hit.point, hit.normal), (4., vec3!(0., 0., -1.), Vec3::NEG_Z));
+        let hit = Ray::new(Vec3::ZERO, Vec3::Z).cast_sphere(&Sphere::new(Vec3::ZERO, 1.)).unwrap();
+        assert_eq!((hit.distance, hit.normal), (1., Vec3::NEG_Z));
+
+        let hit = ray.cast_aabb(&Aabb::new(vec3!(-1., -1., -1.), vec3!(1., 1., 1.))).unwrap();
+        assert_eq!((hit.distance, hit.normal), (4., Vec3::NEG_Z));
+        assert!(ray.cast_aabb(&Aabb::new(vec3!(1., 1., 1.), vec3!(2., 2., 2.))).is_none());
+
+        let tri = Triangle::new(vec3!(-1., -1., 0.), vec3!(1., -1., 0.), vec3!(0., 1., 0.));
+        let hit = ray.cast_triangle(&tri).unwrap();
+        assert_eq!((hit.distance, hit.normal), (5., Vec3::NEG_Z));
+        assert_eq!(hit.barycentric, Some(vec3!(0.25, 0.25, 0.5)));
+        assert!(Ray::new(vec3!(0., 0., -5.), Vec3::NEG_Z).cast_triangle(&tri).is_none());
+
+        let hit = ray.cast_plane(&Plane::new(Vec3::Z, 2.)).unwrap();
+        assert_eq!((hit.distance, hit.point, hit.normal), (7., vec3!(0., 0., 2.), Vec3::NEG_Z));
+        assert!(ray.cast_plane(&Plane::new(Vec3::X, 2.)).is_none());
+    }
+
+    #[test]
+    fn overlaps() {
+        let a = Sphere::new(Vec3::ZERO, 1.);
+        let contact = a.contact_sphere(&Sphere::new(vec3!(1.5, 0., 0.), 1.)).unwrap();
+        assert_eq!((contact.normal, contact.depth, contact.point), (Vec3::X, 0.5, vec3!(0.75, 0., 0.)));
+        assert!(!a.intersects(&Sphere::new(vec3!(2.5, 0., 0.), 1.)));
+
+        let unit = Aabb::new(vec3!(-1., -1., -1.), vec3!(1., 1., 1.));
+        let contact = unit.contact_aabb(&Aabb::new(vec3!(0.5, -3., -3.), vec3!(4., 3., 3.))).unwrap();
+        assert_eq!((contact.normal, contact.depth), (Vec3::X, 0.5));
+        let contact = Sphere::new(vec3!(0., 1.5, 0.), 1.).contact_aabb(&unit).unwrap();
+        assert_eq!((contact.normal, contact.depth, contact.point), (Vec3::NEG_Y, 0.5, vec3!(0., 1., 0.)));
+        let contact = Sphere::new(vec3!(0., 0.75, 0.), 0.5).contact_aabb(&unit).unwrap();
+        assert_eq!((contact.normal, contact.depth), (Vec3::NEG_Y, 0.75));
+
+        let tri = Triangle::new(vec3!(2., 0., 0.), vec3!(0., 2., 0.), vec3!(0., 0., 2.));
+        assert!(tri.intersects(&unit) && unit.intersects(&tri));
+        let far = Triangle::new(vec3!(3.5, 0., 0.), vec3!(0., 3.5, 0.), vec3!(0., 0., 3.5));
+        // every vertex is outside the box's slabs only along the triangle's normal
+        assert!(!far.intersects(&unit));
+    }
+
+    #[test]
+    fn randomized_raycasts() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..500 {
+            let ray = random_ray(&mut rng);
+            let sphere = Sphere::new(rng.vec3(-3., 3.), rng.range(0.2, 2.));
+            let aabb = Aabb::new(rng.vec3(-3., 3.), rng.vec3(-3., 3.));
+            let tri = Triangle::new(rng.vec3(-3., 3.), rng.vec3(-3., 3.), rng.vec3(-3., 3.));
+
+            let shapes: [(Option<RayHit>, Distance); 3] = [
+                (ray.cast_sphere(&sphere), &|p| sphere.distance(p)),
+                (ray.cast_aabb(&aabb), &|p| aabb.distance(p)),
+                (ray.cast_triangle(&tri), &|p| tri.distance(p)),
+            ];
+            for (hit, distance) in shapes {
+                let closest = ray_distance(&ray, distance);
+                // grazing rays are too close to call
+                if (1e-3..1e-2).contains(&closest) {
+                    continue;
+                }
+                match hit {
+                    Some(hit) => {
+                        assert!(closest < 1e-2, "hit a shape {closest} away");
+                        assert!(distance(hit.point) < 1e-3);
+                        assert_approx_eq!(hit.point, ray.at(hit.distance), epsilon = 1e-4);
+                        assert!((hit.normal.mag() - 1.).abs() < 1e-4);
+                    }
+                    None => assert!(closest >= 1e-3, "missed a shape the ray passes through"),
+                }
+            }
+            if let Some(hit) = ray.cast_triangle(&tri) {
+                let bary = hit.barycentric.unwrap();
+                assert_approx_eq!(tri.a * bary.x + tri.b * bary.y + tri.c * bary.z, hit.point, epsilon = 1e-3);
+                assert!(hit.normal.dot(ray.dir) <= 0.);
+            }
+            // the first hit on a box the ray starts outside of is where it enters
+            if let (Some(hit), false) = (ray.cast_aabb(&aabb), aabb.contains(ray.origin)) {
+                if hit.distance > 1e-2 {
+                    assert!(!aabb.contains(ray.at(hit.distance - 1e-2)));
+                }
+            }
+            let plane = Plane::new(rng.vec3(-1., 1.), rng.range(-3., 3.));
+            let start = plane.signed_distance(ray.origin);
+            let end = plane.signed_distance(ray.at(1e5));
+            assert_eq!(ray.cast_plane(&plane).is_some(), start.signum() != end.signum());
+        }
+    }
+
+    #[test]
+    fn randomized_overlaps() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..1000 {
+            let a = Aabb::new(int_vec(&mut rng, -4., 4.), int_vec(&mut rng, -4., 4.));
+            let b = Aabb::new(int_vec(&mut rng, -4., 4.), int_vec(&mut rng, -4., 4.));
+            // integer corners, so the boxes overlap exactly when they share an integer point
+            let mut shared = false;
+            for x in -4..=4 {
+                for y in -4..=4 {
+                    for z in -4..=4 {
+                        let p = vec3!(x as f32, y as f32, z as f32);
+                        shared |= a.contains(p) && b.contains(p);
+                    }
+                }
+            }
+            assert_eq!(a.intersects(&b), shared);
+
+            let sphere = Sphere::new(rng.vec3(-4., 4.), rng.range(0.1, 2.));
+            let gap = a.distance(sphere.center) - sphere.radius;
+            if gap.abs() > 1e-4 {
+                assert_eq!(sphere.intersects(&a), gap < 0.);
+            }
+            if let Some(contact) = sphere.contact_aabb(&a) {
+                let moved = Sphere::new(sphere.center - contact.normal * (contact.depth + 1e-3), sphere.radius);
+                assert!(!moved.intersects(&a));
+            }
+
+            let other = Sphere::new(rng.vec3(-4., 4.), rng.range(0.1, 2.));
+            if let Some(contact) = sphere.contact_sphere(&other) {
+                let moved = Sphere::new(other.center + contact.normal * (contact.depth + 1e-3), other.radius);
+                assert!(!sphere.intersects(&moved));
+            }
+
+            // a grid over the triangle, points within one grid step of each other are at most `step` apart
+            let tri = Triangle::new(rng.vec3(-4., 4.), rng.vec3(-4., 4.), rng.vec3(-4., 4.));
+            let n = 100;
+            let step = [tri.b - tri.a, tri.c - tri.b, tri.a - tri.c].map(|edge| edge.mag()).into_iter().fold(0., f32::max) / n as f32;
+            let mut closest = f32::INFINITY;
+            for i in 0..=n {
+                for j in 0..=n - i {
+                    let (v, w) = (i as f32 / n as f32, j as f32 / n as f32);
+                    closest = closest.min(a.distance(tri.a * (1. - v - w) + tri.b * v + tri.c * w));
+                }
+            }
+            if closest == 0. {
+                assert!(tri.intersects(&a));
+            } else if closest > step {
+                assert!(!tri.intersects(&a));
+            }
+        }
+    }
+}
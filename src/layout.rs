@@ -0,0 +1,203 @@
+//! Memory layout helpers for uploading to the GPU. Every type here is `repr(C)` and made only of
+//! `f32`/`i32`/`f64` fields, so its bytes can be copied straight into a buffer.
+//!
+//! `Matrix` stores its rows, GLSL and WGSL expect columns, so use `to_cols_array()` or the
+//! `to_std140()`/`to_std430()` helpers for matrices that go into uniform or storage buffers.
+use core::mem::size_of;
+
+use crate::matrix::{DMatrix, Matrix};
+use crate::quat::{DQuaternion, Quaternion};
+use crate::vec::{DVec2, DVec3, DVec4, IVec2, IVec3, Vec2, Vec3, Vec4};
+
+fn bytes_of<T: Copy>(value: &T) -> &[u8] {
+    // SAFETY: only used on the types below, they have no padding bytes that could be uninitialized
+    unsafe { core::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()) }
+}
+
+macro_rules! implLayout {
+    ($doc:literal: $($type:ty),+) => {
+        $(
+            impl $type {
+                #[doc = $doc]
+                pub fn as_bytes(&self) -> &[u8] {
+                    bytes_of(self)
+                }
+            }
+            #[cfg(feature = "bytemuck")]
+            unsafe impl bytemuck::Zeroable for $type {}
+            #[cfg(feature = "bytemuck")]
+            unsafe impl bytemuck::Pod for $type {}
+        )+
+    };
+}
+implLayout!("The value's memory, no copy is made": Vec2, Vec3, Vec4, IVec2, IVec3, DVec2, DVec3, DVec4);
+implLayout!(
+    "The value's memory, no copy is made. That's `[w, x, y, z]` like the fields, not the \
+    `[x, y, z, w]` shaders expect, which `Quaternion::to_std140()` gives":
+    Quaternion,
+    DQuaternion
+);
+
+/// A single vector has the same layout in std140 and std430, a 3 component one is padded to 16 bytes
+macro_rules! implStd {
+    ($($type:ty => [$scalar:ty; $len:literal] [$($field:ident),+]),+) => {
+        $(
+            impl $type {
+                pub fn to_std140(&self) -> [$scalar; $len] {
+                    let mut out = [0 as $scalar; $len];
+                    for (slot, value) in out.iter_mut().zip([$(self.$field),+]) {
+                        *slot = value;
+                    }
+                    out
+                }
+                pub fn to_std430(&self) -> [$scalar; $len] {
+                    self.to_std140()
+                }
+            }
+        )+
+    };
+}
+implStd!(
+    Vec2 => [f32; 2] [x, y],
+    Vec3 => [f32; 4] [x, y, z],
+    Vec4 => [f32; 4] [x, y, z, w],
+    IVec2 => [i32; 2] [x, y],
+    IVec3 => [i32; 4] [x, y, z]
+);
+
+impl Quaternion {
+    /// `[x, y, z, w]`, the order shaders and most GPU libraries use. `as_bytes()` and the
+    /// `bytemuck` casts keep the field order, `w` first.
+    pub fn to_std140(&self) -> [f32; 4] {
+        [self.v.x, self.v.y, self.v.z, self.w]
+    }
+    pub fn to_std430(&self) -> [f32; 4] {
+        self.to_std140()
+    }
+}
+
+impl<const ROW: usize, const COL: usize> Matrix<ROW, COL> {
+    /// The rows as they're stored, `to_cols_array()` gives the column major bytes shaders expect
+    pub fn as_bytes(&self) -> &[u8] {
+        bytes_of(self)
+    }
+    /// Column major copy, the same order `to_opengl` uses without allocating
+    pub fn to_cols_array(&self) -> [[f32; ROW]; COL] {
+        core::array::from_fn(|col| core::array::from_fn(|row| self.data[row][col]))
+    }
+    /// Inverse of `to_cols_array()`
+    pub fn from_cols_array(cols: [[f32; ROW]; COL]) -> Self {
+        Matrix { data: core::array::from_fn(|row| core::array::from_fn(|col| cols[col][row])) }
+    }
+    /// Columns padded to a vec4 each
+    fn vec4_cols(&self) -> [[f32; 4]; COL] {
+        assert!(ROW <= 4, "a matrix column has at most 4 components on the GPU");
+        core::array::from_fn(|col| core::array::from_fn(|row| if row < ROW { self.data[row][col] } else { 0. }))
+    }
+}
+#[cfg(feature = "bytemuck")]
+unsafe impl<const ROW: usize, const COL: usize> bytemuck::Zeroable for Matrix<ROW, COL> {}
+#[cfg(feature = "bytemuck")]
+unsafe impl<const ROW: usize, const COL: usize> bytemuck::Pod for Matrix<ROW, COL> {}
+
+impl<const ROW: usize, const COL: usize> DMatrix<ROW, COL> {
+    pub fn as_bytes(&self) -> &[u8] {
+        bytes_of(self)
+    }
+    pub fn to_cols_array(&self) -> [[f64; ROW]; COL] {
+        core::array::from_fn(|col| core::array::from_fn(|row| self.data[row][col]))
+    }
+    pub fn from_cols_array(cols: [[f64; ROW]; COL]) -> Self {
+        DMatrix { data: core::array::from_fn(|row| core::array::from_fn(|col| cols[col][row])) }
+    }
+}
+#[cfg(feature = "bytemuck")]
+unsafe impl<const ROW: usize, const COL: usize> bytemuck::Zeroable for DMatrix<ROW, COL> {}
+#[cfg(feature = "bytemuck")]
+unsafe impl<const ROW: usize, const COL: usize> bytemuck::Pod for DMatrix<ROW, COL> {}
+
+impl Matrix<2, 2> {
+    /// std140 rounds every column up to 16 bytes, a `mat2` takes two vec4's
+    pub fn to_std140(&self) -> [[f32; 4]; 2] {
+        self.vec4_cols()
+    }
+    /// std430 packs the two vec2 columns tightly
+    pub fn to_std430(&self) -> [[f32; 2]; 2] {
+        self.to_cols_array()
+    }
+}
+impl Matrix<3, 3> {
+    /// A `mat3` is three vec4 columns in both layouts
+    pub fn to_std140(&self) -> [[f32; 4]; 3] {
+        self.vec4_cols()
+    }
+    pub fn to_std430(&self) -> [[f32; 4]; 3] {
+        self.vec4_cols()
+    }
+}
+impl Matrix<4, 4> {
+    pub fn to_std140(&self) -> [[f32; 4]; 4] {
+        self.to_cols_array()
+    }
+    pub fn to_std430(&self) -> [[f32; 4]; 4] {
+        self.to_cols_array()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_mat, vec3};
+
+    #[test]
+    fn layout() {
+        assert_eq!(size_of::<Vec3>(), 12);
+        assert_eq!(size_of::<Quaternion>(), 16);
+        assert_eq!(size_of::<Matrix<3, 3>>(), 36);
+
+        let v = vec3!(1.0, 2.0, 3.0);
+        assert_eq!(v.as_bytes(), [1f32, 2., 3.].map(f32::to_ne_bytes).concat());
+        assert_eq!(v.to_std140(), [1., 2., 3., 0.]);
+        assert_eq!(IVec3::new(1, 2, 3).to_std430(), [1, 2, 3, 0]);
+        let q = Quaternion::new(1., vec3!(2., 3., 4.));
+        assert_eq!(q.to_std140(), [2., 3., 4., 1.]);
+        assert_eq!(q.as_bytes(), [1f32, 2., 3., 4.].map(f32::to_ne_bytes).concat());
+
+        let mat = new_mat!(3, 3, [1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+        assert_eq!(mat.to_cols_array(), [[1., 4., 7.], [2., 5., 8.], [3., 6., 9.]]);
+        #[cfg(feature = "alloc")]
+        assert_eq!(mat.to_cols_array().concat(), mat.to_opengl());
+        assert_eq!(mat.to_std140(), [[1., 4., 7., 0.], [2., 5., 8., 0.], [3., 6., 9., 0.]]);
+        assert_eq!(mat.as_bytes().len(), 36);
+
+        let mat = new_mat!(2, 2, [1., 2., 3., 4.]);
+        assert_eq!(mat.to_std140(), [[1., 3., 0., 0.], [2., 4., 0., 0.]]);
+        assert_eq!(mat.to_std430(), [[1., 3.], [2., 4.]]);
+
+        let mat = new_mat!(2, 3, [1., 2., 3., 4., 5., 6.]);
+        assert_eq!(mat.to_cols_array(), [[1., 4.], [2., 5.], [3., 6.]]);
+        assert_eq!(Matrix::from_cols_array(mat.to_cols_array()).data, mat.data);
+
+        assert_eq!(size_of::<DVec3>(), 24);
+        assert_eq!(size_of::<DQuaternion>(), 32);
+        let mat = DMatrix::from(mat);
+        assert_eq!(mat.to_cols_array(), [[1., 4.], [2., 5.], [3., 6.]]);
+        assert_eq!(mat.as_bytes().len(), 48);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn pod() {
+        let mat = Matrix::<4, 4>::identity();
+        let cols = mat.to_std140();
+        assert_eq!(bytemuck::cast_slice::<[f32; 4], f32>(&cols)[5], 1.);
+        assert_eq!(bytemuck::bytes_of(&mat), mat.as_bytes());
+        let v: Vec4 = bytemuck::cast([1f32, 2., 3., 4.]);
+        assert_eq!(v.w, 4.);
+        let zero: Vec3 = bytemuck::Zeroable::zeroed();
+        assert_eq!(zero, Vec3::ZERO);
+        // field order, not the shader order of to_std140()
+        let q = Quaternion::new(1., Vec3::new(2., 3., 4.));
+        assert_eq!(bytemuck::cast::<Quaternion, [f32; 4]>(q), [1., 2., 3., 4.]);
+    }
+}
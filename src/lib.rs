@@ -1,15 +1,78 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("my_math needs the `std` or the `libm` feature for its float functions");
+
+pub mod approx;
+pub mod curves;
+#[cfg(feature = "alloc")]
+pub mod delaunay;
+pub mod easing;
 pub mod fraction;
+pub mod geometry;
+pub mod geometry2d;
+pub mod gjk;
+pub mod intersection;
 pub mod matrix;
+pub mod noise;
+#[cfg(feature = "alloc")]
+pub mod pretty;
 pub mod quat;
+pub mod random;
 pub mod vec;
 
+mod layout;
+mod math;
+#[cfg(test)]
+mod test_rng;
+
+#[cfg(feature = "bigint")]
+pub mod big_fraction;
+#[cfg(feature = "bigint")]
+pub mod bigint;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(any(feature = "mint", feature = "glam", feature = "nalgebra", feature = "cgmath"))]
+mod interop;
+
 pub mod prelude {
+    pub use crate::approx::{ApproxEq, Tolerance};
+    pub use crate::curves;
+    pub use crate::curves::{CubicBezier, Curve, CurvePoint, Hermite, Nearest, QuadraticBezier};
+    #[cfg(feature = "alloc")]
+    pub use crate::curves::{ArcLength, BSpline, CatmullRom, Parameterization};
+    #[cfg(feature = "alloc")]
+    pub use crate::delaunay::Delaunay;
+    pub use crate::easing;
+    pub use crate::easing::{smooth_damp, Ease, Interpolate};
     pub use crate::fraction;
     pub use crate::fraction::*;
+    pub use crate::geometry;
+    pub use crate::geometry::{Aabb, Obb, Plane, Ray, Segment, Sphere, Triangle};
+    pub use crate::geometry2d;
+    pub use crate::geometry2d::{Point2, SegmentIntersection, Winding};
+    pub use crate::gjk::{Capsule, Support, Transformed};
+    pub use crate::intersection::{Contact, Intersects, RayHit};
     pub use crate::matrix;
     pub use crate::matrix::*;
+    pub use crate::noise;
+    pub use crate::noise::{fill_chunk, Fractal, Noise, NoisePoint, OpenSimplex2, Perlin, Warp, Worley};
+    #[cfg(feature = "alloc")]
+    pub use crate::pretty;
+    #[cfg(feature = "alloc")]
+    pub use crate::pretty::*;
     pub use crate::quat;
     pub use crate::quat::*;
+    pub use crate::random;
+    pub use crate::random::{Pcg32, Random};
     pub use crate::vec;
     pub use crate::vec::*;
+
+    #[cfg(feature = "bigint")]
+    pub use crate::big_fraction::*;
+    #[cfg(feature = "bigint")]
+    pub use crate::bigint::*;
 }
@@ -0,0 +1,55 @@
+//! Float functions that are part of `std`, taken from `libm` when building without it
+
+macro_rules! floatFn {
+    ($($name:ident($type:ty) => $method:ident),+) => {
+        $(
+            #[cfg(feature = "std")]
+            #[inline]
+            pub(crate) fn $name(x: $type) -> $type {
+                x.$method()
+            }
+            #[cfg(not(feature = "std"))]
+            #[inline]
+            pub(crate) fn $name(x: $type) -> $type {
+                libm::$name(x)
+            }
+        )+
+    };
+}
+floatFn!(
+    sqrtf(f32) => sqrt,
+    sinf(f32) => sin,
+    cosf(f32) => cos,
+    tanf(f32) => tan,
+    acosf(f32) => acos,
+    exp2f(f32) => exp2,
+    floorf(f32) => floor,
+    roundf(f32) => round,
+    sqrt(f64) => sqrt,
+    sin(f64) => sin,
+    cos(f64) => cos,
+    acos(f64) => acos,
+    round(f64) => round
+);
+
+#[cfg(all(feature = "alloc", feature = "std"))]
+#[inline]
+pub(crate) fn cbrtf(x: f32) -> f32 {
+    x.cbrt()
+}
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+#[inline]
+pub(crate) fn cbrtf(x: f32) -> f32 {
+    libm::cbrtf(x)
+}
+
+#[cfg(all(feature = "bigint", feature = "std"))]
+#[inline]
+pub(crate) fn powi(x: f64, n: i32) -> f64 {
+    x.powi(n)
+}
+#[cfg(all(feature = "bigint", not(feature = "std")))]
+#[inline]
+pub(crate) fn powi(x: f64, n: i32) -> f64 {
+    libm::pow(x, n as f64)
+}
@@ -1,7 +1,14 @@
 use core::f32;
-use std::fmt;
-use std::fmt::{Display, Formatter};
-use std::ops::{Deref, DerefMut, Mul};
+use core::fmt;
+use core::fmt::{Debug, Display, Formatter};
+use core::ops::{Deref, DerefMut, Mul};
+
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec, vec::Vec};
+
+use crate::math;
+#[cfg(feature = "alloc")]
+use crate::pretty::{align, Cell};
 
 #[macro_export]
 macro_rules! new_mat {
@@ -9,17 +16,23 @@ macro_rules! new_mat {
         Matrix::<$rows, $cols>::new_zero();
     };
     ($rows:expr, $cols:expr, $slice:expr) => {
-        Matrix::<$rows, $cols>::new(Vec::from($slice))
+        Matrix::<$rows, $cols>::from_slice(&$slice)
     };
 }
 
 #[derive(Clone, Copy)]
+#[repr(C)]
 pub struct Matrix<const ROW: usize, const COL: usize> {
     pub data: [[f32; COL]; ROW],
 }
 
 impl<const ROW: usize, const COL: usize> Matrix<ROW, COL> {
+    #[cfg(feature = "alloc")]
     pub fn new(data: Vec<f32>) -> Self {
+        Self::from_slice(&data)
+    }
+    /// Entries in row order, works without `alloc`
+    pub fn from_slice(data: &[f32]) -> Self {
         assert_eq!(
             ROW * COL,
             data.len(),
@@ -32,16 +45,20 @@ impl<const ROW: usize, const COL: usize> Matrix<ROW, COL> {
         }
         tmp
     }
+    pub const fn from_rows(data: [[f32; COL]; ROW]) -> Self {
+        Self { data }
+    }
     pub fn new_zero() -> Self {
         Self {
             data: [[0.0; COL]; ROW],
         }
     }
+    #[cfg(feature = "alloc")]
     pub fn to_opengl(self) -> Vec<f32> {
         let mut out = vec![];
         for col in 0..ROW {
             for row in 0..COL {
-                out.push(self.data[row][col] as f32);
+                out.push(self.data[row][col]);
             }
         }
         out
@@ -53,14 +70,14 @@ impl<const N: usize> Matrix<N, N> {
         for i in 0..N {
             mat[i][i] = 1.;
         }
-        return mat;
+        mat
     }
     pub fn scalar(factor: f32) -> Self {
         let mut mat = Self::new_zero();
         for i in 0..N {
             mat[i][i] = factor;
         }
-        return mat;
+        mat
     }
 }
 
@@ -73,38 +90,38 @@ pub fn look_at_lh(camera_pos: Vec3, at: Vec3, up: Vec3) -> Matrix<4,4> {
     let s = f.cross(up).norm();
     let u = s.cross(f);
 
-    Matrix::<4,4>::new(vec![
-         s.x, s.y, s.z, -camera_pos.dot(s),
-         u.x, u.y, u.z, -camera_pos.dot(u),
-         f.x, f.y, f.z, -camera_pos.dot(f),
-         0.,  0.,  0.,   1.,
+    Matrix::from_rows([
+        [s.x, s.y, s.z, -camera_pos.dot(s)],
+        [u.x, u.y, u.z, -camera_pos.dot(u)],
+        [f.x, f.y, f.z, -camera_pos.dot(f)],
+        [0.,  0.,  0.,   1.],
     ])
 }
 
 pub fn proj_mat_gl(fov: f32, ratio: f32, near: f32,far: f32) -> Matrix<4,4> {
-    use std::f32::consts::PI;
+    use core::f32::consts::PI;
     let fov_rad = fov / 180. * PI;
-    let tan_half_fov = f32::tan(fov_rad / 2.);
+    let tan_half_fov = math::tanf(fov_rad / 2.);
 
-    Matrix::<4,4>::new(vec![
-        1./(tan_half_fov*ratio),    0., 0., 0.,
-        0., 1./tan_half_fov,         0., 0.,
-        0., 0., (far+near)/(near-far),(2.*near*far)/(near-far),
-        0., 0.,  -1.   , 0.,
+    Matrix::from_rows([
+        [1./(tan_half_fov*ratio),    0., 0., 0.],
+        [0., 1./tan_half_fov,         0., 0.],
+        [0., 0., (far+near)/(near-far),(2.*near*far)/(near-far)],
+        [0., 0.,  -1.   , 0.],
     ])
 }
 pub fn proj_mat_wgpu(fov: f32, ratio: f32, near: f32,far: f32) -> Matrix<4,4> {
-    use std::f32::consts::PI;
+    use core::f32::consts::PI;
     let fov_rad = fov / 180. * PI;
-    let tan_half_fov = f32::tan(fov_rad / 2.);
+    let tan_half_fov = math::tanf(fov_rad / 2.);
 
     let h_c = (far+near)/((near-far)*2.);
     let h_d = (near*far)/(near-far);
-    Matrix::<4,4>::new(vec![
-        1./(tan_half_fov*ratio),    0.,         0.,     0.,
-                0.,          1./tan_half_fov,   0.,     0.,
-                0.,                 0.,         h_c,    h_d,
-                0.,                 0.,         h_c-1., h_d,
+    Matrix::from_rows([
+        [1./(tan_half_fov*ratio),    0.,         0.,     0.],
+        [        0.,          1./tan_half_fov,   0.,     0.],
+        [        0.,                 0.,         h_c,    h_d],
+        [        0.,                 0.,         h_c-1., h_d],
     ])
 }
 
@@ -157,81 +174,201 @@ impl<const ROW: usize, const COL: usize, const ROW_RHS: usize, const COL_RHS: us
     }
 }
 
+#[cfg(feature = "alloc")]
+/// Writes the rows as `[ a, b ]` lines with every column aligned,
+/// the formatter's width is the minimum width of a column
+fn write_aligned(f: &mut Formatter<'_>, cells: &[Vec<Cell>]) -> fmt::Result {
+    for row in align(cells, f.width().unwrap_or(0)) {
+        writeln!(f, "[ {} ]", row.join(", "))?;
+    }
+    Ok(())
+}
+
+/// Numbers are rounded to the formatter's precision (3 by default) and aligned on the decimal point
+/// ```text
+/// [0.4,4]             [   0.4  ,  4    ]
+/// [123,12.44]      => [ 123    , 12.44 ]
+/// [1.11111111,2]      [   1.111,  3    ]
+/// ```
+#[cfg(feature = "alloc")]
 impl<const ROW: usize, const COL: usize> Display for Matrix<ROW, COL> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        // 0 = whole number len | 1 = fraction len
-        let mut longest_number_size_in_column = [[0; 2]; COL];
-        for col in 0..COL {
-            for row in 0..ROW {
-                longest_number_size_in_column[col][0] = std::cmp::max(
-                    longest_number_size_in_column[col][0],
-                    format!("{}", self[row][col].floor()).len(),
-                );
-                if self[row][col].fract() == 0. {
-                } else {
-                    longest_number_size_in_column[col][1] = std::cmp::max(
-                        longest_number_size_in_column[col][1],
-                        format!("{:.3}", self[row][col].fract())
-                            .trim_end_matches('0')
-                            .trim_end_matches('.')
-                            .len()
-                            - 1,
-                    );
-                }
+        let precision = f.precision().unwrap_or(3);
+        let cells: Vec<Vec<Cell>> = self
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|x| Cell::decimal(format!("{:.precision$}", x)))
+                    .collect()
+            })
+            .collect();
+        write_aligned(f, &cells)
+    }
+}
+
+// ------------------- Double precision -----------------------------
+
+#[macro_export]
+macro_rules! new_dmat {
+    ($rows:expr,$cols:expr) => {
+        DMatrix::<$rows, $cols>::new_zero();
+    };
+    ($rows:expr, $cols:expr, $slice:expr) => {
+        DMatrix::<$rows, $cols>::from_slice(&$slice)
+    };
+}
+
+/// `Matrix` over `f64`, `From<Matrix>` widens losslessly and `as_matrix()` narrows back
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct DMatrix<const ROW: usize, const COL: usize> {
+    pub data: [[f64; COL]; ROW],
+}
+
+impl<const ROW: usize, const COL: usize> DMatrix<ROW, COL> {
+    #[cfg(feature = "alloc")]
+    pub fn new(data: Vec<f64>) -> Self {
+        Self::from_slice(&data)
+    }
+    /// Entries in row order, works without `alloc`
+    pub fn from_slice(data: &[f64]) -> Self {
+        assert_eq!(
+            ROW * COL,
+            data.len(),
+            "Vector len doest match the matrix dimentions | vec len: {}, matrix dimentions: {}x{}", data.len() , ROW,COL
+        );
+
+        let mut tmp = DMatrix::<ROW, COL>::new_zero();
+        for (i, chunk) in data.chunks(COL).enumerate() {
+            tmp[i].copy_from_slice(chunk);
+        }
+        tmp
+    }
+    pub const fn from_rows(data: [[f64; COL]; ROW]) -> Self {
+        Self { data }
+    }
+    pub fn new_zero() -> Self {
+        Self {
+            data: [[0.0; COL]; ROW],
+        }
+    }
+    #[cfg(feature = "alloc")]
+    pub fn to_opengl(self) -> Vec<f64> {
+        let mut out = vec![];
+        for col in 0..ROW {
+            for row in 0..COL {
+                out.push(self.data[row][col]);
             }
         }
-        // Aligning numbers in the matrix and rouding go 3 digits after decimal point
-        // [0.4,4]             [   0.4  ,  4    ]
-        // [123,12.44]      => [ 123    , 12.44 ]
-        // [1.11111111,2]      [   1.111,  3    ]
+        out
+    }
+    /// Rounds every entry to the nearest `f32`
+    pub fn as_matrix(&self) -> Matrix<ROW, COL> {
+        Matrix { data: self.data.map(|row| row.map(|x| x as f32)) }
+    }
+}
+impl<const N: usize> DMatrix<N, N> {
+    pub fn identity() -> Self {
+        let mut mat = Self::new_zero();
+        for i in 0..N {
+            mat[i][i] = 1.;
+        }
+        mat
+    }
+    pub fn scalar(factor: f64) -> Self {
+        let mut mat = Self::new_zero();
+        for i in 0..N {
+            mat[i][i] = factor;
+        }
+        mat
+    }
+}
 
-        for row in 0..ROW {
-            write!(f, "[ ")?;
-            for col in 0..COL {
-                let whole_part_len = format!("{}", self[row][col].floor()).len();
-                let fract_len: usize;
-                if self[row][col].fract() == 0. {
-                    fract_len = 0;
-                } else {
-                    fract_len = format!("{:.3}", self[row][col].fract())
-                        .trim_end_matches('0')
-                        .trim_end_matches('.')
-                        .len()
-                        - 1;
-                }
+impl<const ROW: usize, const COL: usize> From<Matrix<ROW, COL>> for DMatrix<ROW, COL> {
+    fn from(mat: Matrix<ROW, COL>) -> Self {
+        DMatrix { data: mat.data.map(|row| row.map(f64::from)) }
+    }
+}
+impl<const ROW: usize, const COL: usize> Deref for DMatrix<ROW, COL> {
+    type Target = [[f64; COL]; ROW];
 
-                let diff_front = longest_number_size_in_column[col][0] - whole_part_len;
-                for _ in 0..diff_front {
-                    write!(f, " ")?;
-                }
-                let no_trailing_zeros = format!("{:.3}", self[row][col])
-                    .trim_end_matches('0')
-                    .trim_end_matches('.')
-                    .to_owned();
-                write!(f, "{}", no_trailing_zeros)?;
-                let diff_back = longest_number_size_in_column[col][1] - fract_len;
-                for _ in 0..diff_back {
-                    write!(f, " ")?;
-                }
-                if col != COL - 1 {
-                    write!(f, ", ")?;
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+impl<const ROW: usize, const COL: usize> DerefMut for DMatrix<ROW, COL> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.data
+    }
+}
+impl<const ROW: usize, const COL: usize> Mul<f64> for DMatrix<ROW, COL> {
+    type Output = DMatrix<ROW,COL>;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        let mut out = DMatrix::new_zero();
+        for r in 0..ROW {
+            for c in 0..COL {
+                out[r][c] = self[r][c] * rhs;
+            }
+        }
+        out
+    }
+}
+
+impl<const ROW: usize, const COL: usize, const ROW_RHS: usize, const COL_RHS: usize>
+    Mul<DMatrix<ROW_RHS, COL_RHS>> for DMatrix<ROW, COL>
+{
+    type Output = DMatrix<ROW, COL_RHS>;
+
+    fn mul(self, rhs: DMatrix<ROW_RHS, COL_RHS>) -> Self::Output {
+        let mut mat: Self::Output = DMatrix::<ROW, COL_RHS>::new_zero();
+
+        for row in 0..ROW {
+            for col in 0..COL_RHS {
+                let mut sum: f64 = 0.0;
+                for i in 0..COL {
+                    sum += self[row][i] * rhs[i][col];
                 }
+                mat[row][col] = sum;
             }
-            write!(f, " ]\n")?;
         }
-        Ok(())
+        mat
+    }
+}
+
+/// Same layout as `Matrix`'s Display, the precision still defaults to 3
+#[cfg(feature = "alloc")]
+impl<const ROW: usize, const COL: usize> Display for DMatrix<ROW, COL> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(3);
+        let cells: Vec<Vec<Cell>> = self
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|x| Cell::decimal(format!("{:.precision$}", x)))
+                    .collect()
+            })
+            .collect();
+        write_aligned(f, &cells)
     }
 }
 
 use crate::fraction::Fraction;
 use crate::fr;
+#[cfg(feature = "alloc")]
+use crate::pretty::{latex_coefficient, latex_matrix};
 
 #[derive(Clone, Copy)]
 pub struct MatrixFr<const ROW: usize, const COL: usize> {
     pub data: [[Fraction; COL]; ROW],
 }
 impl<const ROW: usize, const COL: usize> MatrixFr<ROW, COL> {
+    #[cfg(feature = "alloc")]
     pub fn new(data: Vec<Fraction>) -> Self {
+        Self::from_slice(&data)
+    }
+    /// Entries in row order, works without `alloc`
+    pub fn from_slice(data: &[Fraction]) -> Self {
         assert_eq!(
             ROW * COL,
             data.len(),
@@ -244,49 +381,384 @@ impl<const ROW: usize, const COL: usize> MatrixFr<ROW, COL> {
         }
         tmp
     }
+    pub const fn from_rows(data: [[Fraction; COL]; ROW]) -> Self {
+        Self { data }
+    }
     fn new_zero() -> Self {
         Self {
             data: [[fr!(0); COL]; ROW],
         }
     }
-    pub fn solve(&mut self) -> [Fraction;ROW] {
-        assert_eq!(ROW + 1,COL,"You can only solve a Nx(N+1) matrix");
-        for i in 0..ROW{
-            // Find the pivot element
-            let mut max_row = i;
-            for k in i + 1..ROW{
-                if self[k][i].abs() > self[max_row][i].abs() {
-                    max_row = k;
-                }
+    /// Index of the row at or below `start` with the largest absolute value in `col`,
+    /// `None` if they are all zero
+    fn pivot_row(&self, start: usize, col: usize) -> Option<usize> {
+        let mut max_row = start;
+        for k in start + 1..ROW {
+            if self[k][col].abs() > self[max_row][col].abs() {
+                max_row = k;
+            }
+        }
+        if self[max_row][col] == 0 {
+            None
+        } else {
+            Some(max_row)
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl<const ROW: usize, const COL: usize> MatrixFr<ROW, COL> {
+    /// Gauss-Jordan elimination, `record` gets every row operation and the matrix after it
+    fn eliminate(&self, mut record: impl FnMut(RowOp, &Self)) -> (Self, Vec<usize>) {
+        let mut mat = *self;
+        let mut pivots = vec![];
+        let mut row = 0;
+        for col in 0..COL {
+            if row == ROW {
+                break;
+            }
+            let Some(max_row) = mat.pivot_row(row, col) else {
+                continue;
+            };
+            if max_row != row {
+                mat.swap(row, max_row);
+                record(RowOp::Swap(row, max_row), &mat);
             }
 
-            // Swap rows if necessary
-            self.swap(i, max_row);
+            let pivot = mat[row][col];
+            if pivot != 1 {
+                for k in col..COL {
+                    mat[row][k] /= pivot;
+                }
+                record(RowOp::Scale { row, factor: pivot.recip() }, &mat);
+            }
+            // Eliminate above and below the pivot
+            for r in 0..ROW {
+                let factor = mat[r][col];
+                if r == row || factor == 0 {
+                    continue;
+                }
+                for k in col..COL {
+                    let sub = factor * mat[row][k];
+                    mat[r][k] -= sub;
+                }
+                record(RowOp::AddMultiple { target: r, source: row, factor: -factor }, &mat);
+            }
+            pivots.push(col);
+            row += 1;
+        }
+        (mat, pivots)
+    }
+    /// Reduced row echelon form and the columns that hold its pivots
+    pub fn rref_with_pivots(&self) -> (Self, Vec<usize>) {
+        self.eliminate(|_, _| {})
+    }
+    /// Same as [`MatrixFr::rref`] but records every row operation on the way
+    pub fn rref_traced(&self) -> Trace<ROW, COL> {
+        let mut steps = vec![];
+        self.eliminate(|op, mat| steps.push(Step { op, matrix: *mat }));
+        Trace {
+            start: *self,
+            steps,
+            augmented: false,
+        }
+    }
+    pub fn rref(&self) -> Self {
+        self.rref_with_pivots().0
+    }
+    pub fn rank(&self) -> usize {
+        self.rref_with_pivots().1.len()
+    }
+    /// Basis of the null space, the vectors `x` with `self * x = 0`
+    pub fn null_space(&self) -> Vec<[Fraction; COL]> {
+        let (rref, pivots) = self.rref_with_pivots();
+        null_basis(&rref, &pivots, COL)
+            .into_iter()
+            .map(|v| v.try_into().unwrap())
+            .collect()
+    }
+    /// Basis of the column space, the pivot columns of the matrix
+    pub fn column_space(&self) -> Vec<[Fraction; ROW]> {
+        let (_, pivots) = self.rref_with_pivots();
+        pivots
+            .iter()
+            .map(|&col| core::array::from_fn(|row| self[row][col]))
+            .collect()
+    }
+    /// Solves the system this augmented matrix describes, the last column holds the right hand side
+    pub fn solve(&self) -> Solution {
+        let (rref, pivots) = self.rref_with_pivots();
+        Self::solution(&rref, &pivots)
+    }
+    /// Same as [`MatrixFr::solve`] but records every row operation on the way
+    pub fn solve_traced(&self) -> (Solution, Trace<ROW, COL>) {
+        let mut steps = vec![];
+        let (rref, pivots) = self.eliminate(|op, mat| steps.push(Step { op, matrix: *mat }));
+        let trace = Trace {
+            start: *self,
+            steps,
+            augmented: true,
+        };
+        (Self::solution(&rref, &pivots), trace)
+    }
+    fn solution(rref: &Self, pivots: &[usize]) -> Solution {
+        assert!(COL > 0, "an augmented matrix needs at least the right hand side column");
+        let vars = COL - 1;
+        if pivots.last() == Some(&vars) {
+            // A row reading 0 = 1
+            return Solution::None;
+        }
 
-            // Check if the pivot is zero (if so, the matrix is singular)
-            if self[i][i] == 0 {
-                panic!("singular matrix"); // No unique solution
+        let mut particular = vec![fr!(0); vars];
+        for (row, &col) in pivots.iter().enumerate() {
+            particular[col] = rref[row][vars];
+        }
+        if pivots.len() == vars {
+            return Solution::Unique(particular);
+        }
+        Solution::Infinite {
+            particular,
+            free: (0..vars).filter(|col| !pivots.contains(col)).collect(),
+            basis: null_basis(rref, pivots, vars),
+        }
+    }
+}
+impl<const N: usize> MatrixFr<N, N> {
+    pub fn identity() -> Self {
+        let mut mat = Self::new_zero();
+        for i in 0..N {
+            mat[i][i] = fr!(1);
+        }
+        mat
+    }
+    pub fn determinant(&self) -> Fraction {
+        let mut mat = *self;
+        let mut det = fr!(1);
+        for i in 0..N {
+            let Some(max_row) = mat.pivot_row(i, i) else {
+                return fr!(0);
+            };
+            if max_row != i {
+                mat.swap(i, max_row);
+                det = -det;
             }
+            det *= mat[i][i];
+            for j in i + 1..N {
+                let factor = mat[j][i] / mat[i][i];
+                for k in i..N {
+                    let sub = factor * mat[i][k];
+                    mat[j][k] -= sub;
+                }
+            }
+        }
+        det
+    }
+    /// `None` if the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        // Gauss-Jordan on the matrix while applying the same row operations to the identity
+        let mut mat = *self;
+        let mut inv = Self::identity();
+        for i in 0..N {
+            let max_row = mat.pivot_row(i, i)?;
+            mat.swap(i, max_row);
+            inv.swap(i, max_row);
 
-            // Eliminate below the pivot
-            for j in i + 1..ROW {
-                let factor = self[j][i] / self[i][i];
-                for k in i..ROW + 1 {
-                    self[j][k] = self[j][k] - factor * self[i][k];
+            let pivot = mat[i][i];
+            for k in 0..N {
+                mat[i][k] /= pivot;
+                inv[i][k] /= pivot;
+            }
+            for r in 0..N {
+                let factor = mat[r][i];
+                if r == i || factor == 0 {
+                    continue;
+                }
+                for k in 0..N {
+                    let sub = factor * mat[i][k];
+                    mat[r][k] -= sub;
+                    let sub = factor * inv[i][k];
+                    inv[r][k] -= sub;
                 }
             }
         }
+        Some(inv)
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Null space basis of the first `cols` columns of a matrix in reduced row echelon form,
+/// one vector per free column
+fn null_basis<const ROW: usize, const COL: usize>(
+    rref: &MatrixFr<ROW, COL>,
+    pivots: &[usize],
+    cols: usize,
+) -> Vec<Vec<Fraction>> {
+    (0..cols)
+        .filter(|col| !pivots.contains(col))
+        .map(|free| {
+            let mut v = vec![fr!(0); cols];
+            v[free] = fr!(1);
+            for (row, &col) in pivots.iter().enumerate() {
+                if col < cols {
+                    v[col] = -rref[row][free];
+                }
+            }
+            v
+        })
+        .collect()
+}
+
+#[cfg(feature = "alloc")]
+/// Result of [`MatrixFr::solve`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solution {
+    Unique(Vec<Fraction>),
+    /// Every solution is `particular + t_1 * basis[0] + t_2 * basis[1] + ...`,
+    /// where `t_i` is the value of the free variable `free[i]`
+    Infinite {
+        particular: Vec<Fraction>,
+        free: Vec<usize>,
+        basis: Vec<Vec<Fraction>>,
+    },
+    None,
+}
 
-        // Back substitution
-        let mut solution = [fr!(0); ROW];
+/// Elementary row operation, rows are indexed from 0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowOp {
+    Swap(usize, usize),
+    /// `row *= factor`
+    Scale { row: usize, factor: Fraction },
+    /// `target += factor * source`
+    AddMultiple {
+        target: usize,
+        source: usize,
+        factor: Fraction,
+    },
+}
+
+/// A row operation and the matrix right after it
+#[derive(Clone, Copy)]
+pub struct Step<const ROW: usize, const COL: usize> {
+    pub op: RowOp,
+    pub matrix: MatrixFr<ROW, COL>,
+}
+
+#[cfg(feature = "alloc")]
+/// Every step of an elimination, `Display` renders it as plain text and
+/// [`Trace::to_latex`] as a LaTeX `align*` block
+#[derive(Clone)]
+pub struct Trace<const ROW: usize, const COL: usize> {
+    pub start: MatrixFr<ROW, COL>,
+    pub steps: Vec<Step<ROW, COL>>,
+    /// The last column is a right hand side, drawn behind a bar in LaTeX
+    pub augmented: bool,
+}
+#[cfg(feature = "alloc")]
+impl<const ROW: usize, const COL: usize> Trace<ROW, COL> {
+    /// Matrix after the last step
+    pub fn result(&self) -> &MatrixFr<ROW, COL> {
+        self.steps.last().map_or(&self.start, |step| &step.matrix)
+    }
+    pub fn to_latex(&self) -> String {
+        let mut out = String::from("\\begin{align*}\n");
+        out += &format!("&{} \\\\\n", latex_matrix(&self.start, self.augmented));
+        for step in &self.steps {
+            out += &format!(
+                "\\xrightarrow{{{}}} &{} \\\\\n",
+                step.op.to_latex(),
+                latex_matrix(&step.matrix, self.augmented)
+            );
+        }
+        out += "\\end{align*}";
+        out
+    }
+}
+#[cfg(feature = "alloc")]
+impl<const ROW: usize, const COL: usize> Display for Trace<ROW, COL> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Start:\n{}", self.start)?;
+        for (i, step) in self.steps.iter().enumerate() {
+            write!(f, "\nStep {}: {}\n{}", i + 1, step.op, step.matrix)?;
+        }
+        Ok(())
+    }
+}
 
-        for i in (0..ROW).rev() {
-            solution[i] = self[i][ROW] / self[i][i];
-            for j in 0..i {
-                self[j][ROW] = self[j][ROW] - self[j][i] * solution[i];
+#[cfg(feature = "alloc")]
+impl RowOp {
+    pub fn to_latex(&self) -> String {
+        match *self {
+            RowOp::Swap(a, b) => format!("R_{{{}}} \\leftrightarrow R_{{{}}}", a + 1, b + 1),
+            RowOp::Scale { row, factor } => {
+                format!("R_{{{0}}} \\leftarrow {1}R_{{{0}}}", row + 1, latex_coefficient(factor))
+            }
+            RowOp::AddMultiple { target, source, factor } => {
+                let sign = if factor < 0 { "-" } else { "+" };
+                format!(
+                    "R_{{{0}}} \\leftarrow R_{{{0}}} {sign} {1}R_{{{2}}}",
+                    target + 1,
+                    latex_coefficient(factor.abs()),
+                    source + 1
+                )
+            }
+        }
+    }
+}
+/// `n/d` without the parentheses of `Fraction`'s own `Display`, the way matrix entries are written
+struct Plain(Fraction);
+impl Display for Plain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0.denom() {
+            1 => write!(f, "{}", self.0.numer()),
+            d => write!(f, "{}/{}", self.0.numer(), d),
+        }
+    }
+}
+impl Debug for Plain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+/// Rows are numbered from 1 like in textbooks, `R2 <- R2 - 3 R1`
+impl Display for RowOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            RowOp::Swap(a, b) => write!(f, "R{} <-> R{}", a + 1, b + 1),
+            RowOp::Scale { row, factor } => write!(f, "R{0} <- {1} R{0}", row + 1, Plain(factor)),
+            RowOp::AddMultiple { target, source, factor } => {
+                let sign = if factor < 0 { "-" } else { "+" };
+                if factor.abs() == 1 {
+                    write!(f, "R{0} <- R{0} {sign} R{1}", target + 1, source + 1)
+                } else {
+                    write!(f, "R{0} <- R{0} {sign} {1} R{2}", target + 1, Plain(factor.abs()), source + 1)
+                }
             }
         }
-        solution
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Entries are aligned on the fraction bar, `{:#}` prints mixed numbers instead and
+/// a precision (`{:.3}`) prints rounded decimals aligned on the decimal point
+impl<const ROW: usize, const COL: usize> Display for MatrixFr<ROW, COL> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let cell = |x: Fraction| match f.precision() {
+            Some(precision) => Cell::rounded(x, precision),
+            None => Cell::fraction(x, f.alternate()),
+        };
+        let cells: Vec<Vec<Cell>> = self
+            .iter()
+            .map(|row| row.iter().map(|x| cell(*x)).collect())
+            .collect();
+        write_aligned(f, &cells)
+    }
+}
+/// Rows of entries written as `n/d`, `[[1, 1/2], [0, -3]]`
+impl<const ROW: usize, const COL: usize> Debug for MatrixFr<ROW, COL> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().map(|row| row.map(Plain)))
+            .finish()
     }
 }
 impl<const ROW: usize, const COL: usize> Deref for MatrixFr<ROW, COL> {
@@ -336,11 +808,13 @@ impl<const ROW: usize, const COL: usize, const ROW_RHS: usize, const COL_RHS: us
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use super::*;
     use crate::vec::*;
+    use alloc::string::ToString;
 
+    #[cfg(feature = "std")]
     macro_rules! print_ident {
         ($($size:expr),*) => { $(
             println!("{}x{} identity:\n{}",$size,$size,Matrix::<$size,$size>::identity());
@@ -349,6 +823,8 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "std")]
+    #[allow(clippy::excessive_precision)]
     fn it_works() {
         //let mat_1 = Matrix::<2, 3>::new(Some(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]));
         let mat_1 = new_mat!(2, 3, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
@@ -366,4 +842,187 @@ mod tests {
         let vec4 = Vec4::new(1.,2.,3.,1.);
         println!("vec4 * ident = {:?}", Matrix::<4, 4>::scalar(2.) * vec4);
     }
+
+    #[test]
+    fn constructors() {
+        const ROT: Matrix<2, 2> = Matrix::from_rows([[0., -1.], [1., 0.]]);
+        assert_eq!(ROT.data, Matrix::<2, 2>::new(vec![0., -1., 1., 0.]).data);
+        assert_eq!(ROT.data, Matrix::<2, 2>::from_slice(&[0., -1., 1., 0.]).data);
+
+        let mat = MatrixFr::from_rows([[fr!(1, 2), fr!(0)], [fr!(3), fr!(-1)]]);
+        assert_eq!(mat.data, MatrixFr::<2, 2>::from_slice(&[fr!(1, 2), fr!(0), fr!(3), fr!(-1)]).data);
+
+        let proj = proj_mat_gl(90., 1., 1., 3.);
+        assert!((proj[0][0] - 1.).abs() < 1e-6);
+        assert_eq!(proj[3][2], -1.);
+    }
+
+    #[test]
+    fn solve() {
+        // Zero in the first pivot position, needs a row swap
+        let mat = MatrixFr::<2, 3>::new(vec![fr!(0), fr!(1), fr!(2), fr!(1), fr!(1), fr!(3)]);
+        assert_eq!(mat.solve(), Solution::Unique(vec![fr!(1), fr!(2)]));
+
+        let mat = MatrixFr::<3, 4>::new(vec![
+            fr!(2), fr!(1), fr!(-1), fr!(8),
+            fr!(-3), fr!(-1), fr!(2), fr!(-11),
+            fr!(-2), fr!(1), fr!(2), fr!(-3),
+        ]);
+        assert_eq!(mat.solve(), Solution::Unique(vec![fr!(2), fr!(3), fr!(-1)]));
+
+        // x + 2y - z = 3 twice over, two free variables
+        let mat = MatrixFr::<2, 4>::new(vec![
+            fr!(1), fr!(2), fr!(-1), fr!(3),
+            fr!(2), fr!(4), fr!(-2), fr!(6),
+        ]);
+        assert_eq!(
+            mat.solve(),
+            Solution::Infinite {
+                particular: vec![fr!(3), fr!(0), fr!(0)],
+                free: vec![1, 2],
+                basis: vec![vec![fr!(-2), fr!(1), fr!(0)], vec![fr!(1), fr!(0), fr!(1)]],
+            }
+        );
+
+        let mat = MatrixFr::<2, 3>::new(vec![fr!(1), fr!(1), fr!(1), fr!(2), fr!(2), fr!(3)]);
+        assert_eq!(mat.solve(), Solution::None);
+    }
+
+    #[test]
+    fn linear_algebra() {
+        let mat = MatrixFr::<3, 3>::new(vec![
+            fr!(2), fr!(-1), fr!(0),
+            fr!(-1), fr!(2), fr!(-1),
+            fr!(0), fr!(-1), fr!(2),
+        ]);
+        assert_eq!(mat.determinant(), fr!(4));
+        assert_eq!(mat.rank(), 3);
+        let inv = mat.inverse().unwrap();
+        assert_eq!(inv.data[0], [fr!(3, 4), fr!(1, 2), fr!(1, 4)]);
+        assert_eq!((mat * inv).data, MatrixFr::<3, 3>::identity().data);
+        assert!(mat.null_space().is_empty());
+
+        let singular = MatrixFr::<3, 3>::new(vec![
+            fr!(1), fr!(2), fr!(3),
+            fr!(4), fr!(5), fr!(6),
+            fr!(7), fr!(8), fr!(9),
+        ]);
+        assert_eq!(singular.determinant(), fr!(0));
+        assert!(singular.inverse().is_none());
+        assert_eq!(singular.rank(), 2);
+        assert_eq!(
+            singular.rref().data,
+            [
+                [fr!(1), fr!(0), fr!(-1)],
+                [fr!(0), fr!(1), fr!(2)],
+                [fr!(0), fr!(0), fr!(0)],
+            ]
+        );
+        assert_eq!(singular.null_space(), vec![[fr!(1), fr!(-2), fr!(1)]]);
+        assert_eq!(
+            singular.column_space(),
+            vec![[fr!(1), fr!(4), fr!(7)], [fr!(2), fr!(5), fr!(8)]]
+        );
+
+        // Row swap flips the sign
+        let swapped = MatrixFr::<2, 2>::new(vec![fr!(0), fr!(1), fr!(1), fr!(0)]);
+        assert_eq!(swapped.determinant(), fr!(-1));
+    }
+
+    #[test]
+    fn traced() {
+        let mat = MatrixFr::<2, 3>::new(vec![fr!(1), fr!(2), fr!(5), fr!(3), fr!(4), fr!(6)]);
+        let (solution, trace) = mat.solve_traced();
+        assert_eq!(solution, mat.solve());
+        assert_eq!(solution, Solution::Unique(vec![fr!(-4), fr!(9, 2)]));
+        assert_eq!(
+            trace.steps.iter().map(|step| step.op).collect::<Vec<_>>(),
+            vec![
+                RowOp::Swap(0, 1),
+                RowOp::Scale { row: 0, factor: fr!(1, 3) },
+                RowOp::AddMultiple { target: 1, source: 0, factor: fr!(-1) },
+                RowOp::Scale { row: 1, factor: fr!(3, 2) },
+                RowOp::AddMultiple { target: 0, source: 1, factor: fr!(-4, 3) },
+            ]
+        );
+        assert_eq!(trace.result().data, mat.rref().data);
+        assert_eq!(mat.rref_traced().result().data, mat.rref().data);
+
+        let text = trace.to_string();
+        assert!(text.starts_with("Start:\n[ 1, 2, 5 ]\n[ 3, 4, 6 ]\n"));
+        assert!(text.contains("Step 2: R1 <- 1/3 R1\n[ 1, 4/3, 2 ]\n[ 1, 2  , 5 ]\n"));
+        assert!(text.contains("Step 3: R2 <- R2 - R1\n"));
+        assert!(text.contains("Step 5: R1 <- R1 - 4/3 R2\n[ 1, 0, -4   ]\n[ 0, 1,  9/2 ]\n"));
+
+        let latex = trace.to_latex();
+        assert!(latex.starts_with("\\begin{align*}\n&\\left[\\begin{array}{cc|c} 1 & 2 & 5 \\\\ 3 & 4 & 6 \\end{array}\\right]"));
+        assert!(latex.contains("\\xrightarrow{R_{1} \\leftrightarrow R_{2}}"));
+        assert!(latex.contains("\\xrightarrow{R_{1} \\leftarrow \\frac{1}{3}R_{1}}"));
+        assert!(latex.contains("\\xrightarrow{R_{2} \\leftarrow R_{2} - R_{1}}"));
+        assert!(latex.contains("1 & 0 & -4 \\\\ 0 & 1 & \\frac{9}{2}"));
+        assert!(latex.ends_with("\\end{align*}"));
+    }
+
+    #[test]
+    fn formatting() {
+        let mat = MatrixFr::<2, 3>::new(vec![
+            fr!(1), fr!(-7, 3), fr!(1, 2),
+            fr!(12), fr!(5, 4), fr!(0),
+        ]);
+        assert_eq!(format!("{mat}"), "[  1, -7/3, 1/2 ]\n[ 12,  5/4, 0   ]\n");
+        assert_eq!(format!("{mat:#}"), "[  1, -2 1/3, 1/2 ]\n[ 12,  1 1/4, 0   ]\n");
+        assert_eq!(format!("{mat:.2}"), "[  1, -2.33, 0.5 ]\n[ 12,  1.25, 0   ]\n");
+        assert_eq!(format!("{mat:6}"), "[      1,   -7/3,    1/2 ]\n[     12,    5/4,    0   ]\n");
+        assert_eq!(format!("{mat:?}"), "[[1, -7/3, 1/2], [12, 5/4, 0]]");
+
+        // rounding works on the digits, so large precisions and entries can't overflow
+        let mat = MatrixFr::<1, 2>::new(vec![fr!(1, 3), fr!(-2, 3)]);
+        let (third, two_thirds) = ("3".repeat(40), "6".repeat(39));
+        assert_eq!(format!("{mat:.40}"), format!("[ 0.{third}, -0.{two_thirds}7 ]\n"));
+        let mat = MatrixFr::<2, 1>::new(vec![fr!(i128::MAX, 3), fr!(i128::MIN, 7)]);
+        assert_eq!(
+            format!("{mat:.2}"),
+            "[  56713727820156410577229101238628035242.33 ]\n[ -24305883351495604533098186245126300818.29 ]\n"
+        );
+        assert_eq!(format!("{:.2}", MatrixFr::<1, 1>::new(vec![fr!(i128::MAX)])), format!("[ {} ]\n", i128::MAX));
+        let mat = MatrixFr::<1, 4>::new(vec![fr!(199, 200), fr!(-1, 200), fr!(999, 100), fr!(-1, 300)]);
+        assert_eq!(format!("{mat:.2}"), "[ 1, -0.01, 9.99, 0 ]\n");
+        assert_eq!(format!("{mat:.1}"), "[ 1, 0, 10, 0 ]\n");
+
+        let mat = new_mat!(2, 2, [0.4, -1.0 / 3.0, 123.0, 12.44]);
+        assert_eq!(format!("{mat}"), "[   0.4, -0.333 ]\n[ 123  , 12.44  ]\n");
+        assert_eq!(format!("{mat:.1}"), "[   0.4, -0.3 ]\n[ 123  , 12.4 ]\n");
+        assert_eq!(format!("{mat:.0}"), "[   0,  0 ]\n[ 123, 12 ]\n");
+    }
+
+    #[test]
+    fn double_precision() {
+        use crate::dvec3;
+        use crate::quat::{rot_dvec_by_dquat, DQuaternion, Quaternion};
+
+        let mat = DMatrix::from(new_mat!(2, 2, [0.1, 2., 3., 4.]));
+        assert_eq!(mat[0][0], 0.1f32 as f64);
+        assert_eq!(mat.as_matrix().data, [[0.1, 2.], [3., 4.]]);
+        assert_eq!((mat * DMatrix::<2, 2>::identity()).data, mat.data);
+        assert_eq!(format!("{}", new_dmat!(1, 2, [0.5, -2.])), "[ 0.5, -2 ]\n");
+
+        let v = DVec3::from(Vec3::new(1., 2., 3.));
+        assert_eq!(DMatrix::<3, 3>::scalar(2.) * v, dvec3!(2., 4., 6.));
+        assert_eq!(v.as_vec3(), Vec3::new(1., 2., 3.));
+        assert_eq!(DVec3::from(IVec3::new(1, -2, 3)), dvec3!(1., -2., 3.));
+
+        // a turn split into many small rotations drifts far less than in f32
+        let mut v = DVec3::X;
+        for _ in 0..3600 {
+            v.rot_quat(0.1, DVec3::Z);
+        }
+        assert!((v - DVec3::X).mag() < 1e-10);
+
+        let half = core::f64::consts::FRAC_1_SQRT_2;
+        let q = DQuaternion::from(Quaternion::new(1., Vec3::ZERO));
+        assert_eq!(rot_dvec_by_dquat(DVec3::Y, &q), DVec3::Y);
+        let q = DQuaternion::new(half, DVec3::Z * half);
+        assert!((rot_dvec_by_dquat(DVec3::X, &q) - DVec3::Y).mag() < 1e-15);
+        assert_eq!(q.as_quaternion().w, half as f32);
+    }
 }
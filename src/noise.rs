@@ -0,0 +1,616 @@
+//! Seedable coherent noise for terrain and textures.
+//!
+//! `Perlin` and `OpenSimplex2` are gradient noises in `-1..=1`, `Worley` measures distances to
+//! scattered feature points. All of them sample `Vec2`, `Vec3` and `Vec4` through `Noise`, and are
+//! deterministic: the same seed gives the same value at the same point on every platform that
+//! rounds `f32` the same way. `Fractal` layers octaves of any of them and `Warp` distorts one by
+//! another. Lattices are hashed rather than looked up in a permutation table, so nothing repeats.
+//! ```
+//! # use my_math::prelude::*;
+//! let terrain = Fractal::fbm(OpenSimplex2::new(42), 5);
+//! let height = terrain.sample(Vec2::new(12.5, -3.25) * 0.01);
+//! assert!((-1. ..=1.).contains(&height));
+//! ```
+use core::array;
+use core::ops::{Add, Mul};
+
+use crate::math;
+use crate::vec::{IVec3, Vec2, Vec3, Vec4};
+
+/// A point noise can be sampled at
+pub trait NoisePoint: Copy + Add<Output = Self> + Mul<f32, Output = Self> {
+    const DIM: usize;
+
+    /// The unit vector along `axis`
+    fn unit(axis: usize) -> Self;
+}
+impl NoisePoint for Vec2 {
+    const DIM: usize = 2;
+
+    fn unit(axis: usize) -> Vec2 {
+        let mut unit = [0.; 2];
+        unit[axis] = 1.;
+        Vec2::new(unit[0], unit[1])
+    }
+}
+impl NoisePoint for Vec3 {
+    const DIM: usize = 3;
+
+    fn unit(axis: usize) -> Vec3 {
+        let mut unit = [0.; 3];
+        unit[axis] = 1.;
+        Vec3::from_slice(unit)
+    }
+}
+impl NoisePoint for Vec4 {
+    const DIM: usize = 4;
+
+    fn unit(axis: usize) -> Vec4 {
+        let mut unit = [0.; 4];
+        unit[axis] = 1.;
+        Vec4::from_slice(&unit)
+    }
+}
+/// The same value on every axis
+fn splat<P: NoisePoint>(value: f32) -> P {
+    (1..P::DIM).fold(P::unit(0) * value, |p, axis| p + P::unit(axis) * value)
+}
+
+/// A noise function over points `P`
+pub trait Noise<P> {
+    fn sample(&self, point: P) -> f32;
+}
+impl<P, N: Noise<P> + ?Sized> Noise<P> for &N {
+    fn sample(&self, point: P) -> f32 {
+        (**self).sample(point)
+    }
+}
+macro_rules! implNoise {
+    ($($noise:ident),+) => {
+        $(
+            impl Noise<Vec2> for $noise {
+                fn sample(&self, p: Vec2) -> f32 {
+                    self.eval([p.x, p.y])
+                }
+            }
+            impl Noise<Vec3> for $noise {
+                fn sample(&self, p: Vec3) -> f32 {
+                    self.eval([p.x, p.y, p.z])
+                }
+            }
+            impl Noise<Vec4> for $noise {
+                fn sample(&self, p: Vec4) -> f32 {
+                    self.eval([p.x, p.y, p.z, p.w])
+                }
+            }
+        )+
+    };
+}
+implNoise!(Perlin, OpenSimplex2, Worley);
+
+// ------------------- Hashing -----------------------------
+
+/// A full avalanche of the bits, the splitmix64 finalizer
+fn mix(mut h: u64) -> u64 {
+    h ^= h >> 30;
+    h = h.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h ^= h >> 27;
+    h = h.wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
+fn hash(seed: u64, lattice: &[i32]) -> u64 {
+    lattice
+        .iter()
+        .fold(mix(seed), |h, &c| mix(h ^ (c as u32 as u64).wrapping_add(0x9e37_79b9_7f4a_7c15)))
+}
+/// A hash as a float in `0..1`
+fn unit_float(h: u64) -> f32 {
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// 16 directions around the circle
+const GRADIENTS_2D: [[f32; 2]; 16] = [
+    [1., 0.], [0.923_879_5, 0.382_683_43], [0.707_106_77, 0.707_106_77], [0.382_683_43, 0.923_879_5],
+    [0., 1.], [-0.382_683_43, 0.923_879_5], [-0.707_106_77, 0.707_106_77], [-0.923_879_5, 0.382_683_43],
+    [-1., 0.], [-0.923_879_5, -0.382_683_43], [-0.707_106_77, -0.707_106_77], [-0.382_683_43, -0.923_879_5],
+    [0., -1.], [0.382_683_43, -0.923_879_5], [0.707_106_77, -0.707_106_77], [0.923_879_5, -0.382_683_43],
+];
+/// The hashed gradient dotted with `offset`. Unit vectors in 2D, above that the midpoints of the
+/// hypercube's edges: one axis zero, the others ±1
+fn gradient<const N: usize>(h: u64, offset: [f32; N]) -> f32 {
+    if N == 2 {
+        let g = GRADIENTS_2D[(h & 15) as usize];
+        return g[0] * offset[0] + g[1] * offset[1];
+    }
+    let zero = (h % N as u64) as usize;
+    let signs = h >> 8;
+    (0..N)
+        .filter(|&a| a != zero)
+        .map(|a| if signs >> a & 1 == 1 { -offset[a] } else { offset[a] })
+        .sum()
+}
+fn floor<const N: usize>(p: [f32; N]) -> [i32; N] {
+    p.map(|c| math::floorf(c) as i32)
+}
+
+// ------------------- Perlin -----------------------------
+
+/// Ken Perlin's improved gradient noise, zero on every integer point
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Perlin {
+    pub seed: u64,
+}
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        Perlin { seed }
+    }
+    fn eval<const N: usize>(&self, p: [f32; N]) -> f32 {
+        let cell = floor(p);
+        let f: [f32; N] = array::from_fn(|a| p[a] - cell[a] as f32);
+        let fade = f.map(|t| t * t * t * (t * (t * 6. - 15.) + 10.));
+        // the nested lerps written out as a weighted sum over the corners
+        let mut value = 0.;
+        for corner in 0..1usize << N {
+            let mut weight = 1.;
+            let mut lattice = cell;
+            let mut offset = f;
+            for a in 0..N {
+                if corner >> a & 1 == 1 {
+                    lattice[a] += 1;
+                    offset[a] -= 1.;
+                    weight *= fade[a];
+                } else {
+                    weight *= 1. - fade[a];
+                }
+            }
+            value += weight * gradient(hash(self.seed, &lattice), offset);
+        }
+        // the peak is in the middle of a cell with every gradient pointing at it
+        let peak = match N {
+            2 => 0.707_106_77,
+            3 => 1.224_744_9,
+            _ => 1.732_050_8,
+        };
+        value / peak
+    }
+}
+
+// ------------------- OpenSimplex2 -----------------------------
+
+/// OpenSimplex2 noise, smoother and less grid aligned than Perlin
+///
+/// 2D and 4D work on the simplex lattice, 3D on a rotated body centred cubic one. Every lattice
+/// point's contribution falls off smoothly to zero at a fixed radius, so the noise has no
+/// discontinuities.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpenSimplex2 {
+    pub seed: u64,
+}
+impl OpenSimplex2 {
+    pub fn new(seed: u64) -> Self {
+        OpenSimplex2 { seed }
+    }
+    fn eval<const N: usize>(&self, p: [f32; N]) -> f32 {
+        // scaled by the measured peaks, the clamp only catches rounding
+        let value = match N {
+            2 => self.simplex(p, 0.366_025_4, 0.211_324_87) * 99.2,
+            3 => self.bcc(array::from_fn(|a| p[a])) * 76.88,
+            _ => self.simplex(p, 0.309_017, 0.138_196_6) * 62.78,
+        };
+        value.clamp(-1., 1.)
+    }
+    /// Sums the corners of the simplex holding `p`, which is found by skewing the simplex lattice
+    /// onto the integer one
+    fn simplex<const N: usize>(&self, p: [f32; N], skew: f32, unskew: f32) -> f32 {
+        let s = p.iter().sum::<f32>() * skew;
+        let cell: [i32; N] = p.map(|c| math::floorf(c + s) as i32);
+        let t = cell.iter().sum::<i32>() as f32 * unskew;
+        let d: [f32; N] = array::from_fn(|a| p[a] - cell[a] as f32 + t);
+        // the simplex steps along the axes in decreasing order of d
+        let mut rank = [0; N];
+        for a in 0..N {
+            for b in a + 1..N {
+                if d[a] > d[b] {
+                    rank[a] += 1;
+                } else {
+                    rank[b] += 1;
+                }
+            }
+        }
+        let mut value = 0.;
+        for k in 0..=N {
+            let step: [i32; N] = array::from_fn(|a| (rank[a] + k >= N) as i32);
+            let offset: [f32; N] = array::from_fn(|a| d[a] - step[a] as f32 + k as f32 * unskew);
+            let lattice: [i32; N] = array::from_fn(|a| cell[a] + step[a]);
+            value += self.contribution(0.5, &lattice, offset);
+        }
+        value
+    }
+    /// The two interleaved cubic grids of the body centred cubic lattice, each contributing its
+    /// nearest point and that point's neighbour along the axis `p` is furthest off on. Within the
+    /// radius nothing else of the grid is ever close enough to count, so nothing jumps.
+    fn bcc(&self, p: [f32; 3]) -> f32 {
+        // turns the lattice so its main diagonal points along y, hiding the grid on xz slices
+        let r = (p[0] + p[1] + p[2]) * (2. / 3.);
+        let p = p.map(|c| r - c);
+        let mut value = 0.;
+        for grid in 0..2 {
+            let shifted = p.map(|c| c - grid as f32 * 0.5);
+            let nearest = shifted.map(|c| math::roundf(c) as i32);
+            let d: [f32; 3] = array::from_fn(|a| shifted[a] - nearest[a] as f32);
+            value += self.contribution(0.5, &[nearest[0], nearest[1], nearest[2], grid], d);
+
+            let axis = (0..3).max_by(|&a, &b| d[a].abs().total_cmp(&d[b].abs())).unwrap();
+            let step = if d[axis] > 0. { 1 } else { -1 };
+            let mut neighbour = [nearest[0], nearest[1], nearest[2], grid];
+            neighbour[axis] += step;
+            let mut offset = d;
+            offset[axis] -= step as f32;
+            value += self.contribution(0.5, &neighbour, offset);
+        }
+        value
+    }
+    /// A lattice point's gradient under a `(radius² - distance²)⁴` falloff
+    fn contribution<const N: usize>(&self, radius_sq: f32, lattice: &[i32], offset: [f32; N]) -> f32 {
+        let falloff = radius_sq - offset.iter().map(|o| o * o).sum::<f32>();
+        if falloff <= 0. {
+            return 0.;
+        }
+        let falloff = falloff * falloff;
+        falloff * falloff * gradient(hash(self.seed, lattice), offset)
+    }
+}
+
+// ------------------- Worley -----------------------------
+
+/// How `Worley` measures the distance to feature points
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Distance {
+    /// Round cells
+    Euclidean,
+    /// Like `Euclidean` without the square root, the same cells with sharper gradients
+    EuclideanSquared,
+    /// Diamond shaped cells
+    Manhattan,
+    /// Square cells
+    Chebyshev,
+}
+impl Distance {
+    fn measure<const N: usize>(self, d: [f32; N]) -> f32 {
+        match self {
+            Distance::Euclidean => math::sqrtf(d.iter().map(|c| c * c).sum()),
+            Distance::EuclideanSquared => d.iter().map(|c| c * c).sum(),
+            Distance::Manhattan => d.iter().map(|c| c.abs()).sum(),
+            Distance::Chebyshev => d.iter().fold(0., |max, c| c.abs().max(max)),
+        }
+    }
+}
+
+/// What `Worley` returns
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Feature {
+    /// Distance to the nearest feature point
+    F1,
+    /// Distance to the second nearest
+    F2,
+    /// Their difference, zero along the cell borders
+    F2MinusF1,
+    /// A random value in `-1..1` shared by the whole cell of the nearest point
+    CellValue,
+}
+
+/// Cellular noise from one randomly placed feature point per unit cell
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Worley {
+    pub seed: u64,
+    pub distance: Distance,
+    pub feature: Feature,
+}
+impl Worley {
+    /// `F1` with `Euclidean` distances
+    pub fn new(seed: u64) -> Self {
+        Worley { seed, distance: Distance::Euclidean, feature: Feature::F1 }
+    }
+    fn eval<const N: usize>(&self, p: [f32; N]) -> f32 {
+        let cell = floor(p);
+        let (mut f1, mut f2, mut nearest) = (f32::INFINITY, f32::INFINITY, 0);
+        // the 3ᴺ cells around, a point further out can only win against very unlucky neighbours
+        for index in 0..3usize.pow(N as u32) {
+            let mut rest = index;
+            let neighbour: [i32; N] = array::from_fn(|a| {
+                let step = (rest % 3) as i32 - 1;
+                rest /= 3;
+                cell[a] + step
+            });
+            let h = hash(self.seed, &neighbour);
+            let d: [f32; N] = array::from_fn(|a| neighbour[a] as f32 + unit_float(mix(h ^ a as u64)) - p[a]);
+            let distance = self.distance.measure(d);
+            if distance < f1 {
+                (f2, f1, nearest) = (f1, distance, h);
+            } else if distance < f2 {
+                f2 = distance;
+            }
+        }
+        match self.feature {
+            Feature::F1 => f1,
+            Feature::F2 => f2,
+            Feature::F2MinusF1 => f2 - f1,
+            Feature::CellValue => unit_float(nearest) * 2. - 1.,
+        }
+    }
+}
+
+// ------------------- Combinators -----------------------------
+
+/// How `Fractal` combines its octaves
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FractalKind {
+    /// The plain sum, in `-1..=1` for noise that is
+    Fbm,
+    /// Sharp ridges where the noise crosses zero, in `0..=1`
+    Ridged,
+    /// The sum of absolute values, billowy and in `0..=1`
+    Turbulence,
+}
+
+/// Octaves of a noise, each `lacunarity` times the frequency and `gain` times the amplitude of
+/// the last, normalized by the total amplitude
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fractal<N> {
+    pub noise: N,
+    pub kind: FractalKind,
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+impl<N> Fractal<N> {
+    /// Doubling the frequency and halving the amplitude every octave
+    pub fn new(noise: N, kind: FractalKind, octaves: u32) -> Self {
+        Fractal { noise, kind, octaves, lacunarity: 2., gain: 0.5 }
+    }
+    pub fn fbm(noise: N, octaves: u32) -> Self {
+        Fractal::new(noise, FractalKind::Fbm, octaves)
+    }
+    pub fn ridged(noise: N, octaves: u32) -> Self {
+        Fractal::new(noise, FractalKind::Ridged, octaves)
+    }
+    pub fn turbulence(noise: N, octaves: u32) -> Self {
+        Fractal::new(noise, FractalKind::Turbulence, octaves)
+    }
+}
+impl<P: NoisePoint, N: Noise<P>> Noise<P> for Fractal<N> {
+    fn sample(&self, point: P) -> f32 {
+        let (mut frequency, mut amplitude) = (1., 1.);
+        let (mut sum, mut total) = (0., 0.);
+        for octave in 0..self.octaves {
+            // shifted so the octaves' lattices don't all line up at the origin
+            let n = self.noise.sample(point * frequency + splat(octave as f32 * 17.31));
+            sum += amplitude
+                * match self.kind {
+                    FractalKind::Fbm => n,
+                    FractalKind::Ridged => (1. - n.abs()) * (1. - n.abs()),
+                    FractalKind::Turbulence => n.abs(),
+                };
+            total += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.gain;
+        }
+        if total > 0. { sum / total } else { 0. }
+    }
+}
+
+/// `noise` sampled at points displaced by `warp`, one decorrelated sample of it per axis scaled
+/// by `strength`
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Warp<N, W> {
+    pub noise: N,
+    pub warp: W,
+    pub strength: f32,
+}
+impl<N, W> Warp<N, W> {
+    pub fn new(noise: N, warp: W, strength: f32) -> Self {
+        Warp { noise, warp, strength }
+    }
+}
+impl<P: NoisePoint, N: Noise<P>, W: Noise<P>> Noise<P> for Warp<N, W> {
+    fn sample(&self, point: P) -> f32 {
+        let displaced = (0..P::DIM).fold(point, |p, axis| {
+            let offset = self.warp.sample(point + splat((axis + 1) as f32 * 41.87));
+            p + P::unit(axis) * (offset * self.strength)
+        });
+        self.noise.sample(displaced)
+    }
+}
+
+// ------------------- Chunks -----------------------------
+
+/// Samples `noise` at every voxel of the `size`³ chunk at chunk coordinate `chunk`
+///
+/// Voxel `(x, y, z)` of it sits at world position `chunk * size + (x, y, z)`, sampled at that
+/// times `frequency` and written to `out[x + size * (y + size * z)]`. Neighbouring chunks line
+/// up seamlessly, the scaled position is formed in `f64` and every voxel gets the `f32` nearest
+/// to it whichever chunk computes it. The noise itself samples in `f32` though, so far out
+/// chunks lose detail, and past about 2²³ voxels from the origin neighbouring voxels round to
+/// the same point. Panics unless `out` holds `size`³ values.
+pub fn fill_chunk<N: Noise<Vec3>>(noise: &N, chunk: IVec3, size: usize, frequency: f32, out: &mut [f32]) {
+    assert_eq!(out.len(), size * size * size, "the chunk holds size³ values");
+    let world = |chunk: i32, voxel: usize| ((chunk as i64 * size as i64 + voxel as i64) as f64 * frequency as f64) as f32;
+    for (i, value) in out.iter_mut().enumerate() {
+        let (x, y, z) = (i % size, i / size % size, i / (size * size));
+        *value = noise.sample(Vec3::new(world(chunk.x, x), world(chunk.y, y), world(chunk.z, z)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rng::Rng;
+    use crate::{vec2, vec3};
+    #[cfg(feature = "alloc")]
+    use crate::ivec3;
+    #[cfg(feature = "alloc")]
+    use alloc::{vec, vec::Vec};
+
+    /// Bounded, varied, about zero on average and without jumps, in every dimension
+    fn check_gradient_noise<P: NoisePoint, N: Noise<P>>(noise: &N, mut point: impl FnMut() -> P) {
+        let (mut sum, mut min, mut max) = (0., f32::INFINITY, f32::NEG_INFINITY);
+        for _ in 0..5000 {
+            let p = point();
+            let v = noise.sample(p);
+            assert!((-1. ..=1.).contains(&v), "{v}");
+            (sum, min, max) = (sum + v, min.min(v), max.max(v));
+            for axis in 0..P::DIM {
+                let step = noise.sample(p + P::unit(axis) * 1e-3);
+                assert!((step - v).abs() < 0.008, "jumps from {v} to {step}");
+            }
+        }
+        assert!((sum / 5000.).abs() < 0.05, "mean {}", sum / 5000.);
+        assert!(min < -0.4 && max > 0.4, "only spans {min}..{max}");
+    }
+
+    #[test]
+    fn gradient_noise() {
+        let mut rng = Rng(0x1234_5678_9abc_def1);
+        for seed in [0, 1, 0xdead_beef] {
+            let perlin = Perlin::new(seed);
+            check_gradient_noise(&perlin, || rng.vec2(-50., 50.));
+            check_gradient_noise(&perlin, || rng.vec3(-50., 50.));
+            check_gradient_noise(&perlin, || rng.vec4(-50., 50.));
+            let simplex = OpenSimplex2::new(seed);
+            check_gradient_noise(&simplex, || rng.vec2(-50., 50.));
+            check_gradient_noise(&simplex, || rng.vec3(-50., 50.));
+            check_gradient_noise(&simplex, || rng.vec4(-50., 50.));
+        }
+
+        // Perlin vanishes on the lattice
+        let perlin = Perlin::new(3);
+        assert_eq!(perlin.sample(vec3!(4., -7., 12.)), 0.);
+        assert_eq!(perlin.sample(vec2!(-1., 0.)), 0.);
+        // the same seed gives the same noise, another seed another
+        let p = vec3!(1.3, 2.7, -0.4);
+        assert_eq!(OpenSimplex2::new(9).sample(p), OpenSimplex2::new(9).sample(p));
+        let differs = (0..20).filter(|&i| {
+            let p = vec2!(i as f32 * 0.37, 0.5);
+            OpenSimplex2::new(9).sample(p) != OpenSimplex2::new(10).sample(p)
+        });
+        assert!(differs.count() > 15);
+        // far from the origin there is no repeating period
+        let far = vec2!(256.3, 0.7);
+        assert_ne!(perlin.sample(far), perlin.sample(vec2!(0.3, 0.7)));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn worley() {
+        let mut rng = Rng(0x0f0f_1234_abcd_5678);
+        let base = Worley::new(5);
+        let with = |distance, feature| Worley { distance, feature, ..base };
+        for _ in 0..2000 {
+            let p = rng.vec3(-50., 50.);
+            let f1 = base.sample(p);
+            let f2 = with(Distance::Euclidean, Feature::F2).sample(p);
+            assert!(f1 >= 0. && f1 <= f2);
+            assert_eq!(with(Distance::Euclidean, Feature::F2MinusF1).sample(p), f2 - f1);
+            assert!((with(Distance::EuclideanSquared, Feature::F1).sample(p) - f1 * f1).abs() < 1e-4);
+            // the norms nest
+            let manhattan = with(Distance::Manhattan, Feature::F1).sample(p);
+            let chebyshev = with(Distance::Chebyshev, Feature::F1).sample(p);
+            assert!(chebyshev <= f1 + 1e-6 && f1 <= manhattan + 1e-6);
+            let value = with(Distance::Euclidean, Feature::CellValue).sample(p);
+            assert!((-1. ..1.).contains(&value));
+
+            let q = rng.vec2(-50., 50.);
+            let f1 = base.sample(q);
+            assert!((0. ..1.5).contains(&f1));
+            let cell = with(Distance::Euclidean, Feature::CellValue);
+            // a small step only changes the cell where the borders are close
+            if with(Distance::Euclidean, Feature::F2MinusF1).sample(q) > 0.01 {
+                assert_eq!(cell.sample(q), cell.sample(q + vec2!(1e-3, 0.)));
+            }
+        }
+        // every cell has its own value
+        let cell = with(Distance::Euclidean, Feature::CellValue);
+        let values: Vec<f32> = (0..10).map(|i| cell.sample(vec2!(i as f32 * 3. + 0.5, 0.5))).collect();
+        assert!(values.windows(2).all(|w| w[0] != w[1]));
+        // it reaches zero on the feature points
+        let mut closest = f32::INFINITY;
+        for i in 0..200 {
+            for j in 0..200 {
+                closest = closest.min(base.sample(vec2!(i as f32 / 100., j as f32 / 100.)));
+            }
+        }
+        assert!(closest < 0.01);
+    }
+
+    #[test]
+    fn combinators() {
+        let mut rng = Rng(0x5555_aaaa_1234_4321);
+        let simplex = OpenSimplex2::new(1);
+        let fbm = Fractal::fbm(simplex, 5);
+        let ridged = Fractal::ridged(Perlin::new(2), 4);
+        let turbulence = Fractal::turbulence(&simplex, 6);
+        let single = Fractal::fbm(simplex, 1);
+        for _ in 0..2000 {
+            let p = rng.vec3(-50., 50.);
+            assert_eq!(single.sample(p), simplex.sample(p));
+            assert!((-1. ..=1.).contains(&fbm.sample(p)));
+            assert!((0. ..=1.).contains(&ridged.sample(p)));
+            assert!((0. ..=1.).contains(&turbulence.sample(p)));
+        }
+        // more octaves add detail at small scales, which shows in the curvature
+        let p = vec2!(0.3, 0.4);
+        let step = vec2!(0.01, 0.);
+        let roughness = |octaves| {
+            let noise = Fractal::fbm(Perlin::new(8), octaves);
+            (0..500).map(|i| {
+                let q = p + vec2!(i as f32 * 0.05, 0.);
+                (noise.sample(q + step) - noise.sample(q) * 2. + noise.sample(q - step)).abs()
+            }).sum::<f32>()
+        };
+        assert!(roughness(6) > roughness(1) * 3.);
+        assert_eq!(Fractal::fbm(simplex, 0).sample(p), 0.);
+
+        let still = Warp::new(simplex, Perlin::new(4), 0.);
+        let warped = Warp::new(simplex, Perlin::new(4), 2.);
+        let mut moved = 0;
+        for _ in 0..200 {
+            let p = rng.vec4(-50., 50.);
+            assert_eq!(still.sample(p), simplex.sample(p));
+            moved += (warped.sample(p) != simplex.sample(p)) as usize;
+        }
+        assert!(moved > 190);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn chunks() {
+        let noise = Fractal::fbm(OpenSimplex2::new(77), 3);
+        let size = 8;
+        let mut out = vec![0.; size * size * size];
+        for chunk in [ivec3!(0, 0, 0), ivec3!(-3, 1, 7), ivec3!(1 << 20, -(1 << 20), 5)] {
+            fill_chunk(&noise, chunk, size, 0.05, &mut out);
+            for (x, y, z) in [(0, 0, 0), (7, 0, 0), (3, 5, 1), (7, 7, 7)] {
+                let world = chunk * size as i32 + ivec3!(x, y, z);
+                let expected = noise.sample(Vec3::from(world) * 0.05);
+                assert!((out[x + size * (y + size * z)] - expected).abs() < 1e-4 * (1. + world.x.abs() as f32 * 1e-3));
+            }
+        }
+        // a world position splits into chunk and voxel, and fills to the same value either way
+        let world = ivec3!(-13, 40, 7);
+        let (chunk, voxel) = (world.div_floor(size as i32), world.modulo(size as i32));
+        fill_chunk(&noise, chunk, size, 0.05, &mut out);
+        let index = (voxel.x + size as i32 * (voxel.y + size as i32 * voxel.z)) as usize;
+        assert_eq!(out[index], noise.sample(Vec3::from(world) * 0.05));
+        // 2²⁵ voxels out f32 can't tell every voxel from its neighbour any more
+        fill_chunk(&noise, ivec3!(1 << 22, 0, 0), size, 0.05, &mut out);
+        assert!(out[..size].windows(2).any(|pair| pair[0] == pair[1]));
+    }
+}
@@ -0,0 +1,411 @@
+//! Renderers for pasting values into documents: LaTeX, Markdown tables and plain Unicode text
+use alloc::borrow::ToOwned;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use crate::fraction::{Fraction, Ratio, SignedInt};
+use crate::matrix::{Matrix, MatrixFr};
+use crate::quat::Quaternion;
+use crate::vec::{Vec2, Vec3, Vec4};
+
+/// The same value rendered for different targets
+/// ```text
+/// to_latex     \begin{bmatrix} 1 & -\frac{7}{3} \\ 12 & \frac{5}{4} \end{bmatrix}
+/// to_markdown  |    |      |
+///              |---:|-----:|
+///              |  1 | -7/3 |
+///              | 12 |  5/4 |
+/// to_unicode   ┌          ┐
+///              │  1  −⁷⁄₃ │
+///              │ 12   ⁵⁄₄ │
+///              └          ┘
+/// ```
+pub trait Pretty {
+    fn to_latex(&self) -> String;
+    fn to_markdown(&self) -> String;
+    fn to_unicode(&self) -> String;
+}
+
+/// Matrix entry split into the parts that get aligned within a column,
+/// `lead` and `int` are right aligned and `tail` is left aligned.
+/// For `-12.5` that is `("", "-12", ".5")`, for the mixed number `2 1/3` it's `("2", "1", "/3")`.
+pub(crate) struct Cell {
+    pub(crate) lead: String,
+    pub(crate) int: String,
+    pub(crate) tail: String,
+}
+impl Cell {
+    /// Splits a formatted decimal on the point, trailing zeros are dropped
+    pub(crate) fn decimal(num: String) -> Cell {
+        let num = if num.contains('.') {
+            num.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            &num
+        };
+        let num = if num == "-0" { "0" } else { num };
+        let (int, tail) = num.split_at(num.find('.').unwrap_or(num.len()));
+        Cell {
+            lead: String::new(),
+            int: int.to_owned(),
+            tail: tail.to_owned(),
+        }
+    }
+    /// `-7/3` aligned on the bar, `mixed` writes it as `-2 1/3`
+    pub(crate) fn fraction(x: Fraction, mixed: bool) -> Cell {
+        let tail = if x.denom() == 1 { String::new() } else { format!("/{}", x.denom()) };
+        if !mixed || x.denom() == 1 || x.trunc() == 0 {
+            return Cell {
+                lead: String::new(),
+                int: x.numer().to_string(),
+                tail,
+            };
+        }
+        Cell {
+            lead: x.trunc().numer().to_string(),
+            int: x.fract().abs().numer().to_string(),
+            tail,
+        }
+    }
+    /// Exactly rounded to `precision` decimals, halfway cases away from zero like `Fraction::round`.
+    /// Rounds the digits of the decimal expansion, so no precision or entry size can overflow.
+    pub(crate) fn rounded(x: Fraction, precision: usize) -> Cell {
+        let expansion = x.decimal_expansion_capped(precision + 1);
+        let mut fract = expansion
+            .non_repeating
+            .iter()
+            .chain(expansion.repeating.iter().cycle())
+            .copied();
+        let fract: Vec<u8> = (0..=precision).map(|_| fract.next().unwrap_or(0)).collect();
+        let mut digits: Vec<u8> = expansion
+            .integer
+            .unsigned_abs()
+            .to_string()
+            .bytes()
+            .map(|b| b - b'0')
+            .chain(fract[..precision].iter().copied())
+            .collect();
+        if fract[precision] >= 5 {
+            // the trailing nines carry over into the first digit that isn't one
+            let carry = digits.iter().rposition(|&d| d != 9);
+            let start = carry.map_or(0, |i| i + 1);
+            digits[start..].fill(0);
+            match carry {
+                Some(i) => digits[i] += 1,
+                None => digits.insert(0, 1),
+            }
+        }
+        let text: String = digits.iter().map(|&d| char::from(b'0' + d)).collect();
+        let (int, fract) = text.split_at(text.len() - precision);
+        let sign = if expansion.negative && digits.iter().any(|&d| d != 0) { "-" } else { "" };
+        Cell::decimal(format!("{sign}{int}.{fract}"))
+    }
+    /// `−⁷⁄₃`, aligned on the slash
+    fn unicode_fraction(x: Fraction) -> Cell {
+        let sign = if x < 0 { "−" } else { "" };
+        let num = x.numer().unsigned_abs().to_string();
+        if x.denom() == 1 {
+            return Cell {
+                lead: String::new(),
+                int: format!("{sign}{num}"),
+                tail: String::new(),
+            };
+        }
+        Cell {
+            lead: String::new(),
+            int: format!("{sign}{}", superscript(&num)),
+            tail: format!("⁄{}", subscript(&x.denom().to_string())),
+        }
+    }
+    fn text(&self) -> String {
+        let sep = if !self.lead.is_empty() && !self.int.is_empty() { " " } else { "" };
+        format!("{}{sep}{}{}", self.lead, self.int, self.tail)
+    }
+}
+
+/// Pads the cells so every column lines up, each column is at least `min_width` characters wide
+pub(crate) fn align(cells: &[Vec<Cell>], min_width: usize) -> Vec<Vec<String>> {
+    let cols = cells.first().map_or(0, |row| row.len());
+    let widths: Vec<[usize; 3]> = (0..cols)
+        .map(|col| {
+            let mut width = [0; 3];
+            for row in cells {
+                width[0] = width[0].max(row[col].lead.chars().count());
+                width[1] = width[1].max(row[col].int.chars().count());
+                width[2] = width[2].max(row[col].tail.chars().count());
+            }
+            width
+        })
+        .collect();
+
+    cells
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, &[lead, int, tail])| {
+                    let sep = if lead > 0 && int > 0 { " " } else { "" };
+                    let text = format!("{:>lead$}{sep}{:>int$}{:<tail$}", cell.lead, cell.int, cell.tail);
+                    format!("{:>min_width$}", text)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `\frac{a}{b}` with the sign in front, integers stay plain
+pub(crate) fn latex_fraction<T: SignedInt>(value: Ratio<T>) -> String {
+    let sign = if value.numer() < T::ZERO { "-" } else { "" };
+    let value = value.abs();
+    if value.denom() == T::ONE {
+        format!("{sign}{}", value.numer())
+    } else {
+        format!("{sign}\\frac{{{}}}{{{}}}", value.numer(), value.denom())
+    }
+}
+/// Factor in front of a row, a factor of 1 is left out
+pub(crate) fn latex_coefficient(value: Fraction) -> String {
+    if value == 1 {
+        String::new()
+    } else if value == -1 {
+        String::from("-")
+    } else {
+        latex_fraction(value)
+    }
+}
+/// `bmatrix` of the entries, an augmented matrix gets a bar before the last column
+pub(crate) fn latex_matrix<const ROW: usize, const COL: usize>(mat: &MatrixFr<ROW, COL>, augmented: bool) -> String {
+    let rows: Vec<Vec<String>> = mat
+        .iter()
+        .map(|row| row.iter().map(|x| latex_fraction(*x)).collect())
+        .collect();
+    if augmented && COL > 1 {
+        let cols = "c".repeat(COL - 1);
+        format!("\\left[\\begin{{array}}{{{cols}|c}} {} \\end{{array}}\\right]", latex_rows(&rows))
+    } else {
+        latex_env("bmatrix", &rows)
+    }
+}
+fn latex_rows(rows: &[Vec<String>]) -> String {
+    rows.iter().map(|row| row.join(" & ")).collect::<Vec<_>>().join(" \\\\ ")
+}
+fn latex_env(env: &str, rows: &[Vec<String>]) -> String {
+    format!("\\begin{{{env}}} {} \\end{{{env}}}", latex_rows(rows))
+}
+
+/// Right aligned table, numbers have no natural header so it's left blank unless given
+fn markdown_table(header: Option<&[&str]>, cells: &[Vec<Cell>]) -> String {
+    let rows = align(cells, 0);
+    let cols = rows.first().map_or(0, |row| row.len());
+    let head: Vec<String> = (0..cols)
+        .map(|col| header.map_or("", |header| header[col]).to_owned())
+        .collect();
+    let widths: Vec<usize> = (0..cols)
+        .map(|col| {
+            let width = rows.first().map_or(0, |row| row[col].chars().count());
+            width.max(head[col].chars().count()).max(1)
+        })
+        .collect();
+
+    let line = |row: &[String]| {
+        let cells: Vec<String> = row.iter().zip(&widths).map(|(x, &w)| format!("{x:>w$}")).collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    let rule: Vec<String> = widths.iter().map(|w| format!("{}:", "-".repeat(w + 1))).collect();
+    let mut out = vec![line(&head), format!("|{}|", rule.join("|"))];
+    out.extend(rows.iter().map(|row| line(row)));
+    out.join("\n")
+}
+/// Aligned columns between box-drawing brackets, a single row gets plain brackets
+fn unicode_matrix(cells: &[Vec<Cell>]) -> String {
+    let lines: Vec<String> = align(cells, 0).iter().map(|row| row.join("  ")).collect();
+    if lines.len() == 1 {
+        return format!("[ {} ]", lines[0]);
+    }
+    let blank = " ".repeat(lines.first().map_or(0, |line| line.chars().count()));
+    let mut out = vec![format!("┌ {blank} ┐")];
+    out.extend(lines.iter().map(|line| format!("│ {line} │")));
+    out.push(format!("└ {blank} ┘"));
+    out.join("\n")
+}
+
+/// `f32` the way `Matrix`'s `Display` writes it, three decimals at most
+fn decimal(x: f32) -> Cell {
+    Cell::decimal(format!("{x:.3}"))
+}
+/// Swaps the ASCII hyphen for a real minus sign
+fn minus(cell: Cell) -> Cell {
+    Cell {
+        lead: cell.lead.replace('-', "−"),
+        int: cell.int.replace('-', "−"),
+        tail: cell.tail,
+    }
+}
+fn superscript(digits: &str) -> String {
+    digits.chars().filter_map(|c| "⁰¹²³⁴⁵⁶⁷⁸⁹".chars().nth(c.to_digit(10)? as usize)).collect()
+}
+fn subscript(digits: &str) -> String {
+    digits.chars().filter_map(|c| "₀₁₂₃₄₅₆₇₈₉".chars().nth(c.to_digit(10)? as usize)).collect()
+}
+
+/// `w + xi + yj + zk` with zero terms left out
+fn quaternion(q: &Quaternion, minus: &str) -> String {
+    let mut out = String::new();
+    for (x, unit) in [(q.w, ""), (q.v.x, "i"), (q.v.y, "j"), (q.v.z, "k")] {
+        let text = decimal(x).text();
+        if text == "0" {
+            continue;
+        }
+        let (negative, abs) = match text.strip_prefix('-') {
+            Some(abs) => (true, abs),
+            None => (false, text.as_str()),
+        };
+        let abs = if abs == "1" && !unit.is_empty() { "" } else { abs };
+        out += &match (out.is_empty(), negative) {
+            (true, false) => String::new(),
+            (true, true) => minus.to_owned(),
+            (false, false) => String::from(" + "),
+            (false, true) => format!(" {minus} "),
+        };
+        out += abs;
+        out += unit;
+    }
+    if out.is_empty() {
+        out.push('0');
+    }
+    out
+}
+
+impl<T: SignedInt> Pretty for Ratio<T> {
+    fn to_latex(&self) -> String {
+        latex_fraction(*self)
+    }
+    fn to_markdown(&self) -> String {
+        if self.denom() == T::ONE {
+            self.numer().to_string()
+        } else {
+            format!("{}/{}", self.numer(), self.denom())
+        }
+    }
+    fn to_unicode(&self) -> String {
+        let sign = if self.numer() < T::ZERO { "−" } else { "" };
+        let value = self.abs();
+        if value.denom() == T::ONE {
+            format!("{sign}{}", value.numer())
+        } else {
+            format!(
+                "{sign}{}⁄{}",
+                superscript(&value.numer().to_string()),
+                subscript(&value.denom().to_string())
+            )
+        }
+    }
+}
+
+impl<const ROW: usize, const COL: usize> Pretty for Matrix<ROW, COL> {
+    fn to_latex(&self) -> String {
+        let rows: Vec<Vec<String>> = self.iter().map(|row| row.iter().map(|x| decimal(*x).text()).collect()).collect();
+        latex_env("bmatrix", &rows)
+    }
+    fn to_markdown(&self) -> String {
+        let cells: Vec<Vec<Cell>> = self.iter().map(|row| row.iter().map(|x| decimal(*x)).collect()).collect();
+        markdown_table(None, &cells)
+    }
+    fn to_unicode(&self) -> String {
+        let cells: Vec<Vec<Cell>> = self.iter().map(|row| row.iter().map(|x| minus(decimal(*x))).collect()).collect();
+        unicode_matrix(&cells)
+    }
+}
+
+impl<const ROW: usize, const COL: usize> Pretty for MatrixFr<ROW, COL> {
+    fn to_latex(&self) -> String {
+        latex_matrix(self, false)
+    }
+    fn to_markdown(&self) -> String {
+        let cells: Vec<Vec<Cell>> = self.iter().map(|row| row.iter().map(|x| Cell::fraction(*x, false)).collect()).collect();
+        markdown_table(None, &cells)
+    }
+    fn to_unicode(&self) -> String {
+        let cells: Vec<Vec<Cell>> = self.iter().map(|row| row.iter().map(|x| Cell::unicode_fraction(*x)).collect()).collect();
+        unicode_matrix(&cells)
+    }
+}
+
+/// Vectors are columns in LaTeX, a table with an `x y z w` header in Markdown and a tuple in Unicode
+macro_rules! implPrettyVec {
+    ($($type:ty => [$($field:ident),+]),+) => {
+        $(
+            impl Pretty for $type {
+                fn to_latex(&self) -> String {
+                    let rows = vec![$(vec![decimal(self.$field).text()]),+];
+                    latex_env("pmatrix", &rows)
+                }
+                fn to_markdown(&self) -> String {
+                    markdown_table(Some(&[$(stringify!($field)),+]), &[vec![$(decimal(self.$field)),+]])
+                }
+                fn to_unicode(&self) -> String {
+                    let parts = [$(minus(decimal(self.$field)).text()),+];
+                    format!("({})", parts.join(", "))
+                }
+            }
+        )+
+    };
+}
+implPrettyVec!(Vec2 => [x, y], Vec3 => [x, y, z], Vec4 => [x, y, z, w]);
+
+impl Pretty for Quaternion {
+    fn to_latex(&self) -> String {
+        quaternion(self, "-")
+    }
+    fn to_markdown(&self) -> String {
+        let cells = vec![decimal(self.w), decimal(self.v.x), decimal(self.v.y), decimal(self.v.z)];
+        markdown_table(Some(&["w", "x", "y", "z"]), &[cells])
+    }
+    fn to_unicode(&self) -> String {
+        quaternion(self, "−")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fr, new_mat, vec3};
+
+    #[test]
+    fn fractions() {
+        assert_eq!(fr!(-7, 3).to_latex(), "-\\frac{7}{3}");
+        assert_eq!(fr!(4).to_latex(), "4");
+        assert_eq!(fr!(-7, 3).to_markdown(), "-7/3");
+        assert_eq!(fr!(-7, 3).to_unicode(), "−⁷⁄₃");
+        assert_eq!(fr!(12, 305).to_unicode(), "¹²⁄₃₀₅");
+        assert_eq!(fr!(-5).to_unicode(), "−5");
+    }
+
+    #[test]
+    fn matrices() {
+        let mat = MatrixFr::<2, 2>::new(vec![fr!(1), fr!(-7, 3), fr!(12), fr!(5, 4)]);
+        assert_eq!(mat.to_latex(), "\\begin{bmatrix} 1 & -\\frac{7}{3} \\\\ 12 & \\frac{5}{4} \\end{bmatrix}");
+        assert_eq!(mat.to_markdown(), "|    |      |\n|---:|-----:|\n|  1 | -7/3 |\n| 12 |  5/4 |");
+        assert_eq!(mat.to_unicode(), "┌          ┐\n│  1  −⁷⁄₃ │\n│ 12   ⁵⁄₄ │\n└          ┘");
+
+        let mat = new_mat!(2, 2, [0.5, -2.0, 1.0 / 3.0, 10.0]);
+        assert_eq!(mat.to_latex(), "\\begin{bmatrix} 0.5 & -2 \\\\ 0.333 & 10 \\end{bmatrix}");
+        assert_eq!(mat.to_markdown(), "|       |    |\n|------:|---:|\n| 0.5   | -2 |\n| 0.333 | 10 |");
+        assert_eq!(new_mat!(1, 2, [1.0, -2.5]).to_unicode(), "[ 1  −2.5 ]");
+    }
+
+    #[test]
+    fn vectors() {
+        let v = vec3!(1.0, -0.5, 2.0);
+        assert_eq!(v.to_latex(), "\\begin{pmatrix} 1 \\\\ -0.5 \\\\ 2 \\end{pmatrix}");
+        assert_eq!(v.to_markdown(), "| x |    y | z |\n|--:|-----:|--:|\n| 1 | -0.5 | 2 |");
+        assert_eq!(v.to_unicode(), "(1, −0.5, 2)");
+        assert_eq!(Vec2::new(0.25, 3.0).to_unicode(), "(0.25, 3)");
+
+        let q = Quaternion::new(0.5, vec3!(-1.0, 0.0, 2.0));
+        assert_eq!(q.to_latex(), "0.5 - i + 2k");
+        assert_eq!(q.to_unicode(), "0.5 − i + 2k");
+        assert_eq!(Quaternion::new(-1.0, vec3!(0.0, 0.0, 0.0)).to_unicode(), "−1");
+        assert_eq!(q.to_markdown(), "|   w |  x | y | z |\n|----:|---:|--:|--:|\n| 0.5 | -1 | 0 | 2 |");
+    }
+}
@@ -1,9 +1,12 @@
-use std::ops::Mul;
+use core::ops::Mul;
 
-use crate::vec::Vec3;
-use crate::vec3;
+use crate::math;
+use crate::vec::{DVec3, Vec3};
+use crate::{dvec3, vec3};
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct Quaternion {
     pub w: f32,
     pub v: Vec3,
@@ -14,6 +17,12 @@ impl Quaternion {
             w,v,
         }
     }
+    /// The rotation by `angle` radians around `axis`, counter clockwise looking down the axis.
+    /// `axis` doesn't have to be normalized.
+    pub fn from_axis_angle(axis: Vec3, angle: f32) -> Self {
+        let half = angle / 2.;
+        Quaternion::new(math::cosf(half), axis.norm() * math::sinf(half))
+    }
     /// Works only if the Quaternion is normalized
     pub fn conjugate(&self) -> Self {
         Quaternion::new(self.w , self.v * -1.)
@@ -21,6 +30,29 @@ impl Quaternion {
     pub fn xyz(&self) -> Vec3 {
         vec3!(self.v.x,self.v.y,self.v.z)
     }
+    pub fn dot(&self, rhs: Quaternion) -> f32 {
+        self.w * rhs.w + self.v.dot(rhs.v)
+    }
+    /// Spherical interpolation along the shorter arc, at constant angular speed.
+    /// Works only if both Quaternions are normalized
+    pub fn slerp(&self, rhs: Quaternion, t: f32) -> Quaternion {
+        let (mut cos, mut end) = (self.dot(rhs), rhs);
+        if cos < 0. {
+            cos = -cos;
+            end = Quaternion::new(-rhs.w, rhs.v * -1.);
+        }
+        let (a, b) = if cos > 0.9995 {
+            // nearly parallel, where the sines vanish and a plain lerp is as good
+            (1. - t, t)
+        } else {
+            let angle = math::acosf(cos);
+            let sin = math::sinf(angle);
+            (math::sinf((1. - t) * angle) / sin, math::sinf(t * angle) / sin)
+        };
+        let out = Quaternion::new(self.w * a + end.w * b, self.v * a + end.v * b);
+        let len = math::sqrtf(out.dot(out));
+        Quaternion::new(out.w / len, out.v * (1. / len))
+    }
 }
 impl Mul<Quaternion> for Quaternion {
     type Output = Quaternion;
@@ -43,3 +75,111 @@ pub fn rot_vec_by_quat(vec: Vec3,quat:&Quaternion) -> Vec3 {
     let out_vec_quat = (*quat) * vec_quat * quat.conjugate();
     out_vec_quat.xyz()
 }
+
+// ------------------- Double precision -----------------------------
+
+/// `Quaternion` over `f64`, `From<Quaternion>` widens losslessly and `as_quaternion()` narrows back
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct DQuaternion {
+    pub w: f64,
+    pub v: DVec3,
+}
+impl DQuaternion {
+    pub fn new(w: f64,v:DVec3) -> Self{
+        DQuaternion {
+            w,v,
+        }
+    }
+    /// The rotation by `angle` radians around `axis`, like [`Quaternion::from_axis_angle`]
+    pub fn from_axis_angle(axis: DVec3, angle: f64) -> Self {
+        let half = angle / 2.;
+        DQuaternion::new(math::cos(half), axis.norm() * math::sin(half))
+    }
+    /// Works only if the Quaternion is normalized
+    pub fn conjugate(&self) -> Self {
+        DQuaternion::new(self.w , self.v * -1.)
+    }
+    pub fn xyz(&self) -> DVec3 {
+        dvec3!(self.v.x,self.v.y,self.v.z)
+    }
+    pub fn dot(&self, rhs: DQuaternion) -> f64 {
+        self.w * rhs.w + self.v.dot(rhs.v)
+    }
+    /// Spherical interpolation along the shorter arc, same as [`Quaternion::slerp`].
+    /// Works only if both Quaternions are normalized
+    pub fn slerp(&self, rhs: DQuaternion, t: f64) -> DQuaternion {
+        let (mut cos, mut end) = (self.dot(rhs), rhs);
+        if cos < 0. {
+            cos = -cos;
+            end = DQuaternion::new(-rhs.w, rhs.v * -1.);
+        }
+        let (a, b) = if cos > 0.9999995 {
+            // nearly parallel, where the sines vanish and a plain lerp is as good
+            (1. - t, t)
+        } else {
+            let angle = math::acos(cos);
+            let sin = math::sin(angle);
+            (math::sin((1. - t) * angle) / sin, math::sin(t * angle) / sin)
+        };
+        let out = DQuaternion::new(self.w * a + end.w * b, self.v * a + end.v * b);
+        let len = math::sqrt(out.dot(out));
+        DQuaternion::new(out.w / len, out.v * (1. / len))
+    }
+    /// Rounds every component to the nearest `f32`
+    pub fn as_quaternion(&self) -> Quaternion {
+        Quaternion::new(self.w as f32, self.v.as_vec3())
+    }
+}
+impl From<Quaternion> for DQuaternion {
+    fn from(q: Quaternion) -> Self {
+        DQuaternion::new(q.w.into(), q.v.into())
+    }
+}
+impl Mul<DQuaternion> for DQuaternion {
+    type Output = DQuaternion;
+
+    fn mul(self, rhs: DQuaternion) -> Self::Output {
+        DQuaternion {
+            w: self.w * rhs.w - self.v.x * rhs.v.x - self.v.y * rhs.v.y - self.v.z * rhs.v.z,
+            v: DVec3 {
+                x: self.w * rhs.v.x + self.v.x * rhs.w + self.v.y * rhs.v.z - self.v.z * rhs.v.y,
+                y: self.w * rhs.v.y - self.v.x * rhs.v.z + self.v.y * rhs.w + self.v.z * rhs.v.x,
+                z: self.w * rhs.v.z + self.v.x * rhs.v.y - self.v.y * rhs.v.x + self.v.z * rhs.w,
+            }
+        }
+    }
+}
+
+/// Assuming the quaternion is normalized
+pub fn rot_dvec_by_dquat(vec: DVec3,quat:&DQuaternion) -> DVec3 {
+    let vec_quat = DQuaternion::new(0.,vec);
+    let out_vec_quat = (*quat) * vec_quat * quat.conjugate();
+    out_vec_quat.xyz()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_approx_eq;
+    use core::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn double_slerp() {
+        let from = DQuaternion::from_axis_angle(DVec3::Z, 0.);
+        let to = DQuaternion::from_axis_angle(dvec3!(0., 0., 2.), FRAC_PI_2 * 3.);
+        for i in 0..=4 {
+            let t = i as f64 / 4.;
+            // the shorter way round is a quarter turn backwards
+            let expected = DQuaternion::from_axis_angle(DVec3::Z, -FRAC_PI_2 * t);
+            assert_approx_eq!(from.slerp(to, t).as_quaternion(), expected.as_quaternion(), epsilon = 1e-6);
+        }
+        let widened = DQuaternion::from(Quaternion::from_axis_angle(Vec3::X, 1.));
+        assert!((widened.dot(DQuaternion::from_axis_angle(DVec3::X, 1.)) - 1.).abs() < 1e-7);
+        let near = DQuaternion::from_axis_angle(DVec3::X, 1e-4);
+        let mid = from.slerp(near, 0.5);
+        assert!((mid.dot(mid) - 1.).abs() < 1e-12);
+        assert!((mid.dot(DQuaternion::from_axis_angle(DVec3::X, 5e-5)) - 1.).abs() < 1e-12);
+    }
+}
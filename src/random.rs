@@ -0,0 +1,483 @@
+//! A small seedable generator and the sampling distributions procedural placement needs.
+//!
+//! `Pcg32` is O'Neill's PCG-XSH-RR generator: 16 bytes of state, fast, statistically solid and
+//! the same sequence for the same seed everywhere. It is not cryptographically secure. The
+//! samplers take any `Random` and return the crate's own types.
+//! ```
+//! # use my_math::prelude::*;
+//! let mut rng = Pcg32::new(7);
+//! let direction = random::on_unit_sphere(&mut rng);
+//! assert!((direction.mag() - 1.).abs() < 1e-5);
+//! ```
+use core::f32::consts::TAU;
+use core::ops::{Add, Mul, Sub};
+
+use crate::fraction::Fraction;
+use crate::math;
+use crate::quat::Quaternion;
+use crate::vec::{Vec2, Vec3};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// A source of uniformly distributed bits
+pub trait Random {
+    fn next_u32(&mut self) -> u32;
+
+    fn next_u64(&mut self) -> u64 {
+        (self.next_u32() as u64) << 32 | self.next_u32() as u64
+    }
+    /// Uniform in `0..1`, every multiple of 2⁻²⁴ equally likely
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * (1. / (1u32 << 24) as f32)
+    }
+    /// Uniform in `0..1`, every multiple of 2⁻⁵³ equally likely
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1. / (1u64 << 53) as f64)
+    }
+    /// Uniform in `lo..hi`
+    fn range(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.next_f32()
+    }
+    /// Uniform in `0..bound` without modulo bias, by Lemire's multiply and reject
+    ///
+    /// Panics if `bound` is zero.
+    fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "`below` needs a positive bound");
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let wide = self.next_u64() as u128 * bound as u128;
+            if wide as u64 >= threshold {
+                return (wide >> 64) as u64;
+            }
+        }
+    }
+    /// `true` with probability `p`
+    fn chance(&mut self, p: f32) -> bool {
+        self.next_f32() < p
+    }
+    /// A uniformly random permutation, by Fisher-Yates
+    fn shuffle<T>(&mut self, slice: &mut [T])
+    where
+        Self: Sized,
+    {
+        for i in (1..slice.len()).rev() {
+            slice.swap(i, self.below(i as u64 + 1) as usize);
+        }
+    }
+}
+
+/// The PCG32 generator, a 64 bit LCG with a permuted 32 bit output
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+impl Pcg32 {
+    const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+    const DEFAULT_STREAM: u64 = 0xda3e_39cb_94b9_5bdb;
+
+    pub fn new(seed: u64) -> Self {
+        Pcg32::with_stream(seed, Pcg32::DEFAULT_STREAM)
+    }
+    /// Generators with the same seed but different streams give unrelated sequences
+    pub fn with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Pcg32 { state: 0, increment: stream << 1 | 1 };
+        rng.step();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.step();
+        rng
+    }
+    fn step(&mut self) {
+        self.state = self.state.wrapping_mul(Pcg32::MULTIPLIER).wrapping_add(self.increment);
+    }
+}
+impl Random for Pcg32 {
+    fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+}
+
+// ------------------- Sampling -----------------------------
+
+/// Uniform inside the unit disk
+pub fn in_unit_disk(rng: &mut impl Random) -> Vec2 {
+    // the square root spreads the points evenly over the area rather than the radius
+    on_unit_circle(rng) * math::sqrtf(rng.next_f32())
+}
+/// Uniform on the unit circle
+pub fn on_unit_circle(rng: &mut impl Random) -> Vec2 {
+    let angle = rng.next_f32() * TAU;
+    Vec2::new(math::cosf(angle), math::sinf(angle))
+}
+/// Uniform inside the unit sphere
+pub fn in_unit_sphere(rng: &mut impl Random) -> Vec3 {
+    // rejection from the cube, taking two tries on average
+    loop {
+        let p = Vec3::new(rng.range(-1., 1.), rng.range(-1., 1.), rng.range(-1., 1.));
+        if p.dot(p) <= 1. {
+            return p;
+        }
+    }
+}
+/// Uniform on the unit sphere
+pub fn on_unit_sphere(rng: &mut impl Random) -> Vec3 {
+    // Archimedes: the height on a sphere is uniform
+    let z = rng.range(-1., 1.);
+    let ring = on_unit_circle(rng) * math::sqrtf((1. - z * z).max(0.));
+    Vec3::new(ring.x, ring.y, z)
+}
+/// A uniformly random rotation, by Shoemake's subgroup algorithm
+pub fn rotation(rng: &mut impl Random) -> Quaternion {
+    let u = rng.next_f32();
+    let (a, b) = (math::sqrtf(1. - u), math::sqrtf(u));
+    let (first, second) = (on_unit_circle(rng) * a, on_unit_circle(rng) * b);
+    Quaternion::new(second.x, Vec3::new(first.y, first.x, second.y))
+}
+/// A unit direction on the hemisphere around the unit vector `normal`, with density
+/// proportional to the cosine of its angle to it, as diffuse lighting wants
+pub fn cosine_hemisphere(rng: &mut impl Random, normal: Vec3) -> Vec3 {
+    // Malley's method: lift a uniform disk point onto the hemisphere
+    let disk = in_unit_disk(rng);
+    let up = math::sqrtf((1. - disk.dot(disk)).max(0.));
+    // an orthonormal basis around the normal, Duff et al.'s branchless construction
+    let sign = 1f32.copysign(normal.z);
+    let a = -1. / (sign + normal.z);
+    let b = normal.x * normal.y * a;
+    let tangent = Vec3::new(1. + sign * normal.x * normal.x * a, sign * b, -sign * normal.x);
+    let bitangent = Vec3::new(b, sign + normal.y * normal.y * a, -normal.y);
+    tangent * disk.x + bitangent * disk.y + normal * up
+}
+/// Uniform inside the triangle `abc`, for `Vec2` or `Vec3` corners
+pub fn in_triangle<P>(rng: &mut impl Random, a: P, b: P, c: P) -> P
+where
+    P: Copy + Add<Output = P> + Sub<Output = P> + Mul<f32, Output = P>,
+{
+    let (mut u, mut v) = (rng.next_f32(), rng.next_f32());
+    // folds the far half of the parallelogram back onto the triangle
+    if u + v > 1. {
+        (u, v) = (1. - u, 1. - v);
+    }
+    a + (b - a) * u + (c - a) * v
+}
+/// Uniform among the distinct fractions in `0..1` whose denominator is at most
+/// `max_denominator`, in lowest terms
+///
+/// Panics if `max_denominator` isn't positive.
+pub fn fraction(rng: &mut impl Random, max_denominator: i128) -> Fraction {
+    assert!(max_denominator > 0, "fractions need a positive denominator");
+    let max = max_denominator as u128;
+    loop {
+        // a uniform denominator kept with probability proportional to it, then a uniform
+        // numerator, makes every pair equally likely; keeping the reduced ones makes every
+        // fraction equally likely
+        let den = below_u128(rng, max) + 1;
+        if below_u128(rng, max) >= den {
+            continue;
+        }
+        let num = below_u128(rng, den);
+        let fraction = Fraction::new(num as i128, den as i128);
+        if fraction.denom() == den as i128 {
+            return fraction;
+        }
+    }
+}
+/// `Random::below` for bounds beyond `u64`
+fn below_u128(rng: &mut impl Random, bound: u128) -> u128 {
+    if let Ok(bound) = u64::try_from(bound) {
+        return rng.below(bound) as u128;
+    }
+    // 2¹²⁸ mod bound, the values below it would favour the small remainders
+    let threshold = bound.wrapping_neg() % bound;
+    loop {
+        let wide = (rng.next_u64() as u128) << 64 | rng.next_u64() as u128;
+        if wide >= threshold {
+            return wide % bound;
+        }
+    }
+}
+
+// ------------------- Poisson disk -----------------------------
+
+/// Bridson's algorithm: grows the set from random points, trying `ATTEMPTS` candidates in the
+/// shell between `radius` and twice it around each before retiring it
+#[cfg(feature = "alloc")]
+const ATTEMPTS: usize = 30;
+
+/// Evenly spread points in the box `min..max`, none closer than `radius` to another and no room
+/// left for one more
+///
+/// Panics unless `radius` is positive. `None` if the box isn't finite or is too many radii
+/// across: the lookup grid takes a `usize` per `radius / √2` square, `None` is returned when
+/// that much memory can't be reserved.
+#[cfg(feature = "alloc")]
+pub fn poisson_disk_2d(rng: &mut impl Random, min: Vec2, max: Vec2, radius: f32) -> Option<Vec<Vec2>> {
+    let points = poisson_disk(rng, [min.x, min.y], [max.x, max.y], radius, |rng| {
+        let d = on_unit_circle(rng);
+        [d.x, d.y]
+    })?;
+    Some(points.into_iter().map(|[x, y]| Vec2::new(x, y)).collect())
+}
+/// Evenly spread points in the box `min..max`, none closer than `radius` to another and no room
+/// left for one more
+///
+/// Panics unless `radius` is positive. `None` if the box is too many radii across, like
+/// [`poisson_disk_2d`] but with a cell per `radius / √3` cube.
+#[cfg(feature = "alloc")]
+pub fn poisson_disk_3d(rng: &mut impl Random, min: Vec3, max: Vec3, radius: f32) -> Option<Vec<Vec3>> {
+    let points = poisson_disk(rng, min.to_array(), max.to_array(), radius, |rng| on_unit_sphere(rng).to_array())?;
+    Some(points.into_iter().map(Vec3::from_slice).collect())
+}
+#[cfg(feature = "alloc")]
+fn poisson_disk<R: Random, const N: usize>(
+    rng: &mut R,
+    min: [f32; N],
+    max: [f32; N],
+    radius: f32,
+    direction: impl Fn(&mut R) -> [f32; N],
+) -> Option<Vec<[f32; N]>> {
+    assert!(radius > 0., "Poisson disk sampling needs a positive radius");
+    if (0..N).any(|a| max[a] < min[a]) {
+        return Some(Vec::new());
+    }
+    // cells small enough to hold at most one point, which is then at most two cells away from
+    // anything within the radius
+    let cell = radius / math::sqrtf(N as f32);
+    let mut dims = [0; N];
+    for a in 0..N {
+        let span = (max[a] - min[a]) / cell;
+        if !span.is_finite() || span >= usize::MAX as f32 {
+            return None;
+        }
+        dims[a] = span as usize + 1;
+    }
+    let cells = dims.iter().try_fold(1usize, |cells, &d| cells.checked_mul(d))?;
+    let mut grid = Vec::new();
+    grid.try_reserve_exact(cells).ok()?;
+    grid.resize(cells, usize::MAX);
+    let cell_of = |p: &[f32; N]| -> [usize; N] {
+        core::array::from_fn(|a| (((p[a] - min[a]) / cell) as usize).min(dims[a] - 1))
+    };
+    let index = |c: &[usize; N]| c.iter().zip(&dims).rev().fold(0, |i, (c, d)| i * d + c);
+
+    let fits = |grid: &[usize], points: &[[f32; N]], p: &[f32; N]| {
+        let centre = cell_of(p);
+        (0..5usize.pow(N as u32)).all(|offset| {
+            let mut rest = offset;
+            let mut neighbour = [0; N];
+            for a in 0..N {
+                let c = (centre[a] + rest % 5).checked_sub(2);
+                rest /= 5;
+                match c {
+                    Some(c) if c < dims[a] => neighbour[a] = c,
+                    _ => return true,
+                }
+            }
+            let other = grid[index(&neighbour)];
+            other == usize::MAX
+                || (0..N).map(|a| (points[other][a] - p[a]) * (points[other][a] - p[a])).sum::<f32>() >= radius * radius
+        })
+    };
+
+    let first: [f32; N] = core::array::from_fn(|a| rng.range(min[a], max[a]));
+    let mut points = alloc::vec![first];
+    grid[index(&cell_of(&first))] = 0;
+    let mut active = alloc::vec![0];
+    while !active.is_empty() {
+        let slot = rng.below(active.len() as u64) as usize;
+        let centre = points[active[slot]];
+        let found = (0..ATTEMPTS).find_map(|_| {
+            // uniform by volume in the shell
+            let d = direction(rng);
+            let t = rng.next_f32();
+            let r = radius * if N == 2 { math::sqrtf(1. + 3. * t) } else { math::cbrtf(1. + 7. * t) };
+            let p: [f32; N] = core::array::from_fn(|a| centre[a] + d[a] * r);
+            let inside = (0..N).all(|a| p[a] >= min[a] && p[a] <= max[a]);
+            (inside && fits(&grid, &points, &p)).then_some(p)
+        });
+        match found {
+            Some(p) => {
+                grid[index(&cell_of(&p))] = points.len();
+                active.push(points.len());
+                points.push(p);
+            }
+            None => {
+                active.swap_remove(slot);
+            }
+        }
+    }
+    Some(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assert_approx_eq, fr};
+    #[cfg(feature = "alloc")]
+    use crate::{vec2, vec3};
+    #[cfg(feature = "alloc")]
+    use alloc::vec;
+
+    const N: usize = 20_000;
+
+    #[test]
+    fn generator() {
+        // the reference output of O'Neill's pcg32-demo
+        let mut rng = Pcg32::with_stream(42, 54);
+        let first: [u32; 6] = core::array::from_fn(|_| rng.next_u32());
+        assert_eq!(first, [0xa15c_02b7, 0x7b47_f409, 0xba1d_3330, 0x83d2_f293, 0xbfa4_784b, 0xcbed_606e]);
+
+        assert_eq!(Pcg32::new(5), Pcg32::new(5));
+        assert_ne!(Pcg32::new(5).next_u64(), Pcg32::new(6).next_u64());
+        assert_ne!(Pcg32::with_stream(5, 1).next_u64(), Pcg32::with_stream(5, 2).next_u64());
+
+        let mut rng = Pcg32::new(1);
+        let mut counts = [0; 6];
+        for _ in 0..6 * N {
+            let f = rng.next_f32();
+            assert!((0. ..1.).contains(&f));
+            assert!((0. ..1.).contains(&rng.next_f64()));
+            assert!((-3. ..2.).contains(&rng.range(-3., 2.)));
+            counts[rng.below(6) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| (c as f32 - N as f32).abs() < N as f32 * 0.05), "{counts:?}");
+        assert_eq!(rng.below(1), 0);
+        assert!(rng.below(u64::MAX) < u64::MAX);
+        let heads = (0..N).filter(|_| rng.chance(0.3)).count();
+        assert_approx_eq!(heads as f32 / N as f32, 0.3, epsilon = 0.02);
+
+        let sorted: [u32; 52] = core::array::from_fn(|i| i as u32);
+        let mut deck = sorted;
+        rng.shuffle(&mut deck);
+        assert_ne!(deck, sorted);
+        deck.sort_unstable();
+        assert_eq!(deck, sorted);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn distributions() {
+        let mut rng = Pcg32::new(2);
+        let fraction = |hits: usize| hits as f32 / N as f32;
+
+        let disk: Vec<Vec2> = (0..N).map(|_| in_unit_disk(&mut rng)).collect();
+        assert!(disk.iter().all(|p| p.mag() <= 1.));
+        assert_approx_eq!(fraction(disk.iter().filter(|p| p.mag() < 0.5).count()), 0.25, epsilon = 0.02);
+        assert_approx_eq!(disk.iter().fold(vec2!(0., 0.), |s, p| s + *p) * (1. / N as f32), vec2!(0., 0.), epsilon = 0.02);
+        for _ in 0..100 {
+            assert_approx_eq!(on_unit_circle(&mut rng).mag(), 1., epsilon = 1e-6);
+        }
+
+        let ball: Vec<Vec3> = (0..N).map(|_| in_unit_sphere(&mut rng)).collect();
+        assert!(ball.iter().all(|p| p.mag() <= 1.));
+        assert_approx_eq!(fraction(ball.iter().filter(|p| p.mag() < 0.5).count()), 0.125, epsilon = 0.02);
+        let sphere: Vec<Vec3> = (0..N).map(|_| on_unit_sphere(&mut rng)).collect();
+        assert!(sphere.iter().all(|p| (p.mag() - 1.).abs() < 1e-5));
+        assert_approx_eq!(sphere.iter().fold(Vec3::ZERO, |s, p| s + *p) * (1. / N as f32), Vec3::ZERO, epsilon = 0.02);
+        // any band of equal height holds the same share of the sphere
+        assert_approx_eq!(fraction(sphere.iter().filter(|p| p.x > 0.5).count()), 0.25, epsilon = 0.02);
+
+        // random rotations send a fixed vector everywhere evenly
+        let mut mean = Vec3::ZERO;
+        for _ in 0..N {
+            let q = rotation(&mut rng);
+            assert_approx_eq!(q.dot(q), 1., epsilon = 1e-5);
+            mean = mean + crate::quat::rot_vec_by_quat(Vec3::X, &q);
+        }
+        assert_approx_eq!(mean * (1. / N as f32), Vec3::ZERO, epsilon = 0.02);
+
+        for normal in [Vec3::UP, Vec3::NEG_Z, vec3!(1., 2., -3.).norm(), on_unit_sphere(&mut rng)] {
+            let mut cosines = 0.;
+            for _ in 0..N {
+                let d = cosine_hemisphere(&mut rng, normal);
+                assert!((d.mag() - 1.).abs() < 1e-4);
+                assert!(d.dot(normal) >= -1e-6);
+                cosines += d.dot(normal);
+            }
+            // cos θ weighted by cos θ over the hemisphere averages to 2/3
+            assert_approx_eq!(cosines / N as f32, 2. / 3., epsilon = 0.01);
+        }
+
+        let (a, b, c) = (vec2!(0., 0.), vec2!(4., 0.), vec2!(1., 3.));
+        let mut centroid = vec2!(0., 0.);
+        for _ in 0..N {
+            let p = in_triangle(&mut rng, a, b, c);
+            assert!((b - a).cross(p - a) >= -1e-5 && (c - b).cross(p - b) >= -1e-5 && (a - c).cross(p - c) >= -1e-5);
+            centroid = centroid + p;
+        }
+        assert_approx_eq!(centroid * (1. / N as f32), (a + b + c) * (1. / 3.), epsilon = 0.03);
+        let p = in_triangle(&mut rng, Vec3::X, Vec3::Y, Vec3::Z);
+        assert_approx_eq!(p.x + p.y + p.z, 1., epsilon = 1e-6);
+    }
+
+    #[test]
+    fn fractions() {
+        let mut rng = Pcg32::new(3);
+        // 0, 1/4, 1/3, 1/2, 2/3 and 3/4, equally often
+        let farey = [fr!(0), fr!(1, 4), fr!(1, 3), fr!(1, 2), fr!(2, 3), fr!(3, 4)];
+        let mut counts = [0; 6];
+        for _ in 0..6 * N {
+            let f = fraction(&mut rng, 4);
+            counts[farey.iter().position(|&x| x == f).unwrap()] += 1;
+        }
+        assert!(counts.iter().all(|&c| (c as f32 - N as f32).abs() < N as f32 * 0.05), "{counts:?}");
+
+        for max in [1, 7, 1000, 1 << 70, i128::MAX] {
+            for _ in 0..100 {
+                let f = fraction(&mut rng, max);
+                assert!(f.denom() <= max && f >= fr!(0) && f < fr!(1));
+            }
+        }
+        assert_eq!(fraction(&mut rng, 1), fr!(0));
+    }
+
+    /// At least `radius` apart, inside the box, and leaving no gap a point would fit in
+    #[cfg(feature = "alloc")]
+    fn check_poisson<const D: usize>(points: &[[f32; D]], min: [f32; D], max: [f32; D], radius: f32, probes: &[[f32; D]]) {
+        let distance = |a: &[f32; D], b: &[f32; D]| math::sqrtf((0..D).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum());
+        for (i, p) in points.iter().enumerate() {
+            assert!((0..D).all(|a| p[a] >= min[a] && p[a] <= max[a]));
+            for q in &points[i + 1..] {
+                assert!(distance(p, q) >= radius * 0.9999, "{p:?} and {q:?} are too close");
+            }
+        }
+        for probe in probes {
+            let nearest = points.iter().map(|p| distance(p, probe)).fold(f32::INFINITY, f32::min);
+            assert!(nearest < 2. * radius, "a gap at {probe:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn poisson_disk() {
+        let mut rng = Pcg32::new(4);
+        let points = poisson_disk_2d(&mut rng, vec2!(-5., 0.), vec2!(5., 4.), 0.3).unwrap();
+        let probes: Vec<[f32; 2]> = (0..=50).flat_map(|i| (0..=20).map(move |j| [-5. + i as f32 * 0.2, j as f32 * 0.2])).collect();
+        let points: Vec<[f32; 2]> = points.iter().map(|p| [p.x, p.y]).collect();
+        check_poisson(&points, [-5., 0.], [5., 4.], 0.3, &probes);
+        // Bridson packs about 0.7 / radius² points per unit area
+        assert!(points.len() > 220 && points.len() < 400, "{}", points.len());
+
+        let points = poisson_disk_3d(&mut rng, Vec3::ZERO, vec3!(3., 2., 2.), 0.4).unwrap();
+        let probes: Vec<[f32; 3]> = (0..1000).map(|_| [rng.range(0., 3.), rng.range(0., 2.), rng.range(0., 2.)]).collect();
+        let points: Vec<[f32; 3]> = points.iter().map(|p| p.to_array()).collect();
+        check_poisson(&points, [0.; 3], [3., 2., 2.], 0.4, &probes);
+
+        // the same seed places the same points
+        let again = |seed| poisson_disk_2d(&mut Pcg32::new(seed), vec2!(0., 0.), vec2!(2., 2.), 0.25);
+        assert_eq!(again(9), again(9));
+        assert_eq!(poisson_disk_2d(&mut rng, vec2!(1., 1.), vec2!(0., 0.), 0.1), Some(vec![]));
+        assert_eq!(poisson_disk_3d(&mut rng, Vec3::X, Vec3::X, 0.1), Some(vec![Vec3::X]));
+
+        // grids too big to allocate
+        assert_eq!(poisson_disk_2d(&mut rng, vec2!(0., 0.), vec2!(1e6, 1e6), 1e-6), None);
+        assert_eq!(poisson_disk_3d(&mut rng, Vec3::ZERO, vec3!(1e4, 1e4, 1e4), 1e-3), None);
+        // 2·10¹⁶ cells fit in an isize but not in memory
+        assert_eq!(poisson_disk_2d(&mut rng, vec2!(0., 0.), vec2!(1e5, 1e5), 1e-3), None);
+        assert_eq!(poisson_disk_2d(&mut rng, vec2!(0., 0.), vec2!(f32::INFINITY, 1.), 0.1), None);
+        assert_eq!(poisson_disk_2d(&mut rng, vec2!(f32::NAN, 0.), vec2!(1., 1.), 0.1), None);
+    }
+}
@@ -0,0 +1,264 @@
+//! `serde` support, fractions are `"3/4"` strings in human readable formats and `(n, d)` pairs otherwise,
+//! matrices are nested row arrays. Vectors and quaternions derive theirs.
+use core::fmt;
+use core::marker::PhantomData;
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use crate::fraction::{FractionErrorKind, ParseFractionError, Ratio, SignedInt};
+use crate::matrix::{DMatrix, Matrix, MatrixFr};
+
+/// `Ratio::new` without the panics, bad denominators and values that don't fit after reducing are errors
+fn ratio<T: SignedInt>(num: T, den: T) -> Result<Ratio<T>, ParseFractionError> {
+    if den == T::ZERO {
+        return Err(FractionErrorKind::ZeroDenominator.into());
+    }
+    Ratio::checked_new(num, den).ok_or(FractionErrorKind::Overflow.into())
+}
+
+impl<T: SignedInt + Serialize> Serialize for Ratio<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if !serializer.is_human_readable() {
+            return (self.numerator, self.denominator).serialize(serializer);
+        }
+        if self.denominator == T::ONE {
+            serializer.collect_str(&self.numerator)
+        } else {
+            serializer.collect_str(&format_args!("{}/{}", self.numerator, self.denominator))
+        }
+    }
+}
+
+struct RatioVisitor<T>(PhantomData<T>);
+
+impl<T: SignedInt> RatioVisitor<T> {
+    fn int<E: de::Error>(value: i128) -> Result<Ratio<T>, E> {
+        let value = T::from_i128(value).ok_or_else(|| E::custom(ParseFractionError::from(FractionErrorKind::Overflow)))?;
+        Ok(Ratio::new(value, T::ONE))
+    }
+}
+
+impl<'de, T: SignedInt + Deserialize<'de>> Visitor<'de> for RatioVisitor<T> {
+    type Value = Ratio<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a fraction as a \"n/d\" string, an [n, d] pair or an integer")
+    }
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let num: T = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let den: T = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(3, &self));
+        }
+        ratio(num, den).map_err(de::Error::custom)
+    }
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Self::int(v as i128)
+    }
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Self::int(v as i128)
+    }
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<Self::Value, E> {
+        Self::int(v)
+    }
+}
+
+impl<'de, T: SignedInt + Deserialize<'de>> Deserialize<'de> for Ratio<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(RatioVisitor(PhantomData))
+        } else {
+            deserializer.deserialize_tuple(2, RatioVisitor(PhantomData))
+        }
+    }
+}
+
+fn serialize_rows<S, T, const ROW: usize, const COL: usize>(data: &[[T; COL]; ROW], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    let mut seq = serializer.serialize_seq(Some(ROW))?;
+    for row in data {
+        seq.serialize_element(&row[..])?;
+    }
+    seq.end()
+}
+
+/// Rows must match the matrix's dimensions exactly
+fn deserialize_rows<'de, D, T, const ROW: usize, const COL: usize>(deserializer: D) -> Result<[[T; COL]; ROW], D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Copy,
+{
+    let rows = Vec::<Vec<T>>::deserialize(deserializer)?;
+    if rows.len() != ROW {
+        return Err(de::Error::invalid_length(rows.len(), &format!("{ROW} rows").as_str()));
+    }
+    if let Some(row) = rows.iter().find(|row| row.len() != COL) {
+        return Err(de::Error::invalid_length(row.len(), &format!("rows of {COL} entries").as_str()));
+    }
+    Ok(core::array::from_fn(|i| core::array::from_fn(|j| rows[i][j])))
+}
+
+impl<const ROW: usize, const COL: usize> Serialize for Matrix<ROW, COL> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_rows(&self.data, serializer)
+    }
+}
+impl<'de, const ROW: usize, const COL: usize> Deserialize<'de> for Matrix<ROW, COL> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Matrix { data: deserialize_rows(deserializer)? })
+    }
+}
+
+impl<const ROW: usize, const COL: usize> Serialize for DMatrix<ROW, COL> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_rows(&self.data, serializer)
+    }
+}
+impl<'de, const ROW: usize, const COL: usize> Deserialize<'de> for DMatrix<ROW, COL> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(DMatrix { data: deserialize_rows(deserializer)? })
+    }
+}
+
+impl<const ROW: usize, const COL: usize> Serialize for MatrixFr<ROW, COL> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_rows(&self.data, serializer)
+    }
+}
+impl<'de, const ROW: usize, const COL: usize> Deserialize<'de> for MatrixFr<ROW, COL> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(MatrixFr { data: deserialize_rows(deserializer)? })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fraction::{Fraction, Ratio};
+    use crate::matrix::{DMatrix, Matrix, MatrixFr};
+    use crate::quat::{DQuaternion, Quaternion};
+    use crate::vec::{DVec2, DVec3, DVec4, IVec2, IVec3, Vec2, Vec3, Vec4};
+    use crate::{fr, new_mat, vec3};
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    fn round_trip<T>(value: &T) -> T
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn fractions() {
+        assert_eq!(serde_json::to_string(&fr!(3, 4)).unwrap(), "\"3/4\"");
+        assert_eq!(serde_json::to_string(&fr!(-5)).unwrap(), "\"-5\"");
+        assert_eq!(serde_json::from_str::<Fraction>("\"-6/8\"").unwrap(), fr!(-3, 4));
+        assert_eq!(serde_json::from_str::<Fraction>("\"2(1/3)\"").unwrap(), fr!(7, 3));
+        assert_eq!(serde_json::from_str::<Fraction>("[6, -8]").unwrap(), fr!(-3, 4));
+        assert_eq!(serde_json::from_str::<Fraction>("7").unwrap(), fr!(7));
+        assert_eq!(serde_json::from_str::<Ratio<i8>>("[-128, 1]").unwrap(), Ratio::<i8>::new(-128, 1));
+
+        for value in [fr!(0), fr!(-7, 3), fr!(i128::MIN), fr!(i128::MAX, i128::MAX - 1)] {
+            assert_eq!(round_trip(&value), value);
+        }
+        assert_eq!(round_trip(&Ratio::<i8>::new(-128, 127)), Ratio::new(-128, 127));
+        assert_eq!(round_trip(&Ratio::<i16>::new(5, -6)), Ratio::new(-5, 6));
+        assert_eq!(round_trip(&Ratio::<i32>::new(i32::MAX, 2)), Ratio::new(i32::MAX, 2));
+        assert_eq!(round_trip(&Ratio::<i64>::new(i64::MIN, 3)), Ratio::new(i64::MIN, 3));
+        assert_eq!(round_trip(&Ratio::<isize>::new(1, isize::MAX)), Ratio::new(1, isize::MAX));
+    }
+
+    #[test]
+    fn fraction_errors() {
+        let err = |json: &str| serde_json::from_str::<Fraction>(json).unwrap_err().to_string();
+        assert!(err("[1, 0]").contains("zero denominator"));
+        assert!(err("\"1/0\"").contains("zero denominator"));
+        assert!(err("\"1/2/3\"").contains("invalid digit"));
+        assert!(err("\"\"").contains("empty"));
+        assert!(err("[1, 2, 3]").contains("invalid length"));
+        assert!(err("[1]").contains("invalid length"));
+        // Reducing these overflows the backing integer, that's an error and never a panic
+        assert!(err("\"-170141183460469231731687303715884105728/-1\"").contains("too large"));
+        assert!(err("\"170141183460469231731687303715884105728/1\"").contains("too large"));
+
+        let err_i8 = |json: &str| serde_json::from_str::<Ratio<i8>>(json).unwrap_err().to_string();
+        assert!(err_i8("300").contains("too large"));
+        assert!(err_i8("[-128, -1]").contains("too large"));
+        assert!(err_i8("\"-128/-1\"").contains("too large"));
+        assert!(err_i8("[1, 200]").contains("invalid value"));
+
+        // A T::MIN denominator is fine whenever the reduced value fits
+        assert_eq!(serde_json::from_str::<Ratio<i8>>("[0, -128]").unwrap(), Ratio::new(0, 1));
+        assert_eq!(serde_json::from_str::<Ratio<i8>>("[-128, -128]").unwrap(), Ratio::new(1, 1));
+        assert_eq!(serde_json::from_str::<Ratio<i8>>("[64, -128]").unwrap(), Ratio::new(-1, 2));
+        assert!(err_i8("[1, -128]").contains("too large"));
+    }
+
+    #[test]
+    fn matrices() {
+        let mat = new_mat!(2, 3, [1.0, 2.5, 3.0, 4.0, 5.0, -6.0]);
+        let json = serde_json::to_string(&mat).unwrap();
+        assert_eq!(json, "[[1.0,2.5,3.0],[4.0,5.0,-6.0]]");
+        assert_eq!(serde_json::from_str::<Matrix<2, 3>>(&json).unwrap().data, mat.data);
+        assert!(serde_json::from_str::<Matrix<3, 2>>(&json).is_err());
+        assert!(serde_json::from_str::<Matrix<2, 2>>(&json).is_err());
+
+        let mat = MatrixFr::<2, 2>::new(vec![fr!(1), fr!(-7, 3), fr!(1, 2), fr!(0)]);
+        let json = serde_json::to_string(&mat).unwrap();
+        assert_eq!(json, "[[\"1\",\"-7/3\"],[\"1/2\",\"0\"]]");
+        assert_eq!(serde_json::from_str::<MatrixFr<2, 2>>(&json).unwrap().data, mat.data);
+        let err = serde_json::from_str::<MatrixFr<2, 2>>("[[\"1\"],[\"2\"]]").unwrap_err();
+        assert!(err.to_string().contains("rows of 2 entries"));
+    }
+
+    #[test]
+    fn all_types() {
+        let mat = new_mat!(2, 2, [1.0, -2.5, 0.0, 4.0]);
+        assert_eq!(round_trip(&mat).data, mat.data);
+        let mat = DMatrix::<2, 3> { data: [[1.0, 2.0, 3.0], [-0.1, 0.2, 1e300]] };
+        assert_eq!(round_trip(&mat).data, mat.data);
+        let mat = MatrixFr::<1, 3>::new(vec![fr!(-1, 3), fr!(i128::MAX), fr!(0)]);
+        assert_eq!(round_trip(&mat).data, mat.data);
+
+        assert_eq!(round_trip(&IVec2::new(1, -2)), IVec2::new(1, -2));
+        assert_eq!(round_trip(&IVec3::new(-3, 0, 7)), IVec3::new(-3, 0, 7));
+        assert_eq!(round_trip(&Vec2::new(0.5, -1.0)), Vec2::new(0.5, -1.0));
+        assert_eq!(round_trip(&vec3!(1.0, -2.0, 0.5)), vec3!(1.0, -2.0, 0.5));
+        let v = round_trip(&Vec4::new(1.0, 2.0, 3.0, 4.0));
+        assert_eq!((v.x, v.y, v.z, v.w), (1.0, 2.0, 3.0, 4.0));
+        let v = round_trip(&DVec2::new(0.1, 0.2));
+        assert_eq!((v.x, v.y), (0.1, 0.2));
+        assert_eq!(round_trip(&DVec3::new(0.1, -0.2, 0.3)), DVec3::new(0.1, -0.2, 0.3));
+        let v = round_trip(&DVec4::new(0.1, 0.2, 0.3, -0.4));
+        assert_eq!((v.x, v.y, v.z, v.w), (0.1, 0.2, 0.3, -0.4));
+
+        let q = Quaternion { w: 0.5, v: vec3!(0.5, -0.5, 0.5) };
+        let back = round_trip(&q);
+        assert_eq!((back.w, back.v), (q.w, q.v));
+        let q = DQuaternion { w: 0.5, v: DVec3::new(0.5, -0.5, 0.5) };
+        let back = round_trip(&q);
+        assert_eq!((back.w, back.v), (q.w, q.v));
+    }
+
+    #[test]
+    fn vectors() {
+        let v = vec3!(1.0, -2.0, 0.5);
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(json, "{\"x\":1.0,\"y\":-2.0,\"z\":0.5}");
+        assert_eq!(serde_json::from_str::<Vec3>(&json).unwrap(), v);
+        assert_eq!(serde_json::from_str::<IVec2>("{\"x\":1,\"y\":-2}").unwrap().y, -2);
+
+        let q: Quaternion = serde_json::from_str("{\"w\":1.0,\"v\":{\"x\":0.0,\"y\":1.0,\"z\":0.0}}").unwrap();
+        assert_eq!(q.v, Vec3::UP);
+    }
+}
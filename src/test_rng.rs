@@ -0,0 +1,34 @@
+//! xorshift for the tests, enough to scatter points and shapes around reproducibly
+use crate::vec::{Vec2, Vec3, Vec4};
+use crate::{vec2, vec3};
+
+pub(crate) struct Rng(pub(crate) u64);
+impl Rng {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+    /// In `0..1`, from the top 24 bits
+    pub(crate) fn unit(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+    pub(crate) fn range(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.unit()
+    }
+    /// In `-bound..=bound`
+    #[cfg(feature = "alloc")]
+    pub(crate) fn int(&mut self, bound: i64) -> i64 {
+        (self.next() % (2 * bound as u64 + 1)) as i64 - bound
+    }
+    pub(crate) fn vec2(&mut self, lo: f32, hi: f32) -> Vec2 {
+        vec2!(self.range(lo, hi), self.range(lo, hi))
+    }
+    pub(crate) fn vec3(&mut self, lo: f32, hi: f32) -> Vec3 {
+        vec3!(self.range(lo, hi), self.range(lo, hi), self.range(lo, hi))
+    }
+    pub(crate) fn vec4(&mut self, lo: f32, hi: f32) -> Vec4 {
+        Vec4::new(self.range(lo, hi), self.range(lo, hi), self.range(lo, hi), self.range(lo, hi))
+    }
+}
@@ -1,9 +1,10 @@
 use core::f32;
-use std::ops::{Add,Div,Sub, Mul};
-use std::f32::consts::PI;
+use core::ops::{Add,Div,Sub, Mul};
+use core::f32::consts::PI;
 
-use super::quat::Quaternion;
-use super::matrix::Matrix;
+use super::math;
+use super::quat::{DQuaternion, Quaternion};
+use super::matrix::{DMatrix, Matrix};
 
 #[macro_export]
 macro_rules! ivec2 {
@@ -12,7 +13,9 @@ macro_rules! ivec2 {
     };
 }
 
-#[derive(Debug,Clone, Copy)]
+#[derive(Debug,Clone, Copy,PartialEq,Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct IVec2 {
     pub x: i32,
     pub y: i32,
@@ -30,8 +33,8 @@ impl Add<IVec2> for IVec2 {
 
     fn add(self, rhs: IVec2) -> Self::Output {
         IVec2 {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
         }
     }
 }
@@ -52,7 +55,9 @@ macro_rules! vec2 {
         Vec2 { x: $x, y: $y }
     };
 }
-#[derive(Copy,Clone,Debug)]
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct Vec2 {
     pub x: f32,
     pub y: f32,
@@ -64,15 +69,26 @@ impl Vec2 {
     pub fn cross(&self, rhs: Self) -> f32 {
         self.x * rhs.y - self.y * rhs.x
     }
+    pub fn dot(&self, rhs: Self) -> f32 {
+        self.x * rhs.x + self.y * rhs.y
+    }
     pub fn mag(&self) -> f32 {
         let x = self.x;
         let y = self.y;
 
-        f32::sqrt(x*x + y*y)
+        math::sqrtf(x*x + y*y)
     }
     pub fn norm(&self) -> Vec2 {
         *self / self.mag()
     }
+    /// Component wise minimum
+    pub fn min(&self, rhs: Vec2) -> Vec2 {
+        vec2!(self.x.min(rhs.x), self.y.min(rhs.y))
+    }
+    /// Component wise maximum
+    pub fn max(&self, rhs: Vec2) -> Vec2 {
+        vec2!(self.x.max(rhs.x), self.y.max(rhs.y))
+    }
 }
 impl Div<f32> for Vec2 {
     type Output = Vec2;
@@ -114,6 +130,16 @@ impl Add<Vec2> for Vec2 {
         }
     }
 }
+impl Sub<Vec2> for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Vec2) -> Self::Output {
+        Vec2 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
 #[macro_export]
 macro_rules! ivec3 {
     ($x:expr,$y:expr,$z:expr) => {
@@ -121,6 +147,8 @@ macro_rules! ivec3 {
     };
 }
 #[derive(Debug,Copy,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct IVec3 {
     pub x: i32,
     pub y: i32,
@@ -222,11 +250,11 @@ impl Div<i32> for IVec3 {
         }
     }
 }
-impl Into<Vec3> for IVec3 {
-    fn into(self) -> Vec3 {
-        Vec3 { x: self.x as f32, 
-               y: self.y as f32, 
-               z: self.z as f32
+impl From<IVec3> for Vec3 {
+    fn from(val: IVec3) -> Self {
+        Vec3 { x: val.x as f32, 
+               y: val.y as f32, 
+               z: val.z as f32
         }
     }
 }
@@ -237,11 +265,13 @@ macro_rules! vec3 {
         Vec3::new($x,$y,$z)
     };
     ($val:expr) => {
-        Vec3::new($val,$val,$val);
+        Vec3::new($val,$val,$val)
     }
 }
 
 #[derive(Debug,Copy,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -269,7 +299,7 @@ impl Vec3 {
         let y = self.y;
         let z = self.z;
 
-        f32::sqrt(x*x + y*y + z*z)
+        math::sqrtf(x*x + y*y + z*z)
     }
     pub fn cross(&self, rhs: Self) -> Self {
         Self {
@@ -279,7 +309,7 @@ impl Vec3 {
         }
     }
     pub fn dot(&self, rhs: Self) -> f32 {
-        return self.x * rhs.x + self.y * rhs.y + self.z * rhs.z;
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
     }
     pub fn with_x(&self, x: f32) -> Self {
         Vec3 { x, y: self.y, z: self.z }  
@@ -293,7 +323,7 @@ impl Vec3 {
     /// takes in a deg and a normalized axis vector 
     pub fn rot_quat(&mut self, deg: f32, axis: Vec3) {
         let deg_rad = deg / 180. * PI;
-        let rot_quat = Quaternion::new(f32::cos(deg_rad/2.),axis * f32::sin(deg_rad/2.));
+        let rot_quat = Quaternion::new(math::cosf(deg_rad/2.),axis * math::sinf(deg_rad/2.));
         let vec_quat = Quaternion::new(0.,*self);
         let out_vec_quat = rot_quat * vec_quat * rot_quat.conjugate();
         *self = vec3!(out_vec_quat.v.x,out_vec_quat.v.y,out_vec_quat.v.z);
@@ -304,6 +334,17 @@ impl Vec3 {
     pub fn to_vec4(&self,w:f32) -> Vec4 {
         Vec4::new(self.x, self.y, self.z, w)
     }
+    pub fn to_array(&self) -> [f32;3] {
+        [self.x, self.y, self.z]
+    }
+    /// Component wise minimum
+    pub fn min(&self, rhs: Vec3) -> Vec3 {
+        vec3!(self.x.min(rhs.x), self.y.min(rhs.y), self.z.min(rhs.z))
+    }
+    /// Component wise maximum
+    pub fn max(&self, rhs: Vec3) -> Vec3 {
+        vec3!(self.x.max(rhs.x), self.y.max(rhs.y), self.z.max(rhs.z))
+    }
 }
 impl Add<Vec3> for Vec3 {
     type Output = Vec3;
@@ -412,7 +453,9 @@ macro_rules! vec4 {
     };
 }
 
-#[derive(Debug,Copy,Clone)]
+#[derive(Debug,Copy,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct Vec4 {
     pub x: f32,
     pub y: f32,
@@ -461,6 +504,20 @@ impl Div<f32> for Vec4 {
         }
     }
 }
+impl Add<Vec4> for Vec4 {
+    type Output = Vec4;
+
+    fn add(self, rhs: Vec4) -> Self::Output {
+        Vec4::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z, self.w + rhs.w)
+    }
+}
+impl Sub<Vec4> for Vec4 {
+    type Output = Vec4;
+
+    fn sub(self, rhs: Vec4) -> Self::Output {
+        Vec4::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z, self.w - rhs.w)
+    }
+}
 
 impl Mul<Vec4> for Matrix<4, 4> {
     type Output = Vec4;
@@ -475,3 +532,408 @@ impl Mul<Vec4> for Matrix<4, 4> {
     }
 }
 
+// ------------------- Double precision -----------------------------
+
+#[macro_export]
+macro_rules! dvec2 {
+    ($x:expr,$y:expr) => {
+        DVec2 { x: $x, y: $y }
+    };
+}
+#[derive(Copy,Clone,Debug,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct DVec2 {
+    pub x: f64,
+    pub y: f64,
+}
+impl DVec2 {
+    pub fn new(x: f64, y: f64) -> Self {
+        DVec2 { x, y }
+    }
+    pub fn cross(&self, rhs: Self) -> f64 {
+        self.x * rhs.y - self.y * rhs.x
+    }
+    pub fn dot(&self, rhs: Self) -> f64 {
+        self.x * rhs.x + self.y * rhs.y
+    }
+    pub fn mag(&self) -> f64 {
+        math::sqrt(self.x * self.x + self.y * self.y)
+    }
+    pub fn norm(&self) -> DVec2 {
+        *self / self.mag()
+    }
+    /// Component wise minimum
+    pub fn min(&self, rhs: DVec2) -> DVec2 {
+        dvec2!(self.x.min(rhs.x), self.y.min(rhs.y))
+    }
+    /// Component wise maximum
+    pub fn max(&self, rhs: DVec2) -> DVec2 {
+        dvec2!(self.x.max(rhs.x), self.y.max(rhs.y))
+    }
+    /// Rounds every component to the nearest `f32`
+    pub fn as_vec2(&self) -> Vec2 {
+        Vec2::new(self.x as f32, self.y as f32)
+    }
+}
+impl From<Vec2> for DVec2 {
+    fn from(v: Vec2) -> Self {
+        DVec2::new(v.x.into(), v.y.into())
+    }
+}
+impl From<IVec2> for DVec2 {
+    fn from(v: IVec2) -> Self {
+        DVec2::new(v.x.into(), v.y.into())
+    }
+}
+impl Div<f64> for DVec2 {
+    type Output = DVec2;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        DVec2 {
+            x: self.x / rhs,
+            y: self.y / rhs,
+        }
+    }
+}
+impl Mul<f64> for DVec2 {
+    type Output = DVec2;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        DVec2 {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+impl Mul<DVec2> for f64 {
+    type Output = DVec2;
+
+    fn mul(self, rhs: DVec2) -> Self::Output {
+        rhs * self
+    }
+}
+impl Add<DVec2> for DVec2 {
+    type Output = DVec2;
+
+    fn add(self, rhs: DVec2) -> Self::Output {
+        DVec2 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+impl Sub<DVec2> for DVec2 {
+    type Output = DVec2;
+
+    fn sub(self, rhs: DVec2) -> Self::Output {
+        DVec2 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! dvec3 {
+    ($x:expr,$y:expr,$z:expr) => {
+        DVec3::new($x,$y,$z)
+    };
+    ($val:expr) => {
+        DVec3::new($val,$val,$val)
+    }
+}
+#[derive(Debug,Copy,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct DVec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+impl DVec3 {
+    pub const UP:DVec3 = DVec3 { x: 0. , y: 1. , z: 0. };
+    pub const ZERO:DVec3 = DVec3 { x: 0. , y: 0. , z: 0. };
+
+    pub const X:    DVec3 = DVec3 { x: 1. , y: 0. , z: 0. };
+    pub const NEG_X:DVec3 = DVec3 { x: -1. , y: 0. , z: 0. };
+    pub const Y:    DVec3 = DVec3 { x: 0. , y: 1. , z: 0. };
+    pub const NEG_Y:DVec3 = DVec3 { x: 0. , y: -1. , z: 0. };
+    pub const Z:    DVec3 = DVec3 { x: 0. , y: 0. , z: 1. };
+    pub const NEG_Z:DVec3 = DVec3 { x: 0. , y: 0. , z: -1. };
+
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+    pub fn from_slice(slice: [f64;3]) -> DVec3 {
+        DVec3::new(slice[0], slice[1], slice[2])
+    }
+    pub fn mag(&self) -> f64 {
+        math::sqrt(self.dot(*self))
+    }
+    pub fn cross(&self, rhs: Self) -> Self {
+        Self {
+            x: self.y * rhs.z - self.z * rhs.y,
+            y: self.z * rhs.x - self.x * rhs.z,
+            z: self.x * rhs.y - self.y * rhs.x,
+        }
+    }
+    pub fn dot(&self, rhs: Self) -> f64 {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+    pub fn with_x(&self, x: f64) -> Self {
+        DVec3 { x, y: self.y, z: self.z }
+    }
+    pub fn with_y(&self, y: f64) -> Self {
+        DVec3 { x: self.x , y, z: self.z }
+    }
+    pub fn with_z(&self, z: f64) -> Self {
+        DVec3 { x: self.x , y: self.y, z }
+    }
+    /// takes in a deg and a normalized axis vector
+    pub fn rot_quat(&mut self, deg: f64, axis: DVec3) {
+        let deg_rad = deg.to_radians();
+        let rot_quat = DQuaternion::new(math::cos(deg_rad/2.),axis * math::sin(deg_rad/2.));
+        let vec_quat = DQuaternion::new(0.,*self);
+        *self = (rot_quat * vec_quat * rot_quat.conjugate()).xyz();
+    }
+    pub fn norm(&self) -> DVec3 {
+        *self / self.mag()
+    }
+    pub fn to_dvec4(&self,w:f64) -> DVec4 {
+        DVec4::new(self.x, self.y, self.z, w)
+    }
+    /// Rounds every component to the nearest `f32`
+    pub fn as_vec3(&self) -> Vec3 {
+        Vec3::new(self.x as f32, self.y as f32, self.z as f32)
+    }
+}
+impl From<Vec3> for DVec3 {
+    fn from(v: Vec3) -> Self {
+        DVec3::new(v.x.into(), v.y.into(), v.z.into())
+    }
+}
+impl From<IVec3> for DVec3 {
+    fn from(v: IVec3) -> Self {
+        DVec3::new(v.x.into(), v.y.into(), v.z.into())
+    }
+}
+impl Add<DVec3> for DVec3 {
+    type Output = DVec3;
+
+    fn add(self, rhs: DVec3) -> Self::Output {
+        DVec3 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+impl Sub<DVec3> for DVec3 {
+    type Output = DVec3;
+
+    fn sub(self, rhs: DVec3) -> Self::Output {
+        DVec3 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+impl Div<DVec3> for f64 {
+    type Output = DVec3;
+
+    fn div(self, rhs: DVec3) -> Self::Output {
+        DVec3 {
+            x: self / rhs.x,
+            y: self / rhs.y,
+            z: self / rhs.z,
+        }
+    }
+}
+impl Div<f64> for DVec3 {
+    type Output = DVec3;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        DVec3 {
+            x: self.x / rhs,
+            y: self.y / rhs,
+            z: self.z / rhs,
+        }
+    }
+}
+impl Add<f64> for DVec3 {
+    type Output = DVec3;
+
+    fn add(self, rhs: f64) -> Self::Output {
+        DVec3 {
+            x: self.x + rhs,
+            y: self.y + rhs,
+            z: self.z + rhs,
+        }
+    }
+}
+impl Mul<f64> for DVec3 {
+    type Output = DVec3;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        DVec3 {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+        }
+    }
+}
+impl Mul<DVec3> for DVec3 {
+    type Output = DVec3;
+
+    fn mul(self, rhs: DVec3) -> Self::Output {
+        DVec3 {
+            x: self.x * rhs.x,
+            y: self.y * rhs.y,
+            z: self.z * rhs.z,
+        }
+    }
+}
+impl Mul<DVec3> for f64 {
+    type Output = DVec3;
+
+    fn mul(self, rhs: DVec3) -> Self::Output {
+        rhs * self
+    }
+}
+impl Mul<DVec3> for DMatrix<3, 3> {
+    type Output = DVec3;
+
+    fn mul(self, rhs: DVec3) -> Self::Output {
+        DVec3 {
+            x: self[0][0] * rhs.x + self[0][1] * rhs.y + self[0][2] * rhs.z,
+            y: self[1][0] * rhs.x + self[1][1] * rhs.y + self[1][2] * rhs.z,
+            z: self[2][0] * rhs.x + self[2][1] * rhs.y + self[2][2] * rhs.z,
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! dvec4 {
+    ($x:expr,$y:expr,$z:expr,$w:expr) => {
+        DVec4::new($x,$y,$z,$w)
+    };
+}
+#[derive(Debug,Copy,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct DVec4 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+impl DVec4 {
+    pub fn new(x: f64, y: f64, z: f64, w: f64) -> Self {
+        DVec4 { x, y, z, w }
+    }
+    pub fn to_dvec3(&self) -> DVec3 {
+        DVec3 { x: self.x, y: self.y, z: self.z }
+    }
+    /// Missing components are 1, same as [`Vec4::from_slice`]
+    pub fn from_slice(slice: &[f64]) -> DVec4 {
+        match slice.len() {
+            1 => DVec4::new(slice[0], 1., 1., 1.),
+            2 => DVec4::new(slice[0], slice[1], 1., 1.),
+            3 => DVec4::new(slice[0], slice[1], slice[2], 1.),
+            4 => DVec4::new(slice[0], slice[1], slice[2], slice[3]),
+            _ => DVec4::new(1., 1., 1., 1.),
+        }
+    }
+    /// Rounds every component to the nearest `f32`
+    pub fn as_vec4(&self) -> Vec4 {
+        Vec4::new(self.x as f32, self.y as f32, self.z as f32, self.w as f32)
+    }
+}
+impl From<Vec4> for DVec4 {
+    fn from(v: Vec4) -> Self {
+        DVec4::new(v.x.into(), v.y.into(), v.z.into(), v.w.into())
+    }
+}
+impl Mul<f64> for DVec4 {
+    type Output = DVec4;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+            z: self.z * rhs,
+            w: self.w * rhs,
+        }
+    }
+}
+impl Div<f64> for DVec4 {
+    type Output = DVec4;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Self {
+            x: self.x / rhs,
+            y: self.y / rhs,
+            z: self.z / rhs,
+            w: self.w / rhs,
+        }
+    }
+}
+impl Add<DVec4> for DVec4 {
+    type Output = DVec4;
+
+    fn add(self, rhs: DVec4) -> Self::Output {
+        DVec4::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z, self.w + rhs.w)
+    }
+}
+impl Sub<DVec4> for DVec4 {
+    type Output = DVec4;
+
+    fn sub(self, rhs: DVec4) -> Self::Output {
+        DVec4::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z, self.w - rhs.w)
+    }
+}
+impl Mul<DVec4> for DMatrix<4, 4> {
+    type Output = DVec4;
+
+    fn mul(self, rhs: DVec4) -> Self::Output {
+        DVec4 {
+            x: self[0][0] * rhs.x + self[0][1] * rhs.y + self[0][2] * rhs.z + self[0][3] * rhs.w,
+            y: self[1][0] * rhs.x + self[1][1] * rhs.y + self[1][2] * rhs.z + self[1][3] * rhs.w,
+            z: self[2][0] * rhs.x + self[2][1] * rhs.y + self[2][2] * rhs.z + self[2][3] * rhs.w,
+            w: self[3][0] * rhs.x + self[3][1] * rhs.y + self[3][2] * rhs.z + self[3][3] * rhs.w,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ivec2_add() {
+        let sum = IVec2::new(3, -4) + IVec2::new(5, 7);
+        assert_eq!((sum.x, sum.y), (8, 3));
+        let sum = IVec2::new(-2, 0) + IVec2::new(-2, 1);
+        assert_eq!((sum.x, sum.y), (-4, 1));
+    }
+
+    #[test]
+    fn dvec2_ops() {
+        let (a, b) = (dvec2!(1., -2.), dvec2!(3., 0.5));
+        assert_eq!(a - b, dvec2!(-2., -2.5));
+        assert_eq!(a.dot(b), 2.);
+        assert_eq!((a.min(b), a.max(b)), (dvec2!(1., -2.), dvec2!(3., 0.5)));
+        assert_eq!(DVec2::from(Vec2::new(0.5, 2.)), dvec2!(0.5, 2.));
+    }
+
+    #[test]
+    fn dvec4_ops() {
+        let (a, b) = (dvec4!(1., 2., 3., 4.), dvec4!(0.5, -1., 0., 2.));
+        assert_eq!(a + b, dvec4!(1.5, 1., 3., 6.));
+        assert_eq!(a - b, dvec4!(0.5, 3., 3., 2.));
+        assert_eq!(DVec4::from(vec4!(1., 2., 3., 4.)), a);
+        assert_eq!((a - b).as_vec4(), vec4!(1., 2., 3., 4.) - vec4!(0.5, -1., 0., 2.));
+    }
+}